@@ -0,0 +1,59 @@
+use std::{sync::Arc, thread};
+
+use fsm::{AtomicFSM, AtomicState, DefineTransform};
+
+// States with `#[repr(u8)]` are stored by their discriminants
+#[derive(Clone, Copy, Debug, PartialEq, AtomicState)]
+#[repr(u8)]
+enum Connection {
+    Closed = 1,
+    Connecting = 2,
+    Open = 4,
+}
+
+#[derive(Clone, Copy)]
+enum Event {
+    Dial,
+    Connected,
+    Hangup,
+}
+
+DefineTransform!(connection, Connection, Event,
+    (Connection::Closed, Event::Dial) => Connection::Connecting,
+    (Connection::Connecting, Event::Connected) => Connection::Open,
+    (_, Event::Hangup) => Connection::Closed,
+    (state, _) => state,
+);
+
+fn main() {
+    let machine = Arc::new(AtomicFSM::new(Connection::Closed, connection));
+
+    // Many threads try to dial, but only one finds the connection closed
+    let threads = (0..4)
+        .map(|_| {
+            let machine = Arc::clone(&machine);
+            thread::spawn(move || machine.apply_if(Connection::Closed, Event::Dial).is_ok())
+        })
+        .collect::<Vec<_>>();
+    let dialled = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .filter(|&dialled| dialled)
+        .count();
+    assert_eq!(dialled, 1);
+
+    assert_eq!(
+        machine.apply(Event::Connected),
+        (Connection::Connecting, Connection::Open)
+    );
+    assert_eq!(
+        machine.apply(Event::Hangup),
+        (Connection::Open, Connection::Closed)
+    );
+    assert_eq!(Connection::Open.into_u8(), 4);
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,92 @@
+use fsm::{union, ClassifyStates, DefineTransform, FSM};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Keyword {
+    If,
+    In,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Int,
+    Float,
+    Keyword(Keyword),
+}
+use Kind::*;
+
+#[derive(Clone, Copy, Default, ClassifyStates)]
+#[class_type(Kind)]
+enum Number {
+    #[default]
+    Empty,
+    #[accept(Int)]
+    Integer,
+    Point,
+    #[accept(Float)]
+    Fraction,
+    #[trap]
+    Invalid,
+}
+
+DefineTransform!(number, Number, char,
+    (Number::Empty | Number::Integer, '0'..='9') => Number::Integer,
+    (Number::Integer, '.') => Number::Point,
+    (Number::Point | Number::Fraction, '0'..='9') => Number::Fraction,
+    (_, _) => Number::Invalid,
+);
+
+#[derive(Clone, Copy, Default, ClassifyStates)]
+#[class_type(Kind)]
+enum Keywords {
+    #[default]
+    Empty,
+    I,
+    #[accept(Keyword(Keyword::If))]
+    If,
+    #[accept(Keyword(Keyword::In))]
+    In,
+    #[trap]
+    Invalid,
+}
+
+DefineTransform!(keyword, Keywords, char,
+    (Keywords::Empty, 'i') => Keywords::I,
+    (Keywords::I, 'f') => Keywords::If,
+    (Keywords::I, 'n') => Keywords::In,
+    (_, _) => Keywords::Invalid,
+);
+
+fn main() {
+    let tests = [
+        ("", None),
+        ("12", Some(Int)),
+        ("12.", None),
+        ("12.5", Some(Float)),
+        ("if", Some(Keyword(Keyword::If))),
+        ("in", Some(Keyword(Keyword::In))),
+        ("inf", None),
+    ];
+
+    for (test, expected) in tests {
+        let machine = union::default_with_transforms_copy(number, keyword).run(test.chars());
+
+        // Each test is only accepted by one of the machines, so take whichever class is present
+        let class = machine.state().class().map(|class| match class {
+            union::Class::A(class) | union::Class::B(class) => class,
+            union::Class::Both(..) => unreachable!(),
+        });
+
+        println!("{test:?} => {class:?}");
+        assert_eq!(class, expected);
+        assert_eq!(machine.is_accepted(), expected.is_some());
+    }
+
+    let machine = FSM::new(Number::Empty, number).run("3.".chars());
+    assert!(machine.state().class().is_none());
+    assert!(machine.run("14".chars()).state().class() == Some(Float));
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,98 @@
+use fsm::{
+    debug::{Breakpoint, Debugger, Error, Stop},
+    AcceptStates, DefineTransform, Finite, FSM,
+};
+
+// A vending machine, that vends after two coins
+#[derive(Clone, Copy, Debug, PartialEq, AcceptStates, Finite)]
+enum States {
+    #[accept]
+    Idle,
+    Paid,
+    PaidTwice,
+}
+
+#[derive(Clone, Copy, Debug, Finite)]
+enum Inputs {
+    Coin,
+    Vend,
+    Refund,
+}
+
+DefineTransform!(vending, States, Inputs,
+    (States::Idle, Inputs::Coin) => States::Paid,
+    (States::Paid, Inputs::Coin) => States::PaidTwice,
+    (States::PaidTwice, Inputs::Vend) | (_, Inputs::Refund) => States::Idle,
+    (state, _) => state,
+);
+
+fn main() {
+    let mut debugger = Debugger::new(FSM::new(States::Idle, vending));
+
+    // Drive the debugger directly
+    debugger
+        .add_breakpoint(Breakpoint::Transition {
+            from: "PaidTwice".to_owned(),
+            to: "Idle".to_owned(),
+        })
+        .unwrap();
+    assert_eq!(
+        debugger.run([
+            Inputs::Coin,
+            Inputs::Vend,
+            Inputs::Coin,
+            Inputs::Vend,
+            Inputs::Coin
+        ]),
+        Ok(Stop::Breakpoint {
+            breakpoint: Breakpoint::Transition {
+                from: "PaidTwice".to_owned(),
+                to: "Idle".to_owned(),
+            },
+            remaining: 1,
+        })
+    );
+    assert_eq!(debugger.machine().state(), &States::Idle);
+
+    assert!(debugger.undo());
+    assert_eq!(debugger.machine().state(), &States::PaidTwice);
+
+    // Or with commands, naming the inputs
+    assert_eq!(
+        debugger.execute("state").unwrap(),
+        "PaidTwice\n  Coin -> PaidTwice\n  Vend -> Idle\n  Refund -> Idle"
+    );
+    assert_eq!(
+        debugger.execute("step Refund").unwrap(),
+        "PaidTwice --Refund--> Idle\nstopped at breakpoint PaidTwice -> Idle"
+    );
+    assert_eq!(
+        debugger.execute("trace").unwrap(),
+        "\
+| # | from      | input  | to        |
+|---|-----------|--------|-----------|
+| 0 | Idle      | Coin   | Paid      |
+| 1 | Paid      | Vend   | Paid      |
+| 2 | Paid      | Coin   | PaidTwice |
+| 3 | PaidTwice | Refund | Idle      |"
+    );
+
+    assert_eq!(
+        debugger.execute("step Kick"),
+        Err(Error::InvalidInput("Kick".to_owned()))
+    );
+    assert_eq!(
+        debugger.execute("break Broken"),
+        Err(Error::UnknownState("Broken".to_owned()))
+    );
+    assert_eq!(
+        debugger.execute("delete PaidTwice -> Idle").unwrap(),
+        "deleted breakpoint PaidTwice -> Idle"
+    );
+    assert_eq!(debugger.execute("breakpoints").unwrap(), "no breakpoints");
+}
+
+#[test]
+fn test() {
+    main()
+}
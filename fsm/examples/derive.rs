@@ -9,10 +9,58 @@ enum States {
     Q2,
 }
 
+// Variants with fields can be marked, and guards can use the fields
+#[derive(AcceptStates)]
+enum Counter<'a, T>
+where
+    T: PartialEq,
+{
+    #[accept(if = "*count >= 3")]
+    Counting { count: u8 },
+    #[accept(if = "_0 == _1")]
+    Matching(&'a T, &'a T),
+    #[accept]
+    Done,
+    #[trap]
+    Overflowed,
+}
+
+// Structs use a function
+#[derive(AcceptStates)]
+#[accept(with = Balance::is_settled)]
+#[trap(with = Balance::is_overdrawn)]
+struct Balance(i32);
+
+impl Balance {
+    const fn is_settled(&self) -> bool {
+        self.0 == 0
+    }
+
+    const fn is_overdrawn(&self) -> bool {
+        self.0 < -100
+    }
+}
+
 fn main() {
     assert!(!States::Q0.is_accepted());
     assert!(States::Q1.is_accepted());
     assert!(States::Q2.is_accepted());
+
+    let two: Counter<char> = Counter::Counting { count: 2 };
+    let three: Counter<char> = Counter::Counting { count: 3 };
+    let overflowed: Counter<char> = Counter::Overflowed;
+    assert!(!two.is_accepted());
+    assert!(three.is_accepted());
+    assert!(Counter::Matching(&'a', &'a').is_accepted());
+    assert!(!Counter::Matching(&'a', &'b').is_accepted());
+    assert!(Counter::<char>::Done.is_accepted());
+    assert!(overflowed.is_trap());
+    assert!(!three.is_trap());
+
+    assert!(Balance(0).is_accepted());
+    assert!(!Balance(-50).is_accepted());
+    assert!(!Balance(-50).is_trap());
+    assert!(Balance(-150).is_trap());
 }
 
 #[test]
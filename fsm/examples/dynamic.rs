@@ -0,0 +1,102 @@
+use fsm::{
+    dynamic::{Builder, Error},
+    DynFSM,
+};
+
+// A ticket workflow from configuration, with one transition per line
+const WORKFLOW: &str = "
+    open -> assign -> assigned
+    assigned -> resolve -> resolved
+    assigned -> unassign -> open
+    resolved -> reopen -> open
+    resolved -> close -> closed
+    closed -> reopen -> open
+";
+
+fn load(config: &str) -> Builder<String> {
+    let mut builder = DynFSM::builder().set_start("open").set_accepting("closed");
+
+    let mut states = Vec::new();
+    for line in config.lines().filter(|line| !line.trim().is_empty()) {
+        let mut parts = line.split("->").map(str::trim);
+        let [from, input, to] = [(); 3].map(|()| parts.next().unwrap());
+
+        if !states.contains(&from) {
+            states.push(from);
+            builder = builder.add_state(from);
+        }
+        builder = builder.add_transition(from, input.to_owned(), to);
+    }
+
+    builder
+}
+
+fn main() {
+    // Every state ignores the inputs it has no transition for
+    let mut builder = load(WORKFLOW);
+    for state in ["open", "assigned", "resolved", "closed"] {
+        builder = builder.add_otherwise(state, state);
+    }
+
+    let mut machine = builder.build().unwrap();
+    assert_eq!(machine.states(), ["open", "assigned", "resolved", "closed"]);
+    assert_eq!(machine.inputs().len(), 5);
+
+    machine.run_assign(["assign", "resolve", "close"].map(String::from));
+    assert_eq!(machine.state(), "closed");
+    assert!(machine.is_accepted());
+
+    let machine = machine.apply("assign".to_owned());
+    assert_eq!(machine.state(), "closed");
+    assert!(!machine.reset().is_accepted());
+
+    // Without `otherwise` transitions, every problem is reported
+    let builder = load(WORKFLOW).add_transition("open", "assign".to_owned(), "resolved");
+    let errors = builder.validate();
+    assert!(errors.contains(&Error::Nondeterministic {
+        state: "open".to_owned(),
+        input: Some("assign".to_owned()),
+    }));
+    assert!(errors.contains(&Error::MissingTransition {
+        state: "open".to_owned(),
+        input: "resolve".to_owned(),
+    }));
+
+    // States that can't be reached are found once the machine is complete
+    let builder = DynFSM::<char>::builder()
+        .add_state("a")
+        .add_state("b")
+        .set_start("a")
+        .add_otherwise("a", "a")
+        .add_otherwise("b", "a");
+    assert_eq!(
+        builder.validate(),
+        [Error::UnreachableState("b".to_owned())]
+    );
+
+    let error = builder.build().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the state `b` can't be reached from the start state"
+    );
+
+    // Inputs without a transition can be rejected instead of panicking
+    let mut machine = DynFSM::builder()
+        .add_state("a")
+        .set_start("a")
+        .add_transition("a", 'x', "a")
+        .build()
+        .unwrap();
+    assert_eq!(
+        machine.try_apply_assign('y'),
+        Err(Error::UnknownInput {
+            state: "a".to_owned(),
+            input: 'y'
+        })
+    );
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,71 @@
+use fsm::{fsm, AcceptStates, Finite, FSM};
+
+fsm! {
+    /// The states of a parser for signed integers, such as `-12`.
+    #[derive(Hash)]
+    enum Integer {
+        #[start]
+        Empty,
+        Sign,
+        #[accept]
+        Digits,
+        Invalid,
+    }
+
+    /// The classes of characters.
+    enum Class {
+        Sign,
+        Digit,
+        Other,
+    }
+
+    /// Parse the next character.
+    fn integer;
+
+    (Empty, Sign) => Sign,
+    (Empty | Sign | Digits, Digit) => Digits,
+    // Anything else is invalid
+    (_, _) => Invalid,
+}
+
+fn classify(character: char) -> Class {
+    match character {
+        '+' | '-' => Class::Sign,
+        '0'..='9' => Class::Digit,
+        _ => Class::Other,
+    }
+}
+
+fn accepts(text: &str) -> bool {
+    FSM::default_with_transform(integer)
+        .run(text.chars().map(classify))
+        .is_accepted()
+}
+
+fn main() {
+    assert!(accepts("-12"));
+    assert!(accepts("7"));
+    assert!(!accepts("+"));
+    assert!(!accepts("1-2"));
+
+    // The start state is the default, and the trap states are worked out from the transitions
+    assert_eq!(Integer::default(), Integer::Empty);
+    assert!(Integer::Invalid.is_trap());
+    assert!(!Integer::Sign.is_trap());
+
+    // The transitions are also available as a table, by state then input
+    assert_eq!(Integer::VALUES.len(), 4);
+    assert_eq!(
+        Integer::TRANSITIONS[Integer::Sign.index()][Class::Digit.index()],
+        Integer::Digits
+    );
+
+    // The transform function is `const`
+    const AFTER_SIGN: Integer = integer(Integer::Empty, Class::Sign);
+    assert_eq!(AFTER_SIGN, Integer::Sign);
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,97 @@
+use fsm::{hierarchy::HierarchicalFSM, DefineHierarchicalTransform, DefineHierarchy, Hooks};
+
+// A device controller, where `PowerOff` and `Fault` apply in every powered state
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum States {
+    Off,
+    Powered,
+    Standby,
+    Active,
+    Heating,
+    Cooling,
+    Error,
+}
+use States::*;
+
+#[derive(Debug)]
+enum Inputs {
+    PowerOn,
+    PowerOff,
+    Wake,
+    Sleep,
+    TooHot,
+    TooCold,
+    Fault,
+}
+
+DefineHierarchy!(States,
+    Powered => [Standby, Active, Error],
+    Active => [Heating, Cooling],
+);
+
+DefineHierarchicalTransform!(controller, States, Inputs,
+    (Off, Inputs::PowerOn) => Powered,
+    (Powered, Inputs::PowerOff) => Off,
+    (Powered, Inputs::Fault) => Error,
+    (Standby, Inputs::Wake) => Active,
+    (Active, Inputs::Sleep) => Standby,
+    (Heating, Inputs::TooHot) => Cooling,
+    (Cooling, Inputs::TooCold) => Heating,
+);
+
+// Logs the states entered and left
+#[derive(Default)]
+struct Log(Vec<String>);
+
+impl Hooks<Inputs, States> for Log {
+    fn on_exit(&mut self, state: &States) {
+        self.0.push(format!("exit {state:?}"));
+    }
+
+    fn on_transition(&mut self, from: &States, input: &Inputs, to: &States) {
+        println!("{from:?} --{input:?}-> {to:?}");
+    }
+
+    fn on_enter(&mut self, state: &States) {
+        self.0.push(format!("enter {state:?}"));
+    }
+}
+
+fn main() {
+    let mut log = Log::default();
+    let mut machine = HierarchicalFSM::start_with_hooks(Off, controller, &mut log);
+    assert_eq!(log.0, ["enter Off"]);
+
+    machine.run_with_hooks([Inputs::PowerOn, Inputs::Wake, Inputs::TooHot], &mut log);
+    assert_eq!(machine.state(), &Cooling);
+    assert!(machine.is_in(&Active) && machine.is_in(&Powered));
+
+    machine.run([Inputs::TooCold, Inputs::Sleep, Inputs::Wake]);
+    assert_eq!(machine.state(), &Heating);
+
+    // `Fault` is handled by `Powered`, and transitions are external, so `Powered` is left and re-entered
+    log.0.clear();
+    assert!(machine.apply_assign_with_hooks(Inputs::Fault, &mut log));
+    assert_eq!(
+        log.0,
+        [
+            "exit Heating",
+            "exit Active",
+            "exit Powered",
+            "enter Powered",
+            "enter Error"
+        ]
+    );
+
+    // Nothing in `Error` or `Powered` handles `Wake`
+    assert!(!machine.apply_assign(Inputs::Wake));
+
+    log.0.clear();
+    machine.apply_assign_with_hooks(Inputs::PowerOff, &mut log);
+    assert_eq!(log.0, ["exit Error", "exit Powered", "enter Off"]);
+}
+
+#[test]
+fn test() {
+    main()
+}
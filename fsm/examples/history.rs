@@ -0,0 +1,93 @@
+use fsm::{
+    hierarchy::{HierarchicalFSM, Memory},
+    DefineHierarchicalTransform, DefineHierarchy, Hooks,
+};
+
+// A media player that returns to where it was after an interruption
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum States {
+    Playing,
+    Menu,
+    Settings,
+    Audio,
+    Video,
+    Library,
+    Interrupted,
+    // History states of `Menu`
+    Back,
+    BackExactly,
+}
+use States::*;
+
+#[derive(Debug)]
+enum Inputs {
+    Open,
+    Close,
+    Reopen,
+    Call,
+    Hangup,
+    Next,
+}
+
+DefineHierarchy!(States,
+    // The first time `Back` is entered, `Library` is entered instead of `Settings`
+    Menu => [Settings, Library; Shallow(Back => Library), Deep(BackExactly)],
+    Settings => [Audio, Video],
+);
+
+DefineHierarchicalTransform!(player, States, Inputs,
+    (Playing, Inputs::Open) => Back,
+    (Playing, Inputs::Reopen) => BackExactly,
+    (Menu, Inputs::Close) => Playing,
+    (Menu, Inputs::Call) => Interrupted,
+    (Interrupted, Inputs::Hangup) => BackExactly,
+    (Library, Inputs::Next) => Settings,
+    (Audio, Inputs::Next) => Video,
+);
+
+// Logs the states entered
+#[derive(Default)]
+struct Log(Vec<String>);
+
+impl Hooks<Inputs, States> for Log {
+    fn on_enter(&mut self, state: &States) {
+        self.0.push(format!("enter {state:?}"));
+    }
+}
+
+fn main() {
+    let mut machine = HierarchicalFSM::new(Playing, player).with_memory(Vec::new());
+
+    // `Menu` has never been exited, so `Back` enters its default
+    machine.apply_assign(Inputs::Open);
+    assert_eq!(machine.state(), &Library);
+
+    machine.run([Inputs::Next, Inputs::Next, Inputs::Close]);
+    assert_eq!(machine.memory().recall(&Menu), Some(Video));
+    assert_eq!(machine.memory().recall(&Settings), Some(Video));
+
+    // Shallow history restores `Settings`, but enters its initial child
+    let mut log = Log::default();
+    machine.apply_assign_with_hooks(Inputs::Open, &mut log);
+    assert_eq!(machine.state(), &Audio);
+    assert_eq!(log.0, ["enter Menu", "enter Settings", "enter Audio"]);
+
+    // Deep history restores every active state, and is never entered itself
+    machine.run([Inputs::Next, Inputs::Call]);
+    assert_eq!(machine.state(), &Interrupted);
+
+    log.0.clear();
+    machine.apply_assign_with_hooks(Inputs::Hangup, &mut log);
+    assert_eq!(machine.state(), &Video);
+    assert_eq!(log.0, ["enter Menu", "enter Settings", "enter Video"]);
+
+    // Without memory, history states always enter their default
+    let mut machine = HierarchicalFSM::new(Playing, player);
+    machine.run([Inputs::Open, Inputs::Next, Inputs::Close, Inputs::Reopen]);
+    assert_eq!(machine.state(), &Audio);
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -1,12 +1,13 @@
 use fsm::{AcceptStates, DefineTransform, FSM};
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, AcceptStates)]
 enum States {
     #[default]
     Empty,
     InString,
     Special,
     Unicode(u8),
+    #[accept]
     End,
     Invalid,
 }
@@ -29,12 +30,6 @@ DefineTransform!(validate_json_char, States, char,
     (_, _) => Invalid,
 );
 
-impl AcceptStates for States {
-    fn is_accepted(&self) -> bool {
-        self == &End
-    }
-}
-
 fn main() {
     let tests = vec![
         (r#""#, false),
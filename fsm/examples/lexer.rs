@@ -0,0 +1,102 @@
+use fsm::{
+    lexer::{self, Rule},
+    AcceptStates, DefineTransform, FSM,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Int,
+    Float,
+    Plus,
+    Space,
+}
+
+#[derive(Clone, Copy, Default, AcceptStates)]
+enum Number {
+    #[default]
+    Empty,
+    #[accept]
+    Int,
+    Point,
+    #[accept]
+    Fraction,
+    #[trap]
+    Invalid,
+}
+
+DefineTransform!(int, Number, char,
+    (Number::Empty | Number::Int, '0'..='9') => Number::Int,
+    (_, _) => Number::Invalid,
+);
+
+DefineTransform!(float, Number, char,
+    (Number::Empty | Number::Int, '0'..='9') => Number::Int,
+    (Number::Int, '.') => Number::Point,
+    (Number::Point | Number::Fraction, '0'..='9') => Number::Fraction,
+    (_, _) => Number::Invalid,
+);
+
+#[derive(Clone, Copy, Default, AcceptStates)]
+enum Single {
+    #[default]
+    Empty,
+    #[accept]
+    Matched,
+    #[trap]
+    Invalid,
+}
+
+DefineTransform!(plus, Single, char,
+    (Single::Empty, '+') => Single::Matched,
+    (_, _) => Single::Invalid,
+);
+
+DefineTransform!(space, Single, char,
+    (Single::Empty | Single::Matched, ' ') => Single::Matched,
+    (_, _) => Single::Invalid,
+);
+
+fn main() {
+    let int = FSM::new(Number::Empty, int);
+    // The float machine also accepts integers, but the int rule is listed first, so it wins ties
+    let float = FSM::new(Number::Empty, float);
+    let plus = FSM::new(Single::Empty, plus);
+    let space = FSM::new(Single::Empty, space);
+
+    let rules = [
+        Rule::new(Kind::Int, &int),
+        Rule::new(Kind::Float, &float),
+        Rule::new(Kind::Plus, &plus),
+        Rule::new(Kind::Space, &space),
+    ];
+
+    const INPUT: &str = "12 + 3.25 ? 4.";
+    let expected = [
+        Ok((Kind::Int, "12")),
+        Ok((Kind::Space, " ")),
+        Ok((Kind::Plus, "+")),
+        Ok((Kind::Space, " ")),
+        Ok((Kind::Float, "3.25")),
+        Ok((Kind::Space, " ")),
+        Err("?"),
+        Ok((Kind::Space, " ")),
+        Ok((Kind::Int, "4")),
+        Err("."),
+    ];
+
+    let tokens = lexer::str(&rules, INPUT).map(|result| {
+        result
+            .map(|token| (*token.kind(), &INPUT[token.span()]))
+            .map_err(|error| &INPUT[error.span()])
+    });
+
+    for (token, expected) in tokens.zip(expected) {
+        println!("{token:?}");
+        assert_eq!(token, expected);
+    }
+}
+
+#[test]
+fn test() {
+    main()
+}
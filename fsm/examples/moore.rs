@@ -0,0 +1,82 @@
+use fsm::{intersection, DefineTransform, MooreMachine, StateOutput};
+
+// A traffic light controller, with a pedestrian crossing light
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Lamp {
+    Red,
+    RedAmber,
+    Green,
+    Amber,
+}
+
+#[derive(Clone, Copy, Default, StateOutput)]
+#[output_type(Lamp)]
+enum Traffic {
+    #[default]
+    #[output(Lamp::Red)]
+    Stop,
+    #[output(Lamp::RedAmber)]
+    Ready,
+    #[output(Lamp::Green)]
+    Go,
+    #[output(Lamp::Amber)]
+    Slow,
+}
+
+#[derive(Clone, Copy)]
+struct Tick;
+
+DefineTransform!(traffic, Traffic, Tick,
+    (Traffic::Stop, Tick) => Traffic::Ready,
+    (Traffic::Ready, Tick) => Traffic::Go,
+    (Traffic::Go, Tick) => Traffic::Slow,
+    (Traffic::Slow, Tick) => Traffic::Stop,
+);
+
+#[derive(Clone, Copy, Default, StateOutput)]
+#[output_type(bool)]
+enum Crossing {
+    #[default]
+    #[output = true]
+    Walk,
+    #[output = false]
+    Wait(u8),
+}
+
+DefineTransform!(crossing, Crossing, Tick,
+    (Crossing::Walk, Tick) => Crossing::Wait(0),
+    (Crossing::Wait(2), Tick) => Crossing::Walk,
+    (Crossing::Wait(n), Tick) => Crossing::Wait(n + 1),
+);
+
+fn main() {
+    let mut lights = MooreMachine::default_with_transform(traffic);
+    assert_eq!(lights.output(), Lamp::Red);
+
+    let outputs: Vec<_> = lights.run_outputs([Tick; 4]).collect();
+    println!("{outputs:?}");
+    assert_eq!(
+        outputs,
+        [Lamp::RedAmber, Lamp::Green, Lamp::Amber, Lamp::Red]
+    );
+
+    // Run both machines together, giving a pair of outputs
+    let mut junction = MooreMachine::from(intersection::default_with_transforms_copy(
+        traffic, crossing,
+    ));
+    assert_eq!(junction.output(), (Lamp::Red, true));
+
+    for output in junction.run_outputs([Tick; 4]) {
+        println!("{output:?}");
+        let (lamp, walk) = output;
+        assert_eq!(walk, lamp == Lamp::Red);
+    }
+
+    assert_eq!(junction.state().output(), (Lamp::Red, true));
+}
+
+#[test]
+fn test() {
+    main()
+}
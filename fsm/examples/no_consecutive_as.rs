@@ -46,7 +46,7 @@ fn main() {
         // Create a new FSM using the default state
         let mut machine = FSM::default_with_transform(no_2_as);
         // Run the inputs
-        machine = machine.run(inputs.into_iter());
+        machine = machine.run(inputs);
 
         // Check if the end state is a 'final' state
         let output = matches!(machine.state(), Q::Q0 | Q::Q1);
@@ -0,0 +1,115 @@
+use fsm::{
+    table::{Table, TableFSM},
+    union, AcceptStates, DefineTransform, Finite, RegisterTransforms, FSM,
+};
+use serde::{Deserialize, Serialize};
+
+// An order workflow, which is saved between process restarts
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, AcceptStates, Finite)]
+enum Order {
+    Placed,
+    Paid,
+    #[accept]
+    Shipped,
+    Cancelled,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Finite)]
+enum Event {
+    Pay,
+    Ship,
+    Cancel,
+}
+
+DefineTransform!(order, Order, Event,
+    (Order::Placed, Event::Pay) => Order::Paid,
+    (Order::Paid, Event::Ship) => Order::Shipped,
+    (Order::Placed | Order::Paid, Event::Cancel) => Order::Cancelled,
+    (state, _) => state,
+);
+
+// Orders placed before a change to the workflow keep running the old one
+DefineTransform!(order_v1, Order, Event,
+    (Order::Placed, Event::Pay) => Order::Paid,
+    (Order::Paid, Event::Ship) => Order::Shipped,
+    (state, _) => state,
+);
+
+RegisterTransforms!(Order, Event, order as "order.v2", order_v1 as "order.v1");
+
+// Counts the events, up to three
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct Count(u8);
+
+DefineTransform!(count, Count, Event,
+    (Count(n), _) => Count((n + 1).min(3)),
+);
+
+RegisterTransforms!(Count, Event, count);
+
+impl AcceptStates for Count {
+    fn is_accepted(&self) -> bool {
+        self.0 == 3
+    }
+}
+
+fn main() {
+    // Machines are saved with the name of their transform function
+    let machine = FSM::new(Order::Placed, order).apply(Event::Pay);
+    let saved = serde_json::to_string(&machine).unwrap();
+    assert_eq!(saved, r#"{"transform":"order.v2","state":"Paid"}"#);
+
+    let mut restored: FSM<Event, Order> = serde_json::from_str(&saved).unwrap();
+    assert_eq!(restored, machine);
+    restored.apply_assign(Event::Cancel);
+    assert_eq!(restored.state(), &Order::Cancelled);
+
+    // An old order can't be cancelled
+    let mut old: FSM<Event, Order> =
+        serde_json::from_str(r#"{"transform":"order.v1","state":"Paid"}"#).unwrap();
+    old.apply_assign(Event::Cancel);
+    assert_eq!(old.state(), &Order::Paid);
+
+    // Unknown names are rejected
+    let error =
+        serde_json::from_str::<FSM<Event, Order>>(r#"{"transform":"order.v3","state":"Paid"}"#)
+            .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("the name of a registered transform function"));
+
+    // Union states are saved with both machines
+    let counted =
+        union::new_copy(Order::Placed, Count(0), order, count).run([Event::Pay, Event::Ship]);
+    let saved = serde_json::to_string(counted.state()).unwrap();
+    assert_eq!(
+        saved,
+        r#"{"a":{"transform":"order.v2","state":"Shipped"},"b":{"transform":"count","state":2}}"#
+    );
+
+    let restored = union::from_state_copy(serde_json::from_str(&saved).unwrap());
+    assert!(restored.is_accepted());
+    assert_eq!(restored.apply(Event::Pay), counted.apply(Event::Pay));
+
+    // Table-driven machines are saved in full
+    let mut machine = TableFSM::new(Table::from_fsm(&FSM::new(Order::Placed, order)).unwrap());
+    machine.run(["Pay", "Ship"]).unwrap();
+
+    let saved = serde_json::to_value(&machine).unwrap();
+    assert_eq!(saved["state"], "Shipped");
+    assert_eq!(saved["table"]["accept"], serde_json::json!(["Shipped"]));
+
+    let restored: TableFSM = serde_json::from_value(saved.clone()).unwrap();
+    assert_eq!(restored, machine);
+
+    // The table is checked when it is restored
+    let mut invalid = saved;
+    invalid["state"] = "Lost".into();
+    let error = serde_json::from_value::<TableFSM>(invalid).unwrap_err();
+    assert_eq!(error.to_string(), "there is no state named `Lost`");
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,105 @@
+use fsm::{
+    hierarchy::HierarchicalFSM,
+    regions::{Join, Orthogonal, Parallel},
+    AcceptStates, DefineHierarchicalTransform, DefineHierarchy, DefineTransform, FSM,
+};
+
+// A course, where the lab and the project are completed in parallel
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Course {
+    Enrolled,
+    Studying,
+    Passed,
+    Failed,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Event {
+    Start,
+    Lab,
+    Draft,
+    Submit,
+    Fail,
+    Retake,
+}
+
+// There are no nested states, but `Course` must still implement `Hierarchy`
+DefineHierarchy!(Course,);
+
+DefineHierarchicalTransform!(course, Course, Event,
+    (Course::Enrolled, Event::Start) => Course::Studying,
+    (Course::Studying, Event::Fail) => Course::Failed,
+    (Course::Failed, Event::Retake) => Course::Studying,
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, AcceptStates)]
+enum Lab {
+    Todo(u8),
+    #[accept]
+    Done,
+}
+
+DefineTransform!(lab, Lab, Event,
+    (Lab::Todo(1), Event::Lab) => Lab::Done,
+    (Lab::Todo(n), Event::Lab) => Lab::Todo(n - 1),
+    (lab, _) => lab,
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, AcceptStates)]
+enum Project {
+    Todo,
+    Drafted,
+    #[accept]
+    Submitted,
+}
+
+DefineTransform!(project, Project, Event,
+    (Project::Todo, Event::Draft) => Project::Drafted,
+    (Project::Drafted, Event::Submit) => Project::Submitted,
+    (project, _) => project,
+);
+
+fn main() {
+    let regions = Parallel::new(
+        (
+            FSM::new(Lab::Todo(2), lab),
+            FSM::new(Project::Todo, project),
+        ),
+        Join::All,
+    );
+
+    let mut machine = Orthogonal::new(
+        HierarchicalFSM::new(Course::Enrolled, course),
+        Course::Studying,
+        regions,
+        Course::Passed,
+    );
+
+    machine.run([Event::Start, Event::Lab, Event::Draft]);
+    assert!(machine.in_regions());
+    assert_eq!(machine.regions().regions().0.state(), &Lab::Todo(1));
+
+    // No region handles `Fail`, so it is passed to the course
+    assert!(machine.dispatch(&Event::Fail));
+    assert_eq!(machine.state(), &Course::Failed);
+    assert!(!machine.in_regions());
+
+    // The regions are reset when the course is retaken
+    machine.dispatch(&Event::Retake);
+    assert_eq!(machine.regions().regions().0.state(), &Lab::Todo(2));
+
+    machine.run([Event::Lab, Event::Draft, Event::Submit]);
+    assert_eq!(machine.state(), &Course::Studying);
+    assert!(!machine.regions().is_joined());
+
+    // Both regions finish, so the course is passed
+    machine.dispatch(&Event::Lab);
+    assert_eq!(machine.state(), &Course::Passed);
+    assert!(!machine.in_regions());
+    println!("{:?}", machine.state());
+}
+
+#[test]
+fn test() {
+    main()
+}
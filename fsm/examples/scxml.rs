@@ -0,0 +1,80 @@
+use fsm::scxml::{Error, Interpreter, Statechart};
+
+// A microwave, exported from a statechart design tool
+const MICROWAVE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<scxml xmlns="http://www.w3.org/2005/07/scxml" version="1.0" name="microwave" initial="closed">
+  <state id="closed" initial="idle">
+    <transition event="door.open" target="open"/>
+    <state id="idle">
+      <transition event="start" target="cooking"/>
+    </state>
+    <parallel id="cooking">
+      <transition event="done.state.cooking" target="idle">
+        <raise event="beep"/>
+      </transition>
+      <state id="timer">
+        <state id="counting">
+          <transition event="tick" target="elapsed"/>
+        </state>
+        <final id="elapsed"/>
+      </state>
+      <state id="light">
+        <state id="lit">
+          <transition event="tick" target="dark"/>
+        </state>
+        <final id="dark"/>
+      </state>
+    </parallel>
+    <!-- Shut the door to carry on from where the microwave was -->
+    <history id="resume" type="deep">
+      <transition target="idle"/>
+    </history>
+  </state>
+  <state id="open">
+    <transition event="door.close" target="resume"/>
+  </state>
+</scxml>
+"#;
+
+fn main() {
+    let chart = Statechart::parse(MICROWAVE).unwrap();
+    assert_eq!(chart.name(), Some("microwave"));
+    assert_eq!(chart.states().count(), 10);
+
+    let mut microwave = Interpreter::new(chart).unwrap();
+    assert!(microwave.is_active("idle"));
+
+    microwave.send("start").unwrap();
+    assert_eq!(
+        microwave.configuration().collect::<Vec<_>>(),
+        ["closed", "cooking", "timer", "counting", "light", "lit"]
+    );
+
+    // Opening the door records the active states, which are restored when it is shut
+    microwave.run(["door.open", "door.close"]).unwrap();
+    assert!(microwave.is_active("counting") && microwave.is_active("lit"));
+
+    // When both regions are final, `done.state.cooking` is raised
+    microwave.send("tick").unwrap();
+    assert!(microwave.is_active("idle"));
+
+    // Unsupported features are reported when parsing
+    let error =
+        Statechart::parse(r#"<scxml><state><script>count++</script></state></scxml>"#).unwrap_err();
+    assert_eq!(
+        error,
+        Error::Unsupported("the `<script>` element".to_owned())
+    );
+    assert_eq!(error.to_string(), "the `<script>` element is not supported");
+
+    let error = Statechart::parse("<scxml>\n  <state id=\"a\">\n</scxml>").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid XML at line 3, column 1: mismatched closing tag"
+    );
+}
+
+#[test]
+fn test() {
+    main()
+}
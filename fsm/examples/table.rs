@@ -0,0 +1,113 @@
+use std::fmt;
+
+use fsm::{
+    scxml::{Interpreter, Statechart},
+    table::{Error, Table, TableFSM},
+    AcceptStates, DefineTransform, Finite, FSM,
+};
+
+// A turnstile, which is accepting while it is locked
+#[derive(Clone, Copy, Debug, PartialEq, AcceptStates, Finite)]
+enum States {
+    #[accept]
+    Locked,
+    Unlocked,
+    Broken,
+}
+
+#[derive(Clone, Copy, Debug, Finite)]
+enum Inputs {
+    Coin,
+    Push,
+    Kick,
+}
+
+DefineTransform!(turnstile, States, Inputs,
+    (States::Broken, _) | (_, Inputs::Kick) => States::Broken,
+    (_, Inputs::Coin) => States::Unlocked,
+    (_, Inputs::Push) => States::Locked,
+);
+
+// A machine whose states all have the same `Debug` name
+#[derive(Clone, Copy, PartialEq, AcceptStates, Finite)]
+enum Light {
+    Off,
+    On,
+}
+
+impl fmt::Debug for Light {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Light")
+    }
+}
+
+DefineTransform!(light, Light, bool,
+    (_, on) => if on { Light::On } else { Light::Off },
+);
+
+fn main() {
+    let mut machine = FSM::new(States::Locked, turnstile);
+    let table = Table::from_fsm(&machine).unwrap();
+    assert_eq!(table.states(), ["Locked", "Unlocked", "Broken"]);
+    assert_eq!(table.inputs(), ["Coin", "Push", "Kick"]);
+
+    // Round trip through JSON, and compare with the original machine
+    let json = table.json().to_string();
+    assert!(json.contains(r#"{"from": "Locked", "input": "Coin", "to": "Unlocked"}"#));
+
+    let mut imported = TableFSM::new(Table::from_json(&json).unwrap());
+    assert_eq!(imported.table(), &table);
+
+    for input in [Inputs::Coin, Inputs::Push, Inputs::Coin, Inputs::Kick] {
+        machine.apply_assign(input);
+        imported.apply_assign(&format!("{input:?}")).unwrap();
+
+        assert_eq!(imported.state(), format!("{:?}", machine.state()));
+        assert_eq!(imported.is_accepted(), machine.is_accepted());
+    }
+
+    // The SCXML export runs in the statechart interpreter
+    let scxml = table.scxml().to_string();
+    assert!(scxml.contains(r#"<state id="Locked" fsm:accept="true">"#));
+    assert!(scxml.contains(r#"<transition event="Coin Push Kick" target="Broken"/>"#));
+
+    let mut interpreter = Interpreter::new(Statechart::parse(&scxml).unwrap()).unwrap();
+    interpreter.run(["Coin", "Push", "Push"]).unwrap();
+    assert!(interpreter.is_active("Locked"));
+    interpreter.run(["Kick", "Coin"]).unwrap();
+    assert!(interpreter.is_active("Broken"));
+
+    // Imported tables are checked against the schema
+    let error = Table::from_json(
+        r#"{"states": ["A"], "inputs": ["x"], "start": "A", "accept": [], "transitions": []}"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error,
+        Error::MissingTransition {
+            state: "A".to_owned(),
+            input: "x".to_owned()
+        }
+    );
+    assert_eq!(error.to_string(), "there is no transition from `A` for `x`");
+
+    let error = Table::from_json(
+        r#"{"states": ["A", "A"], "inputs": [], "start": "A", "accept": [], "transitions": []}"#,
+    )
+    .unwrap_err();
+    assert_eq!(error, Error::DuplicateName("A".to_owned()));
+
+    // Exported names come from `Debug`, so must be unique too
+    let error = Table::from_fsm(&FSM::new(Light::Off, light)).unwrap_err();
+    assert_eq!(error, Error::DuplicateName("Light".to_owned()));
+
+    assert!(matches!(
+        Table::from_json("{\n  \"states\": [\"A\",]\n}"),
+        Err(Error::Json { line: 2, .. })
+    ));
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,94 @@
+use fsm::{dynamic, text};
+
+// Recognises quoted strings, with `\"` escapes, written by someone who doesn't use Rust
+const QUOTED: &str = r#"
+start Start
+accept Closed
+
+// The opening quote
+Start '"' -> Open
+Open '\\' -> Escape
+Open '"' -> Closed
+Open '\n' -> Invalid
+Open _ -> Open
+Escape _ -> Open
+
+// Anything after the closing quote is invalid
+Start | Closed | Invalid _ -> Invalid
+"#;
+
+fn main() {
+    let machine = text::parse(QUOTED).unwrap();
+    assert_eq!(
+        machine.states(),
+        ["Start", "Closed", "Open", "Escape", "Invalid"]
+    );
+
+    let accepts = |input: &str| machine.clone().run(input.chars()).is_accepted();
+    assert!(accepts(r#""hello""#));
+    assert!(accepts(r#""say \"hi\"""#));
+    assert!(!accepts(r#""unterminated"#));
+    assert!(!accepts("\"two\nlines\""));
+
+    // The formatter writes the machine back out, combining characters and leaving out
+    // transitions covered by the wildcard
+    let formatted = text::display(&machine).to_string();
+    assert_eq!(
+        formatted,
+        r#"states Start, Closed, Open, Escape, Invalid
+start Start
+accept Closed
+Start '"' -> Open
+Start _ -> Invalid
+Closed _ -> Invalid
+Open '"' -> Closed
+Open '\\' -> Escape
+Open '\n' -> Invalid
+Open _ -> Open
+Escape _ -> Open
+Invalid _ -> Invalid
+"#
+    );
+    assert_eq!(
+        text::display(&text::parse(&formatted).unwrap()).to_string(),
+        formatted
+    );
+
+    // Consecutive characters are combined into ranges
+    let digits = text::parse("start A\nA '0' | '1' | '2' | '3' | '5' -> A\nA | B _ -> B").unwrap();
+    assert!(text::display(&digits)
+        .to_string()
+        .contains("A '0'..='3' | '5' -> A"));
+
+    // Errors are reported with their line and column
+    let error = text::parse("start A\nA 'a'..='Z' -> A").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "syntax error at line 2, column 9: the range is empty"
+    );
+
+    let error = text::parse("start A\n\nA '\\q' -> A").unwrap_err();
+    assert_eq!(
+        error,
+        text::Error::Syntax {
+            line: 3,
+            column: 4,
+            message: "invalid escape"
+        }
+    );
+
+    // As are problems with the machine
+    let error = text::parse("start A\nA 'a' -> B\nA 'a' -> A\nB _ -> A").unwrap_err();
+    assert_eq!(
+        error,
+        text::Error::Machine(dynamic::Error::Nondeterministic {
+            state: "A".to_owned(),
+            input: Some('a'),
+        })
+    );
+}
+
+#[test]
+fn test() {
+    main()
+}
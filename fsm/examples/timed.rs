@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use fsm::{
+    timed::{Clock, ManualClock, StdClock},
+    DefineTransform, TimedFSM, FSM,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Link {
+    Idle,
+    Connecting,
+    Up,
+    Probing,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Packet {
+    Hello,
+    Ack,
+    Data,
+}
+
+DefineTransform!(link, Link, Packet,
+    (Link::Idle, Packet::Hello) => Link::Connecting,
+    (Link::Connecting | Link::Probing, Packet::Ack) => Link::Up,
+    (Link::Up | Link::Probing, Packet::Data) => Link::Up,
+    (state, _) => state,
+);
+
+// Give up connecting after 5s, probe after 30s of silence, and go idle if the probe gets no reply in 5s
+fn timeout(state: &Link) -> Option<(Duration, Link)> {
+    match state {
+        Link::Connecting | Link::Probing => Some((Duration::from_secs(5), Link::Idle)),
+        Link::Up => Some((Duration::from_secs(30), Link::Probing)),
+        Link::Idle => None,
+    }
+}
+
+// The same timeouts, in ticks of 100ms
+fn timeout_ticks(state: &Link) -> Option<(u64, Link)> {
+    timeout(state).map(|(duration, target)| {
+        let ticks = u64::try_from(duration.as_millis() / 100).unwrap();
+        (ticks, target)
+    })
+}
+
+fn main() {
+    // With the system clock, polling at times in the future
+    let mut machine = TimedFSM::new(FSM::new(Link::Idle, link), timeout, StdClock);
+    assert_eq!(machine.next_deadline(), None);
+
+    machine.run([Packet::Hello, Packet::Ack]);
+    let deadline = machine.next_deadline().unwrap();
+    assert_eq!(machine.poll(deadline - Duration::from_secs(1)), 0);
+    assert_eq!(machine.poll(deadline), 1);
+    assert_eq!(machine.state(), &Link::Probing);
+    println!("{machine:?}");
+
+    // With a manual clock, for tests or ticks from a hardware timer
+    let clock = ManualClock::new(0);
+    let mut machine = TimedFSM::new(FSM::new(Link::Connecting, link), timeout_ticks, &clock);
+
+    clock.advance(49);
+    assert_eq!(machine.update(), 0);
+    machine.apply(Packet::Ack);
+    assert_eq!(machine.next_deadline(), Some(349));
+
+    // Data keeps the link up
+    clock.set(300);
+    machine.apply(Packet::Data);
+    assert_eq!(machine.next_deadline(), Some(600));
+
+    // Silence, then the probe times out
+    clock.set(700);
+    assert_eq!(machine.update(), 2);
+    assert_eq!(machine.state(), &Link::Idle);
+    assert_eq!(clock.now(), 700);
+
+    // Inputs are applied after the timeouts that have expired, so this `Ack` arrives too late
+    machine.apply(Packet::Hello);
+    clock.advance(60);
+    machine.apply(Packet::Ack);
+    assert_eq!(machine.state(), &Link::Idle);
+
+    // Zero timeouts round a cycle don't hang polling, which stops when the cycle is found
+    let mut machine = TimedFSM::new(
+        FSM::new(Link::Up, link),
+        |state| match state {
+            Link::Up => Some((0, Link::Probing)),
+            Link::Probing => Some((0, Link::Up)),
+            _ => None,
+        },
+        &clock,
+    );
+    assert!(machine.update() > 0);
+    assert_eq!(machine.next_deadline(), Some(clock.now()));
+}
+
+#[test]
+fn test() {
+    main()
+}
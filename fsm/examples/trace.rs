@@ -0,0 +1,62 @@
+use fsm::{trace, DefineTransform, FSM};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Door {
+    #[default]
+    Closed,
+    Open,
+    Locked,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Open,
+    Close,
+    Lock,
+    Unlock,
+}
+
+DefineTransform!(door, Door, Action,
+    (Door::Closed, Action::Open) => Door::Open,
+    (Door::Closed, Action::Lock) => Door::Locked,
+    (Door::Open, Action::Close) => Door::Closed,
+    (Door::Locked, Action::Unlock) => Door::Closed,
+    (state, _) => state,
+);
+
+// A new version of the door, that can be locked while open
+DefineTransform!(door_v2, Door, Action,
+    (Door::Closed, Action::Open) => Door::Open,
+    (Door::Closed | Door::Open, Action::Lock) => Door::Locked,
+    (Door::Open, Action::Close) => Door::Closed,
+    (Door::Locked, Action::Unlock) => Door::Closed,
+    (state, _) => state,
+);
+
+fn main() {
+    use Action::*;
+
+    let mut steps = Vec::new();
+    let machine = FSM::default_with_transform(door)
+        .run_traced([Lock, Open, Unlock, Open, Close, Open, Lock], &mut steps);
+    assert_eq!(machine.state(), &Door::Open);
+    assert_eq!(steps.len(), 7);
+
+    print!("{}", trace::Table::new(&steps));
+
+    // Find where the new version would have behaved differently
+    let divergence = trace::replay(&steps, door_v2).expect("the versions differ");
+    println!(
+        "door_v2 diverges at step {}, moving to {:?} instead of {:?}",
+        divergence.step().index(),
+        divergence.state(),
+        divergence.step().to(),
+    );
+    assert_eq!(divergence.step().index(), 6);
+    assert_eq!(divergence.state(), &Door::Locked);
+}
+
+#[test]
+fn test() {
+    main()
+}
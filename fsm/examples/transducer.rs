@@ -0,0 +1,66 @@
+use fsm::{DefineTransducer, Transducer};
+
+// A turnstile that reports the action to take for each input
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Turnstile {
+    #[default]
+    Locked,
+    Unlocked,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Input {
+    Coin,
+    Push,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    Unlock,
+    Lock,
+    Refund,
+    Alarm,
+}
+
+DefineTransducer!(turnstile, Turnstile, Input, Action,
+    (Turnstile::Locked, Input::Coin) => (Turnstile::Unlocked, Action::Unlock),
+    (Turnstile::Locked, Input::Push) => (Turnstile::Locked, Action::Alarm),
+    (Turnstile::Unlocked, Input::Coin) => (Turnstile::Unlocked, Action::Refund),
+    (Turnstile::Unlocked, Input::Push) => (Turnstile::Locked, Action::Lock),
+);
+
+// Counts the alarms, and calls security on every third one
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Alarms(u8);
+
+DefineTransducer!(security, Alarms, Action, bool,
+    (Alarms(2), Action::Alarm) => (Alarms(0), true),
+    (Alarms(n), Action::Alarm) => (Alarms(n + 1), false),
+    (alarms, _) => (alarms, false),
+);
+
+fn main() {
+    use Input::*;
+
+    let mut machine = Transducer::default_with_transform(turnstile);
+    let actions: Vec<_> = machine.run([Coin, Coin, Push, Push]).collect();
+    println!("{actions:?}");
+    assert_eq!(
+        actions,
+        [Action::Unlock, Action::Refund, Action::Lock, Action::Alarm]
+    );
+    assert_eq!(machine.state(), &Turnstile::Locked);
+
+    // Feed the actions of the turnstile into the security counter
+    let mut machine = Transducer::default_with_transform(turnstile)
+        .then(Transducer::default_with_transform(security));
+    let calls: Vec<_> = machine.run([Push, Push, Coin, Push, Push]).collect();
+    println!("{calls:?}");
+    assert_eq!(calls, [false, false, false, false, true]);
+    assert_eq!(machine.state().second().state(), &Alarms(0));
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,65 @@
+use fsm::{Transitions, FSM};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Transitions)]
+#[input_type(Inputs)]
+// Inputs without a transition leave the state as it is
+#[default_transition(Self)]
+enum States {
+    #[default]
+    #[on(Next => S1)]
+    S0,
+    #[on(Next => S2, Prev => S0)]
+    S1,
+    #[on(Prev => S1, Reset => S0)]
+    S2,
+    // Every input returns to the first state
+    #[on(Next | Prev => Self)]
+    #[default_transition(S0)]
+    Paused,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Inputs {
+    Prev,
+    Next,
+    Reset,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Transitions)]
+#[input_type(u8)]
+enum Digits {
+    #[on(b'0'..=b'9' => Digits, _ => Invalid)]
+    Digits,
+    #[default_transition = "Self"]
+    Invalid,
+}
+
+fn main() {
+    use Inputs::*;
+
+    let machine = FSM::default_with_transform(States::transform).run([Next, Next, Next]);
+    assert_eq!(machine.state(), &States::S2);
+
+    let machine = FSM::new(States::S2, States::transform).run([Reset, Prev]);
+    assert_eq!(machine.state(), &States::S0);
+
+    let machine = FSM::new(States::Paused, States::transform).apply(Next);
+    assert_eq!(machine.state(), &States::Paused);
+    assert_eq!(machine.apply(Reset).state(), &States::S0);
+
+    // The transitions are listed, without the default transitions
+    assert_eq!(States::TRANSITIONS.len(), 7);
+    assert!(States::TRANSITIONS.contains(&(States::S1, Prev, States::S0)));
+    assert!(States::TRANSITIONS.contains(&(States::Paused, Prev, States::Paused)));
+
+    let machine = FSM::new(Digits::Digits, Digits::transform);
+    assert_eq!(machine.run(*b"123").state(), &Digits::Digits);
+    assert_eq!(machine.run(*b"1a3").state(), &Digits::Invalid);
+    // Ranges aren't listed
+    assert!(Digits::TRANSITIONS.is_empty());
+}
+
+#[test]
+fn test() {
+    main()
+}
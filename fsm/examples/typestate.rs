@@ -0,0 +1,58 @@
+use fsm::{Typestate, FSM};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Connection {
+    Closed,
+    Open,
+    Authenticated,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Event {
+    Connect,
+    LogIn,
+    LogOut,
+    Disconnect,
+}
+
+// Each arm naming variants gives a method, so `connection::Machine<connection::Closed>`
+// has `connect`, but no `log_in`. The last arm is only used at runtime.
+// Only the start state has a constructor, so the others are only reached by transitions.
+Typestate!(#[typestate(start = Connection::Closed)] connection, Connection, Event,
+    (Connection::Closed, Event::Connect) => Connection::Open,
+    (Connection::Open, Event::LogIn) => Connection::Authenticated,
+    (Connection::Authenticated, Event::LogOut) => Connection::Open,
+    (Connection::Open | Connection::Authenticated, Event::Disconnect) => Connection::Closed,
+    (state, _) => state,
+);
+
+fn main() {
+    // Invalid transitions, such as `log_in` before `connect`, fail to compile
+    let machine = connection::Machine::<connection::Closed>::new()
+        .connect()
+        .log_in();
+    assert_eq!(
+        connection::Machine::<connection::Authenticated>::STATE,
+        Connection::Authenticated
+    );
+
+    // Store the machine as a runtime machine, and apply inputs unknown at compile time
+    let stored: FSM<Event, Connection> = machine.into();
+    let stored = stored.run([
+        Event::LogIn,
+        Event::LogOut,
+        Event::Disconnect,
+        Event::Connect,
+    ]);
+    assert_eq!(stored.state(), &Connection::Open);
+
+    // Then take it back, checking its state
+    let stored = connection::Machine::<connection::Closed>::try_from(stored).unwrap_err();
+    let machine = connection::Machine::<connection::Open>::try_from(stored).unwrap();
+    let _closed: connection::Machine<connection::Closed> = machine.disconnect();
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -0,0 +1,56 @@
+use fsm::{DefineTransform, HistoryFSM, FSM};
+
+// A counter that wraps at 10
+DefineTransform!(counter, u8, bool,
+    (n, true) => (n + 1) % 10,
+    (n, false) => (n + 9) % 10,
+);
+
+fn main() {
+    let machine = FSM::new(0, counter);
+
+    // Keep the last 5 steps, either by storing every state,
+    // or by storing the inputs with a snapshot every 2 steps
+    let mut by_states = HistoryFSM::new(machine, 5);
+    let mut by_inputs = HistoryFSM::with_snapshots(machine, 5, 2);
+
+    for history in [&mut by_states, &mut by_inputs] {
+        history.run([true, true, true, false, true, true, true]);
+        assert_eq!(history.state(), &5);
+        assert_eq!(history.step(), 7);
+        assert_eq!(history.oldest_step(), 2);
+
+        // Undo as far as possible
+        let mut undone = 0;
+        while history.undo() {
+            undone += 1;
+        }
+        assert_eq!(undone, 5);
+        assert_eq!(history.state(), &2);
+
+        assert!(history.goto(4));
+        assert_eq!(history.state(), &2);
+        assert!(history.redo());
+        assert_eq!(history.state(), &3);
+
+        // Branch, discarding the redo history
+        history.apply(false);
+        assert_eq!(history.state(), &2);
+        assert!(!history.can_redo());
+        assert_eq!(history.latest_step(), 6);
+        assert!(!history.goto(7));
+
+        println!(
+            "steps {}..={}, currently at {} in state {}",
+            history.oldest_step(),
+            history.latest_step(),
+            history.step(),
+            history.state()
+        );
+    }
+}
+
+#[test]
+fn test() {
+    main()
+}
@@ -16,10 +16,11 @@ DefineTransform!(starts_with_a, StartsWithA, char,
     (StartsWithA::DidNotStartWithA, _) => StartsWithA::DidNotStartWithA,
 );
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, AcceptStates)]
 enum SameStartAndEnd {
     #[default]
     Empty,
+    #[accept]
     Same(char),
     Different(char),
 }
@@ -36,13 +37,6 @@ DefineTransform!(same_start_and_end, SameStartAndEnd, char,
     ) => SameStartAndEnd::Different(s),
 );
 
-impl AcceptStates for SameStartAndEnd {
-    #[inline]
-    fn is_accepted(&self) -> bool {
-        matches!(self, Self::Same(_))
-    }
-}
-
 fn main() {
     let machine = union::default_with_transforms_copy(starts_with_a, same_start_and_end);
 
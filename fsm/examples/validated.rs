@@ -0,0 +1,53 @@
+use fsm::{AcceptStates, Finite, ValidatedTransform, FSM};
+
+#[derive(Clone, Copy, Debug, PartialEq, AcceptStates, Finite)]
+enum Light {
+    #[accept]
+    Off,
+    On,
+    Blinking,
+}
+
+#[derive(Clone, Copy, Debug, Finite)]
+enum Switch {
+    Toggle,
+    Hold,
+}
+
+// Every arm matches some state and input, and every state can be reached,
+// so this compiles without warnings. A new arm added after the last one would
+// be shadowed by it, and fail the build.
+ValidatedTransform!(#[validate(deny)] light, Light, Switch,
+    (Light::Off, Switch::Toggle) => Light::On,
+    (Light::On | Light::Blinking, Switch::Toggle) => Light::Off,
+    (_, Switch::Hold) => Light::Blinking,
+);
+
+// A counter of odd bytes, where `Start` is only the start state
+#[derive(Clone, Copy, Debug, PartialEq, Finite)]
+enum Parity {
+    Start,
+    Even,
+    Odd,
+}
+
+ValidatedTransform!(#[validate(deny, start = Parity::Start)] parity, Parity, u8,
+    (Parity::Start | Parity::Even, n) if n % 2 == 1 => Parity::Odd,
+    (Parity::Odd, n) if n % 2 == 1 => Parity::Even,
+    (Parity::Start, _) => Parity::Even,
+    (state, _) => state,
+);
+
+fn main() {
+    let machine = FSM::new(Light::Off, light).run([Switch::Toggle, Switch::Hold]);
+    assert_eq!(machine.state(), &Light::Blinking);
+    assert!(!machine.is_accepted());
+
+    let machine = FSM::new(Parity::Start, parity).run([1, 2, 3]);
+    assert_eq!(machine.state(), &Parity::Even);
+}
+
+#[test]
+fn test() {
+    main()
+}
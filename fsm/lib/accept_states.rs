@@ -4,4 +4,14 @@ pub trait AcceptStates {
     #[must_use]
     /// Returns [`true`] if the state is marked as accepted.
     fn is_accepted(&self) -> bool;
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the state is a trap state.
+    /// A trap state is one from which no sequence of inputs can reach an accepted state.
+    ///
+    /// This defaults to [`false`] for every state.
+    fn is_trap(&self) -> bool {
+        false
+    }
 }
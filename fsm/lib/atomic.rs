@@ -0,0 +1,210 @@
+//! A machine that can be shared between threads, with its state in an atomic integer.
+//!
+//! # Examples
+//! ```
+//! use std::{sync::Arc, thread};
+//!
+//! use fsm::{AtomicFSM, DefineTransform, Finite};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, Finite)]
+//! enum Connection { Closed, Open, Authenticated }
+//!
+//! #[derive(Clone, Copy)]
+//! enum Event { Connect, LogIn, Disconnect }
+//!
+//! DefineTransform!(connection, Connection, Event,
+//!     (Connection::Closed, Event::Connect) => Connection::Open,
+//!     (Connection::Open, Event::LogIn) => Connection::Authenticated,
+//!     (_, Event::Disconnect) => Connection::Closed,
+//!     (state, _) => state,
+//! );
+//!
+//! let machine = Arc::new(AtomicFSM::new(Connection::Closed, connection));
+//!
+//! let other = Arc::clone(&machine);
+//! thread::spawn(move || other.apply(Event::Connect)).join().unwrap();
+//! assert_eq!(machine.state(), Connection::Open);
+//!
+//! // Only log in if no other thread has changed the state
+//! assert_eq!(
+//!     machine.apply_if(Connection::Open, Event::LogIn),
+//!     Ok(Connection::Authenticated)
+//! );
+//! assert_eq!(
+//!     machine.apply_if(Connection::Open, Event::Disconnect),
+//!     Err(Connection::Authenticated)
+//! );
+//! ```
+
+use core::marker::PhantomData;
+
+#[cfg(not(loom))]
+use core::sync::atomic::{AtomicU8, Ordering};
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{AcceptStates, Finite, FSM};
+
+/// This trait maps states to and from a [`u8`], so they can be stored in an [`AtomicFSM`].
+///
+/// This is implemented for types implementing [`Finite`] with at most 256 values, using their index,
+/// and can be derived for enums with `#[repr(u8)]`, using their discriminants.
+pub trait AtomicState: Copy {
+    #[must_use]
+    /// Returns the integer for the state.
+    fn into_u8(self) -> u8;
+
+    #[must_use]
+    /// Returns the state for an integer given by [`into_u8`](Self::into_u8).
+    ///
+    /// # Panics
+    /// This may panic if the integer was not given by [`into_u8`](Self::into_u8).
+    fn from_u8(value: u8) -> Self;
+}
+
+impl<States> AtomicState for States
+where
+    States: Finite + Copy,
+{
+    #[inline]
+    /// Returns the index of the state.
+    ///
+    /// Types with more than 256 values fail to compile when this is used.
+    fn into_u8(self) -> u8 {
+        const {
+            assert!(
+                Self::VALUES.len() <= 256,
+                "an `AtomicFSM` can have at most 256 states"
+            );
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let value = self.index() as u8;
+        value
+    }
+
+    #[inline]
+    fn from_u8(value: u8) -> Self {
+        Self::VALUES[usize::from(value)]
+    }
+}
+
+#[derive(Debug)]
+/// A Finite State Machine that can be shared between threads without a lock.
+///
+/// The state is stored in an atomic integer, through [`AtomicState`], and inputs are applied
+/// with a compare and swap loop. If another thread changes the state while an input is being applied,
+/// the transform function is run again on the new state, so it should not have side effects.
+pub struct AtomicFSM<Domain, States> {
+    state: AtomicU8,
+    transform: fn(States, Domain) -> States,
+    states: PhantomData<fn() -> States>,
+}
+
+impl<Domain, States> AtomicFSM<Domain, States>
+where
+    States: AtomicState,
+{
+    #[must_use]
+    #[inline]
+    /// Create a new atomic Finite State Machine starting with the `start_state` and with the `transform` function.
+    pub fn new(start_state: States, transform: fn(States, Domain) -> States) -> Self {
+        Self {
+            state: AtomicU8::new(start_state.into_u8()),
+            transform,
+            states: PhantomData,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current state.
+    pub fn state(&self) -> States {
+        States::from_u8(self.state.load(Ordering::Acquire))
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the transform function.
+    pub const fn transform_function(&self) -> fn(States, Domain) -> States {
+        self.transform
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is marked as accepted.
+    pub fn is_accepted(&self) -> bool
+    where
+        States: AcceptStates,
+    {
+        self.state().is_accepted()
+    }
+
+    /// Apply an input to the Finite State Machine, returning the old and new states.
+    ///
+    /// The old state is the one the input was applied to, which may not be the state
+    /// when this was called, if another thread changed it in between.
+    pub fn apply(&self, input: Domain) -> (States, States)
+    where
+        Domain: Clone,
+    {
+        let mut current = self.state.load(Ordering::Acquire);
+
+        loop {
+            let old = States::from_u8(current);
+            let new = (self.transform)(old, input.clone());
+
+            match self.state.compare_exchange_weak(
+                current,
+                new.into_u8(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return (old, new),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Apply an input to the Finite State Machine, only if it is in the `expected` state.
+    ///
+    /// # Errors
+    /// If the machine is in another state, it is not changed, and that state is returned.
+    pub fn apply_if(&self, expected: States, input: Domain) -> Result<States, States> {
+        let expected_value = expected.into_u8();
+        let new = (self.transform)(expected, input);
+
+        self.state
+            .compare_exchange(
+                expected_value,
+                new.into_u8(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .map(|_| new)
+            .map_err(States::from_u8)
+    }
+
+    #[inline]
+    /// Set the state, returning the old state.
+    pub fn swap(&self, state: States) -> States {
+        States::from_u8(self.state.swap(state.into_u8(), Ordering::AcqRel))
+    }
+
+    #[must_use]
+    #[inline]
+    /// Convert into a [`FSM`] in the current state.
+    pub fn into_fsm(self) -> FSM<Domain, States> {
+        FSM::new(self.state(), self.transform)
+    }
+}
+
+impl<Domain, States> From<FSM<Domain, States>> for AtomicFSM<Domain, States>
+where
+    States: AtomicState,
+{
+    #[inline]
+    fn from(machine: FSM<Domain, States>) -> Self {
+        Self::new(*machine.state(), machine.transform_function())
+    }
+}
@@ -0,0 +1,54 @@
+#[allow(clippy::module_name_repetitions)]
+/// This trait labels the accepted states of state sets for finite state machines with a class.
+///
+/// This is a richer form of [`AcceptStates`](crate::AcceptStates): rather than just reporting if a state is
+/// accepted, it reports what kind of accept state it is.
+/// The `ClassifyStates` derive macro implements both traits, keeping them consistent.
+/// A hand-written implementation can keep them consistent with [`is_classified`](Self::is_classified).
+///
+/// # Examples
+/// ```
+/// use fsm::{AcceptStates, ClassifyStates};
+///
+/// enum Token {
+///     Empty,
+///     Digits(u32),
+/// }
+///
+/// impl ClassifyStates for Token {
+///     type Class = u32;
+///
+///     fn class(&self) -> Option<u32> {
+///         match self {
+///             Self::Empty => None,
+///             Self::Digits(count) => Some(*count),
+///         }
+///     }
+/// }
+///
+/// impl AcceptStates for Token {
+///     fn is_accepted(&self) -> bool {
+///         self.is_classified()
+///     }
+/// }
+///
+/// assert!(Token::Digits(2).is_accepted());
+/// assert!(!Token::Empty.is_accepted());
+/// ```
+pub trait ClassifyStates {
+    /// The type that accept states are labelled with.
+    type Class;
+
+    #[must_use]
+    /// Returns the class of the state if it is an accept state, or [`None`] if it is not.
+    fn class(&self) -> Option<Self::Class>;
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the state has a class, so it is an accept state.
+    ///
+    /// This is meant for implementing [`AcceptStates::is_accepted`](crate::AcceptStates::is_accepted).
+    fn is_classified(&self) -> bool {
+        self.class().is_some()
+    }
+}
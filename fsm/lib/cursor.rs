@@ -0,0 +1,49 @@
+//! A cursor over text, shared by the parsers of the text, JSON and SCXML formats.
+
+#[derive(Clone, Copy, Debug)]
+/// A position in some text, which a parser moves forwards as it reads the text.
+pub struct Cursor<'a> {
+    text: &'a str,
+    /// The byte offset of the position.
+    pub position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Create a cursor at the start of the text.
+    pub const fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+
+    /// Get the text after the position.
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    /// Move past `prefix`, returning [`true`] if the rest of the text starts with it.
+    pub fn eat(&mut self, prefix: &str) -> bool {
+        let found = self.rest().starts_with(prefix);
+        if found {
+            self.position += prefix.len();
+        }
+        found
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Get the line and column of the position, counting from 1, with columns in characters.
+    pub fn location(&self) -> (usize, usize) {
+        let before = &self.text[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rfind('\n')
+            .map_or(before, |newline| &before[newline + 1..])
+            .chars()
+            .count()
+            + 1;
+
+        (line, column)
+    }
+}
@@ -0,0 +1,708 @@
+//! Step-through debugging, for feeding inputs to a machine one at a time.
+//!
+//! A [`Debugger`] runs any [`Target`]: an [`FSM`] over [`Finite`] states and inputs,
+//! a [`DynFSM`] or a [`TableFSM`]. It keeps a trace of the steps taken so they can be undone,
+//! and stops runs at [`Breakpoint`]s. [`Debugger::execute`] runs line commands, for a REPL:
+//!
+//! | command                  | effect                                              |
+//! |--------------------------|-----------------------------------------------------|
+//! | `step <input>`           | apply one input                                     |
+//! | `run <inputs>`           | apply inputs until a breakpoint                     |
+//! | `state`                  | show the current state and the transitions out      |
+//! | `undo`                   | undo the last step                                  |
+//! | `break <state>`          | stop when entering a state                          |
+//! | `break <from> -> <to>`   | stop when taking a transition                       |
+//! | `delete <breakpoint>`    | remove a breakpoint                                 |
+//! | `breakpoints`            | list the breakpoints                                |
+//! | `trace`                  | show the steps taken so far                         |
+//!
+//! Machines over characters read each character of the text as an input,
+//! and other machines read input names separated by whitespace.
+//!
+//! # Examples
+//! ```
+//! use fsm::{debug::Debugger, text};
+//!
+//! let machine = text::parse("start A\naccept C\nA 'a' -> B\nB 'b' -> C\nA | B | C _ -> A").unwrap();
+//! let mut debugger = Debugger::new(machine);
+//!
+//! assert_eq!(debugger.execute("break C").unwrap(), "breakpoint C");
+//! assert_eq!(
+//!     debugger.execute("run xabx").unwrap(),
+//!     "A --'x'--> A\nA --'a'--> B\nB --'b'--> C\nstopped at breakpoint C, with 1 input left"
+//! );
+//! assert_eq!(
+//!     debugger.execute("state").unwrap(),
+//!     "C (accepting)\n  _ -> A"
+//! );
+//!
+//! assert_eq!(debugger.execute("undo").unwrap(), "back in B");
+//! assert_eq!(debugger.trace().len(), 2);
+//! ```
+
+use core::fmt::{self, Write as _};
+
+use crate::{
+    table::TableFSM,
+    trace::{self, Step},
+    AcceptStates, DynFSM, Finite, FSM,
+};
+
+/// A machine that can be run by a [`Debugger`], with named states and inputs.
+pub trait Target {
+    /// The type of inputs.
+    type Input;
+
+    /// The current state, as kept by the [`Debugger`] to undo steps.
+    type Snapshot: Clone + fmt::Debug;
+
+    /// Get the current state, to [`restore`](Self::restore) later.
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Return to a state from [`snapshot`](Self::snapshot).
+    fn restore(&mut self, snapshot: Self::Snapshot);
+
+    /// Get the name of the current state.
+    fn state_name(&self) -> String;
+
+    /// Get the names of every state.
+    fn state_names(&self) -> Vec<String>;
+
+    /// Returns [`true`] if the current state is an accept state.
+    fn is_accepted(&self) -> bool;
+
+    /// Read a sequence of inputs from text.
+    ///
+    /// # Errors
+    /// This returns the part of the text that is not a valid input.
+    fn parse(&self, text: &str) -> Result<Vec<Self::Input>, String>;
+
+    /// Get the name of an input, as it is shown in transitions and traces.
+    fn input_name(&self, input: &Self::Input) -> String;
+
+    /// Get the transitions out of the current state, as the name of the input and of the next state.
+    ///
+    /// The transition for any other input is named `_`.
+    fn transitions(&self) -> Vec<(String, String)>;
+
+    /// Apply an input in place, returning [`false`] if there is no transition for it.
+    fn step(&mut self, input: Self::Input) -> bool;
+}
+
+impl<Domain, States> Target for FSM<Domain, States>
+where
+    Domain: Finite + Clone + fmt::Debug,
+    States: Finite + AcceptStates + Clone + fmt::Debug,
+{
+    type Input = Domain;
+    type Snapshot = States;
+
+    fn snapshot(&self) -> States {
+        self.state().clone()
+    }
+
+    fn restore(&mut self, snapshot: States) {
+        *self = Self::new(snapshot, self.transform_function());
+    }
+
+    fn state_name(&self) -> String {
+        format!("{:?}", self.state())
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        States::VALUES
+            .iter()
+            .map(|state| format!("{state:?}"))
+            .collect()
+    }
+
+    fn is_accepted(&self) -> bool {
+        self.state().is_accepted()
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<Domain>, String> {
+        text.split_whitespace()
+            .map(|name| {
+                Domain::VALUES
+                    .iter()
+                    .find(|input| format!("{input:?}") == name)
+                    .cloned()
+                    .ok_or_else(|| name.to_owned())
+            })
+            .collect()
+    }
+
+    fn input_name(&self, input: &Domain) -> String {
+        format!("{input:?}")
+    }
+
+    fn transitions(&self) -> Vec<(String, String)> {
+        let transform = self.transform_function();
+        Domain::VALUES
+            .iter()
+            .map(|input| {
+                let next = transform(self.state().clone(), input.clone());
+                (format!("{input:?}"), format!("{next:?}"))
+            })
+            .collect()
+    }
+
+    fn step(&mut self, input: Domain) -> bool {
+        self.apply_assign(input);
+        true
+    }
+}
+
+/// Get the transitions out of the current state of a [`DynFSM`].
+fn dyn_transitions<Domain>(
+    machine: &DynFSM<Domain>,
+    name: impl Fn(&Domain) -> String,
+) -> Vec<(String, String)> {
+    let state = machine.state_index();
+    let states = machine.states();
+
+    let otherwise = machine.otherwise(state);
+
+    // Leave out the inputs covered by the `otherwise` transition
+    let mut transitions = machine
+        .inputs()
+        .iter()
+        .enumerate()
+        .map(|(index, input)| (input, machine.next(state, index)))
+        .filter(|&(_, next)| Some(next) != otherwise)
+        .map(|(input, next)| (name(input), states[next].clone()))
+        .collect::<Vec<_>>();
+    if let Some(otherwise) = otherwise {
+        transitions.push(("_".to_owned(), states[otherwise].clone()));
+    }
+    transitions
+}
+
+impl Target for DynFSM<char> {
+    type Input = char;
+    type Snapshot = usize;
+
+    fn snapshot(&self) -> usize {
+        self.state_index()
+    }
+
+    fn restore(&mut self, snapshot: usize) {
+        self.set_state_index(snapshot);
+    }
+
+    fn state_name(&self) -> String {
+        self.state().to_owned()
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        self.states().to_vec()
+    }
+
+    fn is_accepted(&self) -> bool {
+        Self::is_accepted(self)
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<char>, String> {
+        Ok(text.chars().collect())
+    }
+
+    fn input_name(&self, input: &char) -> String {
+        format!("{input:?}")
+    }
+
+    fn transitions(&self) -> Vec<(String, String)> {
+        dyn_transitions(self, |input| format!("{input:?}"))
+    }
+
+    fn step(&mut self, input: char) -> bool {
+        self.try_apply_assign(input).is_ok()
+    }
+}
+
+impl Target for DynFSM<String> {
+    type Input = String;
+    type Snapshot = usize;
+
+    fn snapshot(&self) -> usize {
+        self.state_index()
+    }
+
+    fn restore(&mut self, snapshot: usize) {
+        self.set_state_index(snapshot);
+    }
+
+    fn state_name(&self) -> String {
+        self.state().to_owned()
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        self.states().to_vec()
+    }
+
+    fn is_accepted(&self) -> bool {
+        Self::is_accepted(self)
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<String>, String> {
+        Ok(text.split_whitespace().map(str::to_owned).collect())
+    }
+
+    fn input_name(&self, input: &String) -> String {
+        input.clone()
+    }
+
+    fn transitions(&self) -> Vec<(String, String)> {
+        dyn_transitions(self, Clone::clone)
+    }
+
+    fn step(&mut self, input: String) -> bool {
+        self.try_apply_assign(input).is_ok()
+    }
+}
+
+impl Target for TableFSM {
+    type Input = usize;
+    type Snapshot = usize;
+
+    fn snapshot(&self) -> usize {
+        self.state_index()
+    }
+
+    fn restore(&mut self, snapshot: usize) {
+        self.set_state_index(snapshot);
+    }
+
+    fn state_name(&self) -> String {
+        self.state().to_owned()
+    }
+
+    fn state_names(&self) -> Vec<String> {
+        self.table().states().to_vec()
+    }
+
+    fn is_accepted(&self) -> bool {
+        Self::is_accepted(self)
+    }
+
+    fn parse(&self, text: &str) -> Result<Vec<usize>, String> {
+        text.split_whitespace()
+            .map(|name| {
+                self.table()
+                    .input_index(name)
+                    .ok_or_else(|| name.to_owned())
+            })
+            .collect()
+    }
+
+    fn input_name(&self, input: &usize) -> String {
+        self.table().inputs()[*input].clone()
+    }
+
+    fn transitions(&self) -> Vec<(String, String)> {
+        let table = self.table();
+        (0..table.inputs().len())
+            .map(|input| {
+                let next = table.next(self.state_index(), input);
+                (table.inputs()[input].clone(), table.states()[next].clone())
+            })
+            .collect()
+    }
+
+    fn step(&mut self, input: usize) -> bool {
+        self.apply_index(input);
+        true
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A place for a [`Debugger`] to stop.
+pub enum Breakpoint {
+    /// Stop when entering a state.
+    State(String),
+    /// Stop when taking a transition between two states.
+    Transition { from: String, to: String },
+}
+
+impl Breakpoint {
+    /// Read a breakpoint, written as `state` or `from -> to`.
+    fn parse(text: &str) -> Self {
+        match text.split_once("->") {
+            Some((from, to)) => Self::Transition {
+                from: from.trim().to_owned(),
+                to: to.trim().to_owned(),
+            },
+            None => Self::State(text.trim().to_owned()),
+        }
+    }
+
+    /// Returns [`true`] if taking a transition from `from` to `to` should stop.
+    fn matches(&self, from: &str, to: &str) -> bool {
+        match self {
+            Self::State(state) => state == to,
+            Self::Transition {
+                from: start,
+                to: end,
+            } => start == from && end == to,
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::State(state) => f.write_str(state),
+            Self::Transition { from, to } => write!(f, "{from} -> {to}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An error produced by a [`Debugger`].
+pub enum Error {
+    /// A command that doesn't exist.
+    UnknownCommand(String),
+    /// A command is missing its argument.
+    MissingArgument(&'static str),
+    /// Text that is not a valid input.
+    InvalidInput(String),
+    /// `step` is given more than one input.
+    TooManyInputs,
+    /// The current state has no transition for an input.
+    NoTransition { state: String, input: String },
+    /// A breakpoint names a state that doesn't exist.
+    UnknownState(String),
+    /// A breakpoint is deleted that was never set.
+    UnknownBreakpoint(Breakpoint),
+    /// There are no steps to undo.
+    NothingToUndo,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => {
+                write!(f, "unknown command `{command}`, try `help`")
+            }
+            Self::MissingArgument(command) => write!(f, "`{command}` needs an argument"),
+            Self::InvalidInput(input) => write!(f, "`{input}` is not an input"),
+            Self::TooManyInputs => f.write_str("`step` takes a single input"),
+            Self::NoTransition { state, input } => {
+                write!(f, "the state `{state}` has no transition for {input}")
+            }
+            Self::UnknownState(state) => write!(f, "there is no state named `{state}`"),
+            Self::UnknownBreakpoint(breakpoint) => {
+                write!(f, "there is no breakpoint `{breakpoint}`")
+            }
+            Self::NothingToUndo => f.write_str("there are no steps to undo"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Why a [`Debugger::run`] stopped.
+pub enum Stop {
+    /// Every input was applied.
+    Finished,
+    /// A breakpoint was reached, with some inputs left to apply.
+    Breakpoint {
+        breakpoint: Breakpoint,
+        remaining: usize,
+    },
+}
+
+/// Commands for [`Debugger::execute`].
+const HELP: &str = "\
+step <input>          apply one input
+run <inputs>          apply inputs until a breakpoint
+state                 show the current state and the transitions out of it
+undo                  undo the last step
+break <state>         stop when entering a state
+break <from> -> <to>  stop when taking a transition
+delete <breakpoint>   remove a breakpoint
+breakpoints           list the breakpoints
+trace                 show the steps taken so far";
+
+/// A name, formatted without quotes in a [`trace::Table`].
+struct Name<'a>(&'a str);
+
+impl fmt::Debug for Name<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[derive(Clone, Debug)]
+/// A debugger, that runs a machine one step at a time.
+pub struct Debugger<M: Target> {
+    machine: M,
+    /// The state before each step in the trace.
+    history: Vec<M::Snapshot>,
+    trace: Vec<Step<String, String>>,
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl<M: Target> Debugger<M> {
+    #[must_use]
+    #[inline]
+    /// Create a new debugger for a machine, with no breakpoints.
+    pub const fn new(machine: M) -> Self {
+        Self {
+            machine,
+            history: Vec::new(),
+            trace: Vec::new(),
+            breakpoints: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the machine.
+    pub const fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the steps taken so far, with the names of the states and inputs.
+    pub fn trace(&self) -> &[Step<String, String>] {
+        &self.trace
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the breakpoints, in the order they were added.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Add a breakpoint, if it isn't already set.
+    ///
+    /// # Errors
+    /// This returns [`Error::UnknownState`] if the breakpoint names a state that doesn't exist.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> Result<(), Error> {
+        let states = self.machine.state_names();
+        let names = match &breakpoint {
+            Breakpoint::State(state) => [state, state],
+            Breakpoint::Transition { from, to } => [from, to],
+        };
+        if let Some(name) = names.into_iter().find(|name| !states.contains(name)) {
+            return Err(Error::UnknownState(name.clone()));
+        }
+
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+        Ok(())
+    }
+
+    /// Remove a breakpoint, returning [`false`] if it wasn't set.
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|other| other != breakpoint);
+        self.breakpoints.len() != count
+    }
+
+    /// Apply an input, returning the breakpoint it reached, if any.
+    ///
+    /// # Errors
+    /// This returns [`Error::NoTransition`] if the current state has no transition for the input,
+    /// leaving the machine unchanged.
+    pub fn step(&mut self, input: M::Input) -> Result<Option<&Breakpoint>, Error> {
+        let from = self.machine.state_name();
+        let name = self.machine.input_name(&input);
+
+        let previous = self.machine.snapshot();
+        if !self.machine.step(input) {
+            return Err(Error::NoTransition {
+                state: from,
+                input: name,
+            });
+        }
+        self.history.push(previous);
+
+        let to = self.machine.state_name();
+        let breakpoint = self
+            .breakpoints
+            .iter()
+            .find(|breakpoint| breakpoint.matches(&from, &to));
+        self.trace.push(Step::new(self.trace.len(), from, name, to));
+        Ok(breakpoint)
+    }
+
+    /// Apply inputs until one reaches a breakpoint.
+    ///
+    /// # Errors
+    /// This returns [`Error::NoTransition`] for the first input without a transition,
+    /// after applying the inputs before it.
+    pub fn run<I>(&mut self, inputs: I) -> Result<Stop, Error>
+    where
+        I: IntoIterator<Item = M::Input>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut inputs = inputs.into_iter();
+        while let Some(input) = inputs.next() {
+            if let Some(breakpoint) = self.step(input)? {
+                return Ok(Stop::Breakpoint {
+                    breakpoint: breakpoint.clone(),
+                    remaining: inputs.len(),
+                });
+            }
+        }
+        Ok(Stop::Finished)
+    }
+
+    /// Undo the last step, returning [`false`] if there are no steps.
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.history.pop() else {
+            return false;
+        };
+        self.machine.restore(snapshot);
+        self.trace.pop();
+        true
+    }
+
+    /// Run a command, returning its output.
+    ///
+    /// See the [module documentation](self) for the commands.
+    ///
+    /// # Errors
+    /// This returns an error if the command is invalid or can't be run.
+    /// A `run` that fails part way keeps the steps before the failure.
+    pub fn execute(&mut self, command: &str) -> Result<String, Error> {
+        let command = command.trim();
+        let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+        let argument = |name| {
+            if argument.is_empty() {
+                Err(Error::MissingArgument(name))
+            } else {
+                Ok(argument)
+            }
+        };
+        let parse = |machine: &M, text| machine.parse(text).map_err(Error::InvalidInput);
+
+        match name {
+            "" => Ok(String::new()),
+            "help" => Ok(HELP.to_owned()),
+            "step" | "s" => {
+                let mut inputs = parse(&self.machine, argument("step")?)?;
+                if inputs.len() > 1 {
+                    return Err(Error::TooManyInputs);
+                }
+                let Some(input) = inputs.pop() else {
+                    return Err(Error::MissingArgument("step"));
+                };
+
+                let breakpoint = self
+                    .step(input)?
+                    .map(|breakpoint| format!("\nstopped at breakpoint {breakpoint}"));
+                Ok(self.last_step() + &breakpoint.unwrap_or_default())
+            }
+            "run" | "r" => {
+                let inputs = parse(&self.machine, argument("run")?)?;
+                let start = self.trace.len();
+                let stop = self.run(inputs);
+                Ok(self.describe_run(start, stop))
+            }
+            "state" => Ok(self.describe_state()),
+            "undo" | "u" => {
+                if self.undo() {
+                    Ok(format!("back in {}", self.machine.state_name()))
+                } else {
+                    Err(Error::NothingToUndo)
+                }
+            }
+            "break" | "b" => {
+                let breakpoint = Breakpoint::parse(argument("break")?);
+                let output = format!("breakpoint {breakpoint}");
+                self.add_breakpoint(breakpoint)?;
+                Ok(output)
+            }
+            "delete" => {
+                let breakpoint = Breakpoint::parse(argument("delete")?);
+                if self.remove_breakpoint(&breakpoint) {
+                    Ok(format!("deleted breakpoint {breakpoint}"))
+                } else {
+                    Err(Error::UnknownBreakpoint(breakpoint))
+                }
+            }
+            "breakpoints" => Ok(if self.breakpoints.is_empty() {
+                "no breakpoints".to_owned()
+            } else {
+                self.breakpoints
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            "trace" => Ok(self.describe_trace()),
+            _ => Err(Error::UnknownCommand(name.to_owned())),
+        }
+    }
+
+    /// Describe a step, as `from --input--> to`.
+    fn describe(step: &Step<String, String>) -> String {
+        format!("{} --{}--> {}", step.from(), step.input(), step.to())
+    }
+
+    /// Describe the last step taken.
+    fn last_step(&self) -> String {
+        self.trace.last().map(Self::describe).unwrap_or_default()
+    }
+
+    /// Describe the steps of a run from the step at index `start`, and why it stopped.
+    fn describe_run(&self, start: usize, stop: Result<Stop, Error>) -> String {
+        let mut output = self.trace[start..]
+            .iter()
+            .map(Self::describe)
+            .collect::<Vec<_>>()
+            .join("\n");
+        match stop {
+            Ok(Stop::Finished) => {}
+            Ok(Stop::Breakpoint {
+                breakpoint,
+                remaining,
+            }) => {
+                let plural = if remaining == 1 { "" } else { "s" };
+                let _ = write!(
+                    output,
+                    "\nstopped at breakpoint {breakpoint}, with {remaining} input{plural} left"
+                );
+            }
+            Err(error) => {
+                let _ = write!(output, "\n{error}");
+            }
+        }
+        output.trim_start().to_owned()
+    }
+
+    /// Describe the steps taken so far, as a [`trace::Table`].
+    fn describe_trace(&self) -> String {
+        let steps = self
+            .trace
+            .iter()
+            .map(|step| {
+                Step::new(
+                    step.index(),
+                    Name(step.from()),
+                    Name(step.input()),
+                    Name(step.to()),
+                )
+            })
+            .collect::<Vec<_>>();
+        trace::Table::new(&steps).to_string().trim_end().to_owned()
+    }
+
+    /// Describe the current state, and the transitions out of it.
+    fn describe_state(&self) -> String {
+        let mut output = self.machine.state_name();
+        if self.machine.is_accepted() {
+            output += " (accepting)";
+        }
+        for (input, next) in self.machine.transitions() {
+            let _ = write!(output, "\n  {input} -> {next}");
+        }
+        output
+    }
+}
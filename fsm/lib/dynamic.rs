@@ -0,0 +1,631 @@
+//! Machines defined at runtime, such as from configuration.
+//!
+//! A [`DynFSM`] is built with a [`Builder`], which checks that the machine is deterministic,
+//! has a transition for every state and input, and can reach every state.
+//!
+//! # Examples
+//! ```
+//! use fsm::{dynamic::Error, DynFSM};
+//!
+//! let machine = DynFSM::builder()
+//!     .add_state("closed")
+//!     .add_state("open")
+//!     .add_state("locked")
+//!     .set_start("closed")
+//!     .set_accepting("locked")
+//!     .add_transition("closed", "open", "open")
+//!     .add_transition("closed", "lock", "locked")
+//!     .add_transition("open", "close", "closed")
+//!     .add_transition("locked", "unlock", "closed")
+//!     // Every other input leaves the door as it is
+//!     .add_otherwise("closed", "closed")
+//!     .add_otherwise("open", "open")
+//!     .add_otherwise("locked", "locked")
+//!     .build()
+//!     .unwrap();
+//!
+//! let machine = machine.run(["open", "lock", "close", "lock"]);
+//! assert_eq!(machine.state(), "locked");
+//! assert!(machine.is_accepted());
+//!
+//! // Without the `otherwise` transitions, the machine is incomplete
+//! let error = DynFSM::builder()
+//!     .add_state("closed")
+//!     .add_state("open")
+//!     .set_start("closed")
+//!     .add_transition("closed", "open", "open")
+//!     .add_transition("open", "close", "closed")
+//!     .build()
+//!     .unwrap_err();
+//!
+//! assert_eq!(
+//!     error,
+//!     Error::MissingTransition {
+//!         state: "closed".to_owned(),
+//!         input: "close",
+//!     }
+//! );
+//! ```
+
+use alloc::collections::{BTreeMap, VecDeque};
+use core::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An error produced when building or running a [`DynFSM`].
+pub enum Error<Domain> {
+    /// A state is added more than once.
+    DuplicateState(String),
+    /// A state name is used, but there is no state with that name.
+    UnknownState(String),
+    /// There is no start state.
+    MissingStart,
+    /// There is more than one start state.
+    ConflictingStarts { first: String, second: String },
+    /// There is no transition from a state for an input.
+    MissingTransition { state: String, input: Domain },
+    /// There are transitions from a state to different states for the same input,
+    /// or for any other input if `input` is [`None`].
+    Nondeterministic {
+        state: String,
+        input: Option<Domain>,
+    },
+    /// A state can't be reached from the start state.
+    UnreachableState(String),
+    /// An input is applied to a state with no transition for it.
+    UnknownInput { state: String, input: Domain },
+}
+
+impl<Domain: fmt::Debug> fmt::Display for Error<Domain> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateState(name) => write!(f, "the state `{name}` is added more than once"),
+            Self::UnknownState(name) => write!(f, "there is no state named `{name}`"),
+            Self::MissingStart => f.write_str("there is no start state"),
+            Self::ConflictingStarts { first, second } => {
+                write!(
+                    f,
+                    "both `{first}` and `{second}` are set as the start state"
+                )
+            }
+            Self::MissingTransition { state, input } => {
+                write!(f, "there is no transition from `{state}` for {input:?}")
+            }
+            Self::Nondeterministic {
+                state,
+                input: Some(input),
+            } => write!(
+                f,
+                "there are transitions from `{state}` to different states for {input:?}"
+            ),
+            Self::Nondeterministic { state, input: None } => write!(
+                f,
+                "there are `otherwise` transitions from `{state}` to different states"
+            ),
+            Self::UnreachableState(name) => {
+                write!(
+                    f,
+                    "the state `{name}` can't be reached from the start state"
+                )
+            }
+            Self::UnknownInput { state, input } => {
+                write!(f, "the state `{state}` has no transition for {input:?}")
+            }
+        }
+    }
+}
+
+impl<Domain: fmt::Debug> core::error::Error for Error<Domain> {}
+
+#[derive(Clone, Debug)]
+/// A builder for a [`DynFSM`].
+///
+/// States are named, and are referred to by name when adding transitions,
+/// so they can be added in any order. Problems are reported by [`validate`](Self::validate)
+/// and [`build`](Self::build).
+///
+/// Inputs must be ordered, so a machine finds the transition for an input by binary search.
+pub struct Builder<Domain> {
+    states: Vec<String>,
+    starts: Vec<String>,
+    accepting: Vec<String>,
+    transitions: Vec<(String, Domain, String)>,
+    otherwise: Vec<(String, String)>,
+}
+
+impl<Domain> Default for Builder<Domain> {
+    fn default() -> Self {
+        Self {
+            states: Vec::new(),
+            starts: Vec::new(),
+            accepting: Vec::new(),
+            transitions: Vec::new(),
+            otherwise: Vec::new(),
+        }
+    }
+}
+
+impl<Domain> Builder<Domain>
+where
+    Domain: Clone + Ord,
+{
+    #[must_use]
+    #[inline]
+    /// Create a new builder, with no states.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    /// Add a state.
+    pub fn add_state(mut self, name: impl Into<String>) -> Self {
+        self.states.push(name.into());
+        self
+    }
+
+    #[must_use]
+    /// Set the start state.
+    pub fn set_start(mut self, name: impl Into<String>) -> Self {
+        self.starts.push(name.into());
+        self
+    }
+
+    #[must_use]
+    /// Mark a state as an accept state.
+    pub fn set_accepting(mut self, name: impl Into<String>) -> Self {
+        self.accepting.push(name.into());
+        self
+    }
+
+    #[must_use]
+    /// Add a transition from the state `from` to the state `to` for `input`.
+    pub fn add_transition(
+        mut self,
+        from: impl Into<String>,
+        input: Domain,
+        to: impl Into<String>,
+    ) -> Self {
+        self.transitions.push((from.into(), input, to.into()));
+        self
+    }
+
+    #[must_use]
+    /// Add a transition from the state `from` to the state `to` for any input
+    /// without a transition from `from`.
+    ///
+    /// This includes inputs that are not used by any other transition.
+    pub fn add_otherwise(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.otherwise.push((from.into(), to.into()));
+        self
+    }
+
+    #[must_use]
+    /// Check the machine, returning every problem found.
+    ///
+    /// The inputs of the machine are the inputs used by its transitions, and every state
+    /// must have a transition for each of them.
+    pub fn validate(&self) -> Vec<Error<Domain>> {
+        match self.resolve() {
+            Ok(machine) => machine
+                .unreachable()
+                .into_iter()
+                .map(Error::UnreachableState)
+                .collect(),
+            Err(errors) => errors,
+        }
+    }
+
+    /// Build the machine.
+    ///
+    /// # Errors
+    /// This returns the first problem found by [`validate`](Self::validate).
+    pub fn build(self) -> Result<DynFSM<Domain>, Error<Domain>> {
+        let machine = self
+            .resolve()
+            .map_err(|errors| errors.into_iter().next().unwrap_or(Error::MissingStart))?;
+
+        if let Some(state) = machine.unreachable().into_iter().next() {
+            return Err(Error::UnreachableState(state));
+        }
+
+        Ok(machine)
+    }
+
+    /// Build the machine, allowing states that can't be reached from the start state.
+    ///
+    /// This is for tools that report unreachable states themselves, rather than rejecting the machine.
+    ///
+    /// # Errors
+    /// This returns the first problem found by [`validate`](Self::validate),
+    /// other than [`Error::UnreachableState`].
+    pub fn build_allowing_unreachable(self) -> Result<DynFSM<Domain>, Error<Domain>> {
+        self.resolve()
+            .map_err(|errors| errors.into_iter().next().unwrap_or(Error::MissingStart))
+    }
+
+    /// The inputs used by the transitions, in the order they are first used, and the index of each.
+    fn inputs(&self) -> (Vec<Domain>, BTreeMap<&Domain, usize>) {
+        let mut inputs = Vec::new();
+        let mut indices = BTreeMap::new();
+        for (_, input, _) in &self.transitions {
+            indices.entry(input).or_insert_with(|| {
+                inputs.push(input.clone());
+                inputs.len() - 1
+            });
+        }
+        (inputs, indices)
+    }
+
+    /// Resolve the names and transitions into a machine, without checking if every state is reachable.
+    fn resolve(&self) -> Result<DynFSM<Domain>, Vec<Error<Domain>>> {
+        let mut errors = Vec::new();
+
+        for (index, name) in self.states.iter().enumerate() {
+            if self.states[..index].contains(name) {
+                report(&mut errors, Error::DuplicateState(name.clone()));
+            }
+        }
+
+        let mut state_index = |name: &String| {
+            let index = self.states.iter().position(|state| state == name);
+            if index.is_none() {
+                report(&mut errors, Error::UnknownState(name.clone()));
+            }
+            index
+        };
+
+        let start = self.starts.iter().map(&mut state_index).collect::<Vec<_>>();
+        let accept = self
+            .accepting
+            .iter()
+            .map(&mut state_index)
+            .collect::<Vec<_>>();
+        let transitions = self
+            .transitions
+            .iter()
+            .map(|(from, input, to)| (state_index(from), input, state_index(to)))
+            .collect::<Vec<_>>();
+        let otherwise = self
+            .otherwise
+            .iter()
+            .map(|(from, to)| (state_index(from), state_index(to)))
+            .collect::<Vec<_>>();
+
+        match self.starts.as_slice() {
+            [] => errors.push(Error::MissingStart),
+            [first, rest @ ..] => {
+                if let Some(second) = rest.iter().find(|second| *second != first) {
+                    errors.push(Error::ConflictingStarts {
+                        first: first.clone(),
+                        second: second.clone(),
+                    });
+                }
+            }
+        }
+
+        let (inputs, indices) = self.inputs();
+
+        let mut table = vec![None; self.states.len() * inputs.len()];
+        let mut conflicting = vec![false; table.len()];
+        let mut defaults = vec![None; self.states.len()];
+
+        for (from, input, to) in transitions {
+            let (Some(from), Some(to)) = (from, to) else {
+                continue;
+            };
+            let input = indices[input];
+
+            let index = from * inputs.len() + input;
+            if table[index].is_some_and(|other| other != to) && !conflicting[index] {
+                conflicting[index] = true;
+                errors.push(Error::Nondeterministic {
+                    state: self.states[from].clone(),
+                    input: Some(inputs[input].clone()),
+                });
+            }
+            table[index] = Some(to);
+        }
+
+        for (from, to) in otherwise {
+            let (Some(from), Some(to)) = (from, to) else {
+                continue;
+            };
+
+            let cell: &mut Option<usize> = &mut defaults[from];
+            if cell.is_some_and(|other| other != to) {
+                let state = self.states[from].clone();
+                report(&mut errors, Error::Nondeterministic { state, input: None });
+            }
+            *cell = Some(to);
+        }
+
+        let mut transitions = Vec::with_capacity(table.len());
+        for (index, to) in table.into_iter().enumerate() {
+            let from = index / inputs.len();
+            match to.or(defaults[from]) {
+                Some(to) => transitions.push(to),
+                None => errors.push(Error::MissingTransition {
+                    state: self.states[from].clone(),
+                    input: inputs[index % inputs.len()].clone(),
+                }),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut accepting = vec![false; self.states.len()];
+        for state in accept.into_iter().flatten() {
+            accepting[state] = true;
+        }
+
+        // There are no errors, so there is a start state
+        let start = start.into_iter().flatten().next().unwrap_or(0);
+
+        Ok(DynFSM {
+            states: self.states.clone(),
+            order: indices.into_values().collect(),
+            inputs,
+            transitions,
+            otherwise: defaults,
+            accepting,
+            start,
+            state: start,
+        })
+    }
+}
+
+/// Add an error to a list of errors, if it is not already in the list.
+fn report<Domain: PartialEq>(errors: &mut Vec<Error<Domain>>, error: Error<Domain>) {
+    if !errors.contains(&error) {
+        errors.push(error);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A Finite State Machine defined at runtime, with named states.
+///
+/// This is created with a [`Builder`], and runs like an [`FSM`](crate::FSM).
+pub struct DynFSM<Domain> {
+    states: Vec<String>,
+    inputs: Vec<Domain>,
+    /// The indices of the inputs, sorted by input, to find an input by binary search.
+    order: Vec<usize>,
+    /// The next state for each state and input, by state then input.
+    transitions: Vec<usize>,
+    /// The next state for each state, for inputs that are not in `inputs`.
+    otherwise: Vec<Option<usize>>,
+    accepting: Vec<bool>,
+    start: usize,
+    state: usize,
+}
+
+impl<Domain> DynFSM<Domain>
+where
+    Domain: Clone + Ord,
+{
+    #[must_use]
+    #[inline]
+    /// Create a new builder for a machine.
+    pub fn builder() -> Builder<Domain> {
+        Builder::new()
+    }
+}
+
+impl<Domain> DynFSM<Domain> {
+    #[must_use]
+    #[inline]
+    /// Get the name of the current state.
+    pub fn state(&self) -> &str {
+        &self.states[self.state]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the current state, in the order the states were added.
+    pub const fn state_index(&self) -> usize {
+        self.state
+    }
+
+    #[inline]
+    /// Move to the state at index `state`, for the [debugger](crate::debug) to undo steps.
+    pub(crate) const fn set_state_index(&mut self, state: usize) {
+        self.state = state;
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the names of the states, in the order they were added.
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the inputs used by the transitions, in the order they were first used.
+    pub fn inputs(&self) -> &[Domain] {
+        &self.inputs
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is marked as accepted.
+    pub fn is_accepted(&self) -> bool {
+        self.accepting[self.state]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the start state.
+    pub const fn start(&self) -> usize {
+        self.start
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the state at index `state` is an accept state.
+    ///
+    /// # Panics
+    /// This will panic if the index is out of range.
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the state after giving the input at index `input` to the state at index `state`.
+    ///
+    /// # Panics
+    /// This will panic if either index is out of range.
+    pub fn next(&self, state: usize, input: usize) -> usize {
+        assert!(input < self.inputs.len(), "the input index is out of range");
+        self.transitions[state * self.inputs.len() + input]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the target of the `otherwise` transition from the state at index `state`, if it has one.
+    ///
+    /// # Panics
+    /// This will panic if the index is out of range.
+    pub fn otherwise(&self, state: usize) -> Option<usize> {
+        self.otherwise[state]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get a copy of the machine, in the start state.
+    pub fn reset(&self) -> Self
+    where
+        Domain: Clone,
+    {
+        Self {
+            state: self.start,
+            ..self.clone()
+        }
+    }
+
+    /// The states that can't be reached from the start state.
+    fn unreachable(&self) -> Vec<String> {
+        let mut reached = vec![false; self.states.len()];
+        reached[self.start] = true;
+
+        let mut queue = VecDeque::from([self.start]);
+        while let Some(state) = queue.pop_front() {
+            let row = &self.transitions[state * self.inputs.len()..][..self.inputs.len()];
+            for &next in row.iter().chain(&self.otherwise[state]) {
+                if !reached[next] {
+                    reached[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        self.states
+            .iter()
+            .zip(reached)
+            .filter(|(_, reached)| !reached)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+impl<Domain> DynFSM<Domain>
+where
+    Domain: Ord,
+{
+    #[must_use]
+    /// Get the index of an input in [`inputs`](Self::inputs), if it is used by a transition.
+    pub fn input_index(&self, input: &Domain) -> Option<usize> {
+        self.order
+            .binary_search_by(|&other| self.inputs[other].cmp(input))
+            .ok()
+            .map(|position| self.order[position])
+    }
+
+    /// Get the index of the next state, if there is a transition for the input.
+    fn target(&self, input: &Domain) -> Option<usize> {
+        self.input_index(input)
+            .map_or(self.otherwise[self.state], |input| {
+                Some(self.transitions[self.state * self.inputs.len() + input])
+            })
+    }
+
+    #[must_use = "this returns the result of the transformation, without modifying the original"]
+    #[inline]
+    /// Apply an input to the machine, returning the new state of the machine.
+    ///
+    /// # Panics
+    /// This will panic if the input is not used by any transition,
+    /// and the current state has no `otherwise` transition.
+    pub fn apply(mut self, input: Domain) -> Self
+    where
+        Domain: fmt::Debug,
+    {
+        self.apply_assign(input);
+        self
+    }
+
+    #[inline]
+    /// Apply an input to the machine in place.
+    ///
+    /// # Panics
+    /// This will panic if the input is not used by any transition,
+    /// and the current state has no `otherwise` transition.
+    pub fn apply_assign(&mut self, input: Domain)
+    where
+        Domain: fmt::Debug,
+    {
+        if let Err(error) = self.try_apply_assign(input) {
+            panic!("{error}");
+        }
+    }
+
+    /// Apply an input to the machine in place, if there is a transition for it.
+    ///
+    /// # Errors
+    /// This returns [`Error::UnknownInput`] if the input is not used by any transition,
+    /// and the current state has no `otherwise` transition, leaving the machine unchanged.
+    pub fn try_apply_assign(&mut self, input: Domain) -> Result<(), Error<Domain>> {
+        match self.target(&input) {
+            Some(next) => {
+                self.state = next;
+                Ok(())
+            }
+            None => Err(Error::UnknownInput {
+                state: self.states[self.state].clone(),
+                input,
+            }),
+        }
+    }
+
+    #[must_use = "this returns the result of the transformations, without modifying the original"]
+    /// Apply a set of inputs to the machine, returning the new state of the machine.
+    ///
+    /// # Panics
+    /// This will panic if an input is not used by any transition,
+    /// and the state it is applied to has no `otherwise` transition.
+    pub fn run<I>(mut self, inputs: I) -> Self
+    where
+        Domain: fmt::Debug,
+        I: IntoIterator<Item = Domain>,
+    {
+        self.run_assign(inputs);
+        self
+    }
+
+    /// Apply a set of inputs to the machine in place.
+    ///
+    /// # Panics
+    /// This will panic if an input is not used by any transition,
+    /// and the state it is applied to has no `otherwise` transition.
+    pub fn run_assign<I>(&mut self, inputs: I)
+    where
+        Domain: fmt::Debug,
+        I: IntoIterator<Item = Domain>,
+    {
+        for input in inputs {
+            self.apply_assign(input);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+/// This trait lists every value of a type with a finite number of values,
+/// such as a set of states or an input alphabet.
+///
+/// One trait is used for both states and inputs, rather than a trait for each,
+/// as a machine needs the same from them: a list of every value, and the index of a value.
+///
+/// This can be derived for enums whose variants have no fields,
+/// and is implemented for [`bool`] and [`u8`].
+pub trait Finite: Sized + 'static {
+    /// Every value of the type, in order.
+    const VALUES: &'static [Self];
+
+    #[must_use]
+    /// Returns the position of the value in [`VALUES`](Self::VALUES).
+    fn index(&self) -> usize;
+}
+
+impl Finite for bool {
+    const VALUES: &'static [Self] = &[false, true];
+
+    #[inline]
+    fn index(&self) -> usize {
+        usize::from(*self)
+    }
+}
+
+impl Finite for u8 {
+    #[allow(clippy::cast_possible_truncation)]
+    const VALUES: &'static [Self] = &{
+        let mut values = [0; 256];
+        let mut index = 0;
+        while index < values.len() {
+            values[index] = index as Self;
+            index += 1;
+        }
+        values
+    };
+
+    #[inline]
+    fn index(&self) -> usize {
+        usize::from(*self)
+    }
+}
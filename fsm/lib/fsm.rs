@@ -1,6 +1,7 @@
-use crate::AcceptStates;
+use crate::{AcceptStates, Hooks};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(unpredictable_function_pointer_comparisons)]
 /// A Finite State Machine.
 ///
 /// This contains a state and a transform function.
@@ -108,4 +109,112 @@ impl<Domain, States> FSM<Domain, States> {
 
         self.state = state;
     }
+
+    #[must_use = "this returns the result of the transformation, without modifying the original"]
+    #[inline]
+    /// Apply an input to the Finite State Machine, running `hooks`, and returning the new state of the machine.
+    pub fn apply_with_hooks<H>(mut self, input: Domain, hooks: &mut H) -> Self
+    where
+        States: Clone,
+        Domain: Clone,
+        H: Hooks<Domain, States>,
+    {
+        self.apply_assign_with_hooks(input, hooks);
+        self
+    }
+
+    /// Apply an input to the Finite State Machine in place, running `hooks`.
+    ///
+    /// The transform function takes the input, so it is cloned for [`Hooks::on_transition`].
+    pub fn apply_assign_with_hooks<H>(&mut self, input: Domain, hooks: &mut H)
+    where
+        States: Clone,
+        Domain: Clone,
+        H: Hooks<Domain, States>,
+    {
+        hooks.on_exit(&self.state);
+
+        let new_state = (self.transform)(self.state.clone(), input.clone());
+        hooks.on_transition(&self.state, &input, &new_state);
+        self.state = new_state;
+
+        hooks.on_enter(&self.state);
+    }
+
+    #[must_use = "this returns the result of the transformations, without modifying the original"]
+    /// Apply a set of inputs to the Finite State Machine, running `hooks`, and returning the new state of the machine.
+    pub fn run_with_hooks<I, H>(mut self, inputs: I, hooks: &mut H) -> Self
+    where
+        States: Clone,
+        Domain: Clone,
+        I: IntoIterator<Item = Domain>,
+        H: Hooks<Domain, States>,
+    {
+        self.run_assign_with_hooks(inputs, hooks);
+        self
+    }
+
+    /// Apply a set of inputs to the Finite State Machine in place, running `hooks`.
+    pub fn run_assign_with_hooks<I, H>(&mut self, inputs: I, hooks: &mut H)
+    where
+        States: Clone,
+        Domain: Clone,
+        I: IntoIterator<Item = Domain>,
+        H: Hooks<Domain, States>,
+    {
+        for input in inputs {
+            self.apply_assign_with_hooks(input, hooks);
+        }
+    }
+
+    #[must_use = "this returns the result of the transformation, without modifying the original"]
+    #[inline]
+    /// Apply an input to the Finite State Machine, running the `on_exit` and `on_enter` hooks,
+    /// and returning the new state of the machine.
+    pub fn apply_with_state_hooks<H>(mut self, input: Domain, hooks: &mut H) -> Self
+    where
+        States: Clone,
+        H: Hooks<Domain, States>,
+    {
+        self.apply_assign_with_state_hooks(input, hooks);
+        self
+    }
+
+    /// Apply an input to the Finite State Machine in place, running the `on_exit` and `on_enter` hooks.
+    ///
+    /// [`Hooks::on_transition`] is never run, so the input doesn't need to be [`Clone`].
+    pub fn apply_assign_with_state_hooks<H>(&mut self, input: Domain, hooks: &mut H)
+    where
+        States: Clone,
+        H: Hooks<Domain, States>,
+    {
+        hooks.on_exit(&self.state);
+        self.state = (self.transform)(self.state.clone(), input);
+        hooks.on_enter(&self.state);
+    }
+
+    #[must_use = "this returns the result of the transformations, without modifying the original"]
+    /// Apply a set of inputs to the Finite State Machine, running the `on_exit` and `on_enter` hooks,
+    /// and returning the new state of the machine.
+    pub fn run_with_state_hooks<I, H>(mut self, inputs: I, hooks: &mut H) -> Self
+    where
+        States: Clone,
+        I: IntoIterator<Item = Domain>,
+        H: Hooks<Domain, States>,
+    {
+        self.run_assign_with_state_hooks(inputs, hooks);
+        self
+    }
+
+    /// Apply a set of inputs to the Finite State Machine in place, running the `on_exit` and `on_enter` hooks.
+    pub fn run_assign_with_state_hooks<I, H>(&mut self, inputs: I, hooks: &mut H)
+    where
+        States: Clone,
+        I: IntoIterator<Item = Domain>,
+        H: Hooks<Domain, States>,
+    {
+        for input in inputs {
+            self.apply_assign_with_state_hooks(input, hooks);
+        }
+    }
 }
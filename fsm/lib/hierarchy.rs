@@ -0,0 +1,560 @@
+//! Hierarchical state machines (statecharts), where states can be nested in parent states.
+//!
+//! The machine is always in a leaf state, and is also in every ancestor of that state.
+//! An input is handled by the innermost of these states that has a transition for it,
+//! so a transition given for a parent state applies to all of its descendants.
+//!
+//! When a transition is taken, the states that are left are exited from the innermost outwards,
+//! and the states that are entered are entered from the outermost inwards,
+//! stopping at the least common ancestor of the source and target states.
+//! Transitions are external, so a transition from a state to itself, or to one of its descendants,
+//! leaves and re-enters the state.
+//! If the target has children, its initial child is entered, repeatedly, until a leaf state is reached.
+//!
+//! A parent state can also have [`History`] states, which are not entered themselves.
+//! A transition to a history state enters its parent, then restores the states that were active
+//! when the parent was last exited, as recorded by the machine's [`Memory`].
+//! If the parent has never been exited, the history state's default is entered instead.
+//!
+//! # Examples
+//! ```
+//! use fsm::{hierarchy::HierarchicalFSM, DefineHierarchicalTransform, DefineHierarchy, Hooks};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum States { Off, On, Idle, Running, Fast, Slow }
+//! use States::*;
+//!
+//! enum Inputs { PowerOn, PowerOff, Start, Stop, Toggle }
+//!
+//! DefineHierarchy!(States,
+//!     On => [Idle, Running],
+//!     Running => [Slow, Fast],
+//! );
+//!
+//! DefineHierarchicalTransform!(device, States, Inputs,
+//!     (Off, Inputs::PowerOn) => On,
+//!     // This applies to every state inside `On`
+//!     (On, Inputs::PowerOff) => Off,
+//!     (Idle, Inputs::Start) => Running,
+//!     (Running, Inputs::Stop) => Idle,
+//!     (Slow, Inputs::Toggle) => Fast,
+//!     (Fast, Inputs::Toggle) => Slow,
+//! );
+//!
+//! let mut machine = HierarchicalFSM::new(Off, device);
+//!
+//! // Entering `On` enters its initial child, `Idle`
+//! assert!(machine.apply_assign(Inputs::PowerOn));
+//! assert_eq!(machine.state(), &Idle);
+//! assert!(machine.is_in(&On));
+//!
+//! machine.run([Inputs::Start, Inputs::Toggle]);
+//! assert_eq!(machine.state(), &Fast);
+//!
+//! // `Fast` has no transition for `Start`, nor do any of its ancestors
+//! assert!(!machine.apply_assign(Inputs::Start));
+//!
+//! struct Log(Vec<String>);
+//!
+//! impl Hooks<Inputs, States> for Log {
+//!     fn on_exit(&mut self, state: &States) {
+//!         self.0.push(format!("exit {state:?}"));
+//!     }
+//!
+//!     fn on_enter(&mut self, state: &States) {
+//!         self.0.push(format!("enter {state:?}"));
+//!     }
+//! }
+//!
+//! let mut log = Log(Vec::new());
+//! machine.apply_assign_with_hooks(Inputs::PowerOff, &mut log);
+//! assert_eq!(machine.state(), &Off);
+//! assert_eq!(log.0, ["exit Fast", "exit Running", "exit On", "enter Off"]);
+//!
+//! // Entering a child of `Running` directly enters `Running` and `On` first
+//! # DefineHierarchicalTransform!(jump, States, Inputs, (Off, _) => Fast);
+//! # let mut machine = HierarchicalFSM::new(Off, jump);
+//! log.0.clear();
+//! machine.apply_assign_with_hooks(Inputs::Toggle, &mut log);
+//! assert_eq!(log.0, ["exit Off", "enter On", "enter Running", "enter Fast"]);
+//! ```
+//!
+//! ## History
+//! ```
+//! use fsm::{hierarchy::HierarchicalFSM, DefineHierarchicalTransform, DefineHierarchy};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum States { Off, On, Idle, Running, Fast, Slow, Resume, ResumeExactly }
+//! use States::*;
+//!
+//! enum Inputs { PowerOff, Resume, ResumeExactly, Start, Toggle }
+//!
+//! DefineHierarchy!(States,
+//!     On => [Idle, Running; Shallow(Resume), Deep(ResumeExactly)],
+//!     Running => [Slow, Fast],
+//! );
+//!
+//! DefineHierarchicalTransform!(device, States, Inputs,
+//!     (Off, Inputs::Resume) => Resume,
+//!     (Off, Inputs::ResumeExactly) => ResumeExactly,
+//!     (On, Inputs::PowerOff) => Off,
+//!     (Idle, Inputs::Start) => Running,
+//!     (Slow, Inputs::Toggle) => Fast,
+//! );
+//!
+//! let mut machine = HierarchicalFSM::new(Off, device).with_memory(Vec::new());
+//!
+//! // `On` has never been exited, so its initial child is entered
+//! machine.apply_assign(Inputs::ResumeExactly);
+//! assert_eq!(machine.state(), &Idle);
+//!
+//! machine.run([Inputs::Start, Inputs::Toggle, Inputs::PowerOff]);
+//!
+//! // Shallow history restores `Running`, then enters its initial child
+//! machine.apply_assign(Inputs::Resume);
+//! assert_eq!(machine.state(), &Slow);
+//!
+//! machine.run([Inputs::Toggle, Inputs::PowerOff]);
+//!
+//! // Deep history restores `Running` and `Fast`
+//! machine.apply_assign(Inputs::ResumeExactly);
+//! assert_eq!(machine.state(), &Fast);
+//! ```
+
+use crate::{AcceptStates, Hooks};
+
+/// This trait defines the nesting of states in a hierarchical state machine.
+///
+/// This can be implemented with [`DefineHierarchy!`](crate::DefineHierarchy).
+pub trait Hierarchy: Sized {
+    #[must_use]
+    /// Returns the parent of the state, or [`None`] if it is a top level state.
+    fn parent(&self) -> Option<Self>;
+
+    #[must_use]
+    #[inline]
+    /// Returns the child that is entered when the state is entered, or [`None`] if it is a leaf state.
+    ///
+    /// This defaults to [`None`] for every state.
+    fn initial(&self) -> Option<Self> {
+        None
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns the kind of history if the state is a history state, or [`None`] if it is not.
+    ///
+    /// A history state restores the active states of its parent, and its
+    /// [`initial`](Self::initial) state is entered if the parent has never been exited.
+    /// If it has no initial state, the parent's initial state is entered instead.
+    ///
+    /// This defaults to [`None`] for every state.
+    fn history(&self) -> Option<History> {
+        None
+    }
+
+    #[must_use]
+    /// Returns [`true`] if `self` is a proper ancestor of `state`.
+    fn is_ancestor_of(&self, state: &Self) -> bool
+    where
+        Self: PartialEq,
+    {
+        let mut state = state.parent();
+
+        while let Some(ancestor) = state {
+            if &ancestor == self {
+                return true;
+            }
+            state = ancestor.parent();
+        }
+
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The kind of a history state.
+pub enum History {
+    /// Restore the child of the parent state that was last active, then enter its initial descendants.
+    Shallow,
+    /// Restore every descendant of the parent state that was last active.
+    Deep,
+}
+
+/// Storage for the states that were active when each parent state was last exited.
+///
+/// This is implemented for `()`, which remembers nothing,
+/// and for a [`Vec`] of parent and leaf state pairs with the `std` feature.
+pub trait Memory<States> {
+    /// Remember that `leaf` was the active leaf state when `parent` was exited.
+    fn remember(&mut self, parent: &States, leaf: &States);
+
+    #[must_use]
+    /// Returns the active leaf state when `parent` was last exited,
+    /// or [`None`] if it has never been exited.
+    fn recall(&self, parent: &States) -> Option<States>;
+}
+
+impl<States> Memory<States> for () {
+    #[inline]
+    fn remember(&mut self, parent: &States, leaf: &States) {
+        let _ = (parent, leaf);
+    }
+
+    #[inline]
+    fn recall(&self, parent: &States) -> Option<States> {
+        let _ = parent;
+        None
+    }
+}
+
+#[cfg(feature = "std")]
+impl<States> Memory<States> for Vec<(States, States)>
+where
+    States: PartialEq + Clone,
+{
+    fn remember(&mut self, parent: &States, leaf: &States) {
+        match self.iter_mut().find(|(state, _)| state == parent) {
+            Some((_, last)) => last.clone_from(leaf),
+            None => self.push((parent.clone(), leaf.clone())),
+        }
+    }
+
+    fn recall(&self, parent: &States) -> Option<States> {
+        self.iter()
+            .find(|(state, _)| state == parent)
+            .map(|(_, leaf)| leaf.clone())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(unpredictable_function_pointer_comparisons)]
+/// A hierarchical state machine.
+///
+/// This contains the current leaf state and a partial transform function, that returns [`None`]
+/// if a state has no transition for an input.
+/// The transform function can be defined with [`DefineHierarchicalTransform!`](crate::DefineHierarchicalTransform).
+///
+/// The [`Memory`] records the states to restore when a [`History`] state is entered.
+/// By default, nothing is remembered, see [`with_memory`](Self::with_memory).
+pub struct HierarchicalFSM<Domain, States, M = ()> {
+    state: States,
+    transform: fn(&States, &Domain) -> Option<States>,
+    memory: M,
+}
+
+impl<Domain, States> HierarchicalFSM<Domain, States>
+where
+    States: Hierarchy + PartialEq,
+{
+    #[must_use]
+    #[inline]
+    /// Create a new hierarchical machine starting in `start_state`, and with the `transform` function.
+    ///
+    /// If the start state has children, the machine starts in its initial leaf state.
+    /// No entry actions are run for the start states, see [`start_with_hooks`](Self::start_with_hooks).
+    pub fn new(start_state: States, transform: fn(&States, &Domain) -> Option<States>) -> Self {
+        Self::start_with_hooks(start_state, transform, &mut ())
+    }
+
+    #[must_use]
+    #[inline]
+    /// Create a new hierarchical machine starting with the default state and with the `transform` function.
+    pub fn default_with_transform(transform: fn(&States, &Domain) -> Option<States>) -> Self
+    where
+        States: Default,
+    {
+        Self::new(States::default(), transform)
+    }
+
+    #[must_use]
+    /// Create a new hierarchical machine, like [`new`](Self::new),
+    /// running the entry actions of `hooks` for the start state and each of its ancestors.
+    pub fn start_with_hooks<H>(
+        start_state: States,
+        transform: fn(&States, &Domain) -> Option<States>,
+        hooks: &mut H,
+    ) -> Self
+    where
+        H: Hooks<Domain, States>,
+    {
+        Self {
+            state: enter(start_state, None, &(), hooks),
+            transform,
+            memory: (),
+        }
+    }
+}
+
+impl<Domain, States, M> HierarchicalFSM<Domain, States, M>
+where
+    States: Hierarchy + PartialEq,
+    M: Memory<States>,
+{
+    #[must_use]
+    #[inline]
+    /// Replace the machine's memory, which records the states to restore when a [`History`] state is entered.
+    pub fn with_memory<N>(self, memory: N) -> HierarchicalFSM<Domain, States, N>
+    where
+        N: Memory<States>,
+    {
+        HierarchicalFSM {
+            state: self.state,
+            transform: self.transform,
+            memory,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the machine's memory.
+    pub const fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current leaf state.
+    pub const fn state(&self) -> &States {
+        &self.state
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the transform function.
+    pub const fn transform_function(&self) -> fn(&States, &Domain) -> Option<States> {
+        self.transform
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the current leaf state.
+    pub fn into_state(self) -> States {
+        self.state
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the machine is in `state`, either as its current leaf state or one of its ancestors.
+    pub fn is_in(&self, state: &States) -> bool {
+        &self.state == state || state.is_ancestor_of(&self.state)
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current leaf state is marked as accepted.
+    pub fn is_accepted(&self) -> bool
+    where
+        States: AcceptStates,
+    {
+        self.state.is_accepted()
+    }
+
+    #[must_use]
+    /// Find the innermost active state with a transition for `input`, returning it and the target.
+    fn find_transition(&self, input: &Domain) -> Option<(States, States)>
+    where
+        States: Clone,
+    {
+        let mut source = self.state.clone();
+
+        loop {
+            if let Some(target) = (self.transform)(&source, input) {
+                return Some((source, target));
+            }
+            source = source.parent()?;
+        }
+    }
+
+    #[must_use = "this returns the result of the transformation, without modifying the original"]
+    #[inline]
+    /// Apply an input to the machine, returning the new state of the machine.
+    pub fn apply(mut self, input: Domain) -> Self
+    where
+        States: Clone,
+    {
+        self.apply_assign(input);
+        self
+    }
+
+    #[inline]
+    /// Apply an input to the machine in place,
+    /// returning [`false`] if no active state has a transition for the input.
+    pub fn apply_assign(&mut self, input: Domain) -> bool
+    where
+        States: Clone,
+    {
+        self.apply_assign_with_hooks(input, &mut ())
+    }
+
+    /// Apply an input to the machine in place, running `hooks`,
+    /// and returning [`false`] if no active state has a transition for the input.
+    ///
+    /// [`Hooks::on_exit`] and [`Hooks::on_enter`] are run for each state left and entered,
+    /// and [`Hooks::on_transition`] is given the old and new leaf states.
+    #[inline]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn apply_assign_with_hooks<H>(&mut self, input: Domain, hooks: &mut H) -> bool
+    where
+        States: Clone,
+        H: Hooks<Domain, States>,
+    {
+        self.dispatch_with_hooks(&input, hooks)
+    }
+
+    #[inline]
+    /// Apply a borrowed input to the machine in place,
+    /// returning [`false`] if no active state has a transition for the input.
+    pub fn dispatch(&mut self, input: &Domain) -> bool
+    where
+        States: Clone,
+    {
+        self.dispatch_with_hooks(input, &mut ())
+    }
+
+    /// Apply a borrowed input to the machine in place, running `hooks`,
+    /// and returning [`false`] if no active state has a transition for the input.
+    ///
+    /// See [`apply_assign_with_hooks`](Self::apply_assign_with_hooks).
+    pub fn dispatch_with_hooks<H>(&mut self, input: &Domain, hooks: &mut H) -> bool
+    where
+        States: Clone,
+        H: Hooks<Domain, States>,
+    {
+        let Some((source, target)) = self.find_transition(input) else {
+            return false;
+        };
+
+        self.transition_with_hooks(&source, target, input, hooks);
+        true
+    }
+
+    /// Take a transition from `source`, which must be active, to `target`, running `hooks`.
+    pub(crate) fn transition_with_hooks<H>(
+        &mut self,
+        source: &States,
+        target: States,
+        input: &Domain,
+        hooks: &mut H,
+    ) where
+        States: Clone,
+        H: Hooks<Domain, States>,
+    {
+        // The innermost proper ancestor of the source that contains the target
+        let mut domain = source.parent();
+        while let Some(ancestor) = &domain {
+            if ancestor.is_ancestor_of(&target) {
+                break;
+            }
+            domain = ancestor.parent();
+        }
+
+        let mut exiting = Some(self.state.clone());
+        while let Some(state) = exiting {
+            if domain.as_ref() == Some(&state) {
+                break;
+            }
+            hooks.on_exit(&state);
+            if state != self.state {
+                self.memory.remember(&state, &self.state);
+            }
+            exiting = state.parent();
+        }
+
+        let new_state = enter(target, domain.as_ref(), &self.memory, hooks);
+        hooks.on_transition(&self.state, input, &new_state);
+        self.state = new_state;
+    }
+
+    /// Apply a set of inputs to the machine in place.
+    pub fn run<I>(&mut self, inputs: I)
+    where
+        States: Clone,
+        I: IntoIterator<Item = Domain>,
+    {
+        self.run_with_hooks(inputs, &mut ());
+    }
+
+    /// Apply a set of inputs to the machine in place, running `hooks`.
+    pub fn run_with_hooks<I, H>(&mut self, inputs: I, hooks: &mut H)
+    where
+        States: Clone,
+        I: IntoIterator<Item = Domain>,
+        H: Hooks<Domain, States>,
+    {
+        for input in inputs {
+            self.apply_assign_with_hooks(input, hooks);
+        }
+    }
+}
+
+/// Enter `state` and its ancestors below `domain`, from the outermost inwards,
+/// then its initial descendants, returning the leaf state entered.
+///
+/// If `state` is a history state, its parent is entered instead, then the states in `memory`.
+fn enter<Domain, States, M, H>(
+    state: States,
+    domain: Option<&States>,
+    memory: &M,
+    hooks: &mut H,
+) -> States
+where
+    States: Hierarchy + PartialEq,
+    M: Memory<States>,
+    H: Hooks<Domain, States>,
+{
+    fn enter_ancestors<Domain, States, H>(state: &States, domain: Option<&States>, hooks: &mut H)
+    where
+        States: Hierarchy + PartialEq,
+        H: Hooks<Domain, States>,
+    {
+        if let Some(parent) = state.parent() {
+            if domain != Some(&parent) {
+                enter_ancestors(&parent, domain, hooks);
+            }
+        }
+        hooks.on_enter(state);
+    }
+
+    if let (Some(history), Some(parent)) = (state.history(), state.parent()) {
+        enter_ancestors(&parent, domain, hooks);
+
+        let restored = memory.recall(&parent).map(|leaf| match history {
+            History::Shallow => child_towards(&parent, leaf),
+            History::Deep => leaf,
+        });
+
+        return match restored
+            .or_else(|| state.initial())
+            .or_else(|| parent.initial())
+        {
+            Some(state) => enter(state, Some(&parent), memory, hooks),
+            None => parent,
+        };
+    }
+
+    enter_ancestors(&state, domain, hooks);
+
+    let mut state = state;
+    while let Some(child) = state.initial() {
+        hooks.on_enter(&child);
+        state = child;
+    }
+
+    state
+}
+
+/// Returns the ancestor of `state` that is a child of `parent`, or `state` if it is a child itself.
+fn child_towards<States>(parent: &States, state: States) -> States
+where
+    States: Hierarchy + PartialEq,
+{
+    let mut state = state;
+    while let Some(ancestor) = state.parent() {
+        if &ancestor == parent {
+            break;
+        }
+        state = ancestor;
+    }
+    state
+}
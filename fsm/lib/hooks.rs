@@ -0,0 +1,204 @@
+//! Hooks, for running side effects when a machine changes state.
+//!
+//! Hooks are run by the `_with_hooks` methods on [`FSM`](crate::FSM), and are statically dispatched,
+//! so the plain methods are unaffected and hooks that do nothing cost nothing.
+//! The transform function of a [`FSM`](crate::FSM) takes each input, so the `_with_hooks` methods
+//! clone it for [`Hooks::on_transition`]. For inputs that aren't [`Clone`], the `_with_state_hooks`
+//! methods only run [`on_exit`](Hooks::on_exit) and [`on_enter`](Hooks::on_enter).
+//!
+//! # Examples
+//! ```
+//! use fsm::{hooks::{OnEnter, OnTransition}, DefineTransform, FSM};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum Light {
+//!     Off,
+//!     On,
+//! }
+//!
+//! #[derive(Clone, Copy, Debug)]
+//! struct Toggle;
+//!
+//! DefineTransform!(light, Light, Toggle,
+//!     (Light::Off, Toggle) => Light::On,
+//!     (Light::On, Toggle) => Light::Off,
+//! );
+//!
+//! let mut switched_on = 0;
+//! let mut log = Vec::new();
+//!
+//! let mut hooks = (
+//!     OnEnter(|state: &Light| {
+//!         if *state == Light::On {
+//!             switched_on += 1;
+//!         }
+//!     }),
+//!     OnTransition(|from: &Light, input: &Toggle, to: &Light| {
+//!         log.push(format!("{from:?} --{input:?}-> {to:?}"));
+//!     }),
+//! );
+//!
+//! let machine = FSM::new(Light::Off, light).run_with_hooks([Toggle; 3], &mut hooks);
+//! assert_eq!(machine.state(), &Light::On);
+//!
+//! drop(hooks);
+//! assert_eq!(switched_on, 2);
+//! assert_eq!(log[0], "Off --Toggle-> On");
+//!
+//! // Inputs that aren't `Clone` can still be given to hooks for the states
+//! struct Press(String);
+//!
+//! DefineTransform!(press, Light, Press,
+//!     (Light::Off, _) => Light::On,
+//!     (Light::On, _) => Light::Off,
+//! );
+//!
+//! let mut entered = Vec::new();
+//! FSM::new(Light::Off, press).apply_with_state_hooks(
+//!     Press("button".to_owned()),
+//!     &mut OnEnter(|state: &Light| entered.push(*state)),
+//! );
+//! assert_eq!(entered, [Light::On]);
+//! ```
+
+#[allow(clippy::module_name_repetitions)]
+/// This trait defines actions to run when a machine changes state.
+///
+/// For each input, the hooks are run in the order:
+/// [`on_exit`](Self::on_exit) for the old state,
+/// [`on_transition`](Self::on_transition),
+/// then [`on_enter`](Self::on_enter) for the new state.
+/// Each hook is run for every input, including those that lead back to the same state.
+/// The only exception is the `_with_state_hooks` methods on [`FSM`](crate::FSM), which don't
+/// run [`on_transition`](Self::on_transition), so their inputs don't need to be [`Clone`].
+///
+/// Every hook does nothing by default.
+pub trait Hooks<Domain, States> {
+    #[inline]
+    /// Run when the machine leaves `state`.
+    fn on_exit(&mut self, state: &States) {
+        let _ = state;
+    }
+
+    #[inline]
+    /// Run when the machine moves from `from` to `to` after being given `input`.
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        let _ = (from, input, to);
+    }
+
+    #[inline]
+    /// Run when the machine enters `state`.
+    fn on_enter(&mut self, state: &States) {
+        let _ = state;
+    }
+}
+
+impl<Domain, States> Hooks<Domain, States> for () {}
+
+impl<Domain, States, H> Hooks<Domain, States> for &mut H
+where
+    H: Hooks<Domain, States> + ?Sized,
+{
+    #[inline]
+    fn on_exit(&mut self, state: &States) {
+        (**self).on_exit(state);
+    }
+
+    #[inline]
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        (**self).on_transition(from, input, to);
+    }
+
+    #[inline]
+    fn on_enter(&mut self, state: &States) {
+        (**self).on_enter(state);
+    }
+}
+
+impl<Domain, States, A, B> Hooks<Domain, States> for (A, B)
+where
+    A: Hooks<Domain, States>,
+    B: Hooks<Domain, States>,
+{
+    #[inline]
+    fn on_exit(&mut self, state: &States) {
+        self.0.on_exit(state);
+        self.1.on_exit(state);
+    }
+
+    #[inline]
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        self.0.on_transition(from, input, to);
+        self.1.on_transition(from, input, to);
+    }
+
+    #[inline]
+    fn on_enter(&mut self, state: &States) {
+        self.0.on_enter(state);
+        self.1.on_enter(state);
+    }
+}
+
+#[cfg(feature = "std")]
+/// A list of boxed hooks, run in order.
+impl<Domain, States> Hooks<Domain, States> for Vec<Box<dyn Hooks<Domain, States>>> {
+    fn on_exit(&mut self, state: &States) {
+        for hooks in self {
+            hooks.on_exit(state);
+        }
+    }
+
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        for hooks in self {
+            hooks.on_transition(from, input, to);
+        }
+    }
+
+    fn on_enter(&mut self, state: &States) {
+        for hooks in self {
+            hooks.on_enter(state);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Hooks that run a function when the machine enters a state.
+pub struct OnEnter<F>(pub F);
+
+impl<Domain, States, F> Hooks<Domain, States> for OnEnter<F>
+where
+    F: FnMut(&States),
+{
+    #[inline]
+    fn on_enter(&mut self, state: &States) {
+        (self.0)(state);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Hooks that run a function when the machine leaves a state.
+pub struct OnExit<F>(pub F);
+
+impl<Domain, States, F> Hooks<Domain, States> for OnExit<F>
+where
+    F: FnMut(&States),
+{
+    #[inline]
+    fn on_exit(&mut self, state: &States) {
+        (self.0)(state);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// Hooks that run a function when the machine moves between states.
+pub struct OnTransition<F>(pub F);
+
+impl<Domain, States, F> Hooks<Domain, States> for OnTransition<F>
+where
+    F: FnMut(&States, &Domain, &States),
+{
+    #[inline]
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        (self.0)(from, input, to);
+    }
+}
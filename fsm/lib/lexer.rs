@@ -0,0 +1,310 @@
+//! A maximal munch lexer, built from a prioritised list of machines.
+//!
+//! Each [`Rule`] pairs a token kind with a machine.
+//! At each position, every rule is run over the remaining input until its machine reaches a
+//! trap state or the input ends, and the rule with the longest accepted prefix produces the next token.
+//! If two rules accept prefixes of the same length, the rule given first wins.
+//!
+//! # Examples
+//! ```
+//! use fsm::{lexer::{self, Rule}, AcceptStates, DefineTransform, FSM};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum Kind {
+//!     If,
+//!     Ident,
+//!     Space,
+//! }
+//!
+//! #[derive(Clone, Copy, Default, AcceptStates)]
+//! enum Word {
+//!     #[default]
+//!     Empty,
+//!     #[accept]
+//!     Word,
+//!     #[trap]
+//!     Invalid,
+//! }
+//!
+//! DefineTransform!(word, Word, char,
+//!     (Word::Empty | Word::Word, 'a'..='z') => Word::Word,
+//!     (_, _) => Word::Invalid,
+//! );
+//!
+//! #[derive(Clone, Copy, Default, AcceptStates)]
+//! enum If {
+//!     #[default]
+//!     Empty,
+//!     I,
+//!     #[accept]
+//!     If,
+//!     #[trap]
+//!     Invalid,
+//! }
+//!
+//! DefineTransform!(keyword_if, If, char,
+//!     (If::Empty, 'i') => If::I,
+//!     (If::I, 'f') => If::If,
+//!     (_, _) => If::Invalid,
+//! );
+//!
+//! #[derive(Clone, Copy, Default, AcceptStates)]
+//! enum Space {
+//!     #[default]
+//!     Empty,
+//!     #[accept]
+//!     Space,
+//!     #[trap]
+//!     Invalid,
+//! }
+//!
+//! DefineTransform!(space, Space, char,
+//!     (Space::Empty | Space::Space, ' ') => Space::Space,
+//!     (_, _) => Space::Invalid,
+//! );
+//!
+//! let keyword_if = FSM::default_with_transform(keyword_if);
+//! let word = FSM::default_with_transform(word);
+//! let space = FSM::default_with_transform(space);
+//!
+//! // `If` is listed before `Ident`, so it wins ties
+//! let rules = [
+//!     Rule::new(Kind::If, &keyword_if),
+//!     Rule::new(Kind::Ident, &word),
+//!     Rule::new(Kind::Space, &space),
+//! ];
+//!
+//! let tokens: Vec<_> = lexer::str(&rules, "if iffy !")
+//!     .map(|result| result.map(|token| (*token.kind(), token.span())))
+//!     .collect();
+//!
+//! assert_eq!(tokens[0], Ok((Kind::If, 0..2)));
+//! assert_eq!(tokens[1], Ok((Kind::Space, 2..3)));
+//! assert_eq!(tokens[2], Ok((Kind::Ident, 3..7)));
+//! assert_eq!(tokens[3], Ok((Kind::Space, 7..8)));
+//! assert!(tokens[4].is_err());
+//! ```
+
+use core::{ops::Range, str::Chars};
+
+use crate::{AcceptStates, FSM};
+
+/// A machine that can match prefixes of its input.
+pub trait Pattern<Domain> {
+    #[must_use]
+    /// Returns the number of inputs in the longest prefix of `input` that the machine accepts,
+    /// or [`None`] if it accepts no prefix.
+    fn longest_prefix(&self, input: &mut dyn Iterator<Item = Domain>) -> Option<usize>;
+}
+
+impl<Domain, States> Pattern<Domain> for FSM<Domain, States>
+where
+    States: AcceptStates + Clone,
+{
+    fn longest_prefix(&self, input: &mut dyn Iterator<Item = Domain>) -> Option<usize> {
+        let transform = self.transform_function();
+        let mut state = self.state().clone();
+        let mut longest = state.is_accepted().then_some(0);
+
+        for (index, item) in input.enumerate() {
+            if state.is_trap() {
+                break;
+            }
+
+            state = transform(state, item);
+
+            if state.is_accepted() {
+                longest = Some(index + 1);
+            }
+        }
+
+        longest
+    }
+}
+
+#[derive(Clone, Copy)]
+/// A token kind, paired with the machine that recognises it.
+pub struct Rule<'a, Kind, Domain> {
+    kind: Kind,
+    pattern: &'a dyn Pattern<Domain>,
+}
+
+impl<'a, Kind, Domain> Rule<'a, Kind, Domain> {
+    #[must_use]
+    #[inline]
+    /// Create a new rule, producing tokens of `kind` for the prefixes accepted by `pattern`.
+    pub const fn new(kind: Kind, pattern: &'a dyn Pattern<Domain>) -> Self {
+        Self { kind, pattern }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the kind of token this rule produces.
+    pub const fn kind(&self) -> &Kind {
+        &self.kind
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A token produced by a [`Lexer`].
+pub struct Token<Kind> {
+    kind: Kind,
+    span: Range<usize>,
+}
+
+impl<Kind> Token<Kind> {
+    #[must_use]
+    #[inline]
+    /// Get the kind of the token.
+    pub const fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the position of the token in the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the kind of the token.
+    pub fn into_kind(self) -> Kind {
+        self.kind
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An error produced by a [`Lexer`] for input that no rule accepts.
+pub struct Error {
+    span: Range<usize>,
+}
+
+impl Error {
+    #[must_use]
+    #[inline]
+    /// Get the position of the input that could not be tokenized.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "no token matches the input at {:?}", self.span)
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[derive(Clone)]
+/// An iterator of tokens, produced from some input using maximal munch.
+///
+/// Each item is either a [`Token`] or an [`Error`] for a single input that no rule accepts.
+/// After an error, tokenizing continues from the next input.
+pub struct Lexer<'a, Kind, I>
+where
+    I: Iterator,
+{
+    rules: &'a [Rule<'a, Kind, I::Item>],
+    input: I,
+    position: usize,
+    width: fn(&I::Item) -> usize,
+}
+
+#[must_use]
+#[inline]
+/// Create a new lexer over `input`, where each input has a width of one.
+pub fn new<'a, Kind, I>(rules: &'a [Rule<'a, Kind, I::Item>], input: I) -> Lexer<'a, Kind, I>
+where
+    I: Iterator + Clone,
+{
+    with_width(rules, input, |_| 1)
+}
+
+#[must_use]
+#[inline]
+/// Create a new lexer over `input`, where the width of each input is given by `width`.
+/// Spans are given in terms of these widths.
+pub fn with_width<'a, Kind, I>(
+    rules: &'a [Rule<'a, Kind, I::Item>],
+    input: I,
+    width: fn(&I::Item) -> usize,
+) -> Lexer<'a, Kind, I>
+where
+    I: Iterator + Clone,
+{
+    Lexer {
+        rules,
+        input,
+        position: 0,
+        width,
+    }
+}
+
+#[must_use]
+#[inline]
+/// Create a new lexer over the characters of `input`.
+/// Spans are given in bytes, so they can be used to index `input`.
+pub fn str<'a, Kind>(
+    rules: &'a [Rule<'a, Kind, char>],
+    input: &'a str,
+) -> Lexer<'a, Kind, Chars<'a>> {
+    with_width(rules, input.chars(), |c| c.len_utf8())
+}
+
+impl<'a, Kind, I> Lexer<'a, Kind, I>
+where
+    I: Iterator + Clone,
+{
+    /// Find the rule with the longest non-empty match, preferring earlier rules.
+    fn longest_match(&self) -> Option<(&'a Rule<'a, Kind, I::Item>, usize)> {
+        let mut longest: Option<(&Rule<Kind, I::Item>, usize)> = None;
+
+        for rule in self.rules {
+            let Some(length) = rule.pattern.longest_prefix(&mut self.input.clone()) else {
+                continue;
+            };
+
+            if length > longest.map_or(0, |(_, longest)| longest) {
+                longest = Some((rule, length));
+            }
+        }
+
+        longest
+    }
+
+    /// Advance the input by `count` inputs, returning the span covered.
+    fn advance(&mut self, count: usize) -> Range<usize> {
+        let start = self.position;
+
+        for item in self.input.by_ref().take(count) {
+            self.position += (self.width)(&item);
+        }
+
+        start..self.position
+    }
+}
+
+impl<Kind, I> Iterator for Lexer<'_, Kind, I>
+where
+    Kind: Clone,
+    I: Iterator + Clone,
+{
+    type Item = Result<Token<Kind>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.input.clone().next()?;
+
+        Some(match self.longest_match() {
+            Some((rule, length)) => Ok(Token {
+                kind: rule.kind.clone(),
+                span: self.advance(length),
+            }),
+            None => Err(Error {
+                span: self.advance(1),
+            }),
+        })
+    }
+}
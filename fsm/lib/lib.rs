@@ -74,16 +74,66 @@
 )]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "std")]
+extern crate alloc;
+
 mod accept_states;
+mod classify_states;
+#[cfg(feature = "std")]
+mod cursor;
+mod finite;
 mod fsm;
+#[cfg(feature = "serde")]
+mod registry;
+mod state_output;
 mod transform;
 pub use accept_states::AcceptStates;
+#[cfg(target_has_atomic = "8")]
+pub use atomic::{AtomicFSM, AtomicState};
+pub use classify_states::ClassifyStates;
+#[cfg(feature = "std")]
+pub use dynamic::DynFSM;
+pub use finite::Finite;
 pub use fsm::FSM;
+pub use hooks::Hooks;
+pub use moore::MooreMachine;
+#[cfg(feature = "serde")]
+pub use registry::Registry;
+pub use state_output::StateOutput;
+pub use timed::TimedFSM;
+pub use transducer::Transducer;
+#[cfg(feature = "std")]
+pub use undo::HistoryFSM;
+#[cfg(target_has_atomic = "8")]
+pub mod atomic;
+#[cfg(feature = "std")]
+pub mod debug;
+#[cfg(feature = "std")]
+pub mod dynamic;
+pub mod hierarchy;
+pub mod hooks;
 pub mod intersection;
+pub mod lexer;
+pub mod moore;
+pub mod regions;
+#[cfg(feature = "std")]
+pub mod scxml;
+#[cfg(feature = "std")]
+pub mod table;
+#[cfg(feature = "std")]
+pub mod text;
+pub mod timed;
+pub mod trace;
+pub mod transducer;
+#[cfg(feature = "std")]
+pub mod undo;
 pub mod union;
 
 #[cfg(feature = "derive")]
-pub use fsm_derive::AcceptStates;
+pub use fsm_derive::{
+    fsm, AcceptStates, AtomicState, ClassifyStates, Finite, StateOutput, Transitions, Typestate,
+    ValidatedTransform,
+};
 
 #[cfg(test)]
 mod test {
@@ -0,0 +1,155 @@
+//! Moore machines, where the output of a machine is a function of its state.
+
+use crate::{AcceptStates, StateOutput, FSM};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// A Moore machine.
+///
+/// This wraps a [`FSM`], whose states implement [`StateOutput`].
+pub struct MooreMachine<Domain, States> {
+    machine: FSM<Domain, States>,
+}
+
+impl<Domain, States> MooreMachine<Domain, States>
+where
+    States: StateOutput,
+{
+    #[must_use]
+    #[inline]
+    /// Create a new Moore machine starting with the `start_state` and with the `transform` function.
+    pub const fn new(start_state: States, transform: fn(States, Domain) -> States) -> Self {
+        Self::from_machine(FSM::new(start_state, transform))
+    }
+
+    #[must_use]
+    #[inline]
+    /// Create a new Moore machine starting with the default state and with the `transform` function.
+    pub fn default_with_transform(transform: fn(States, Domain) -> States) -> Self
+    where
+        States: Default,
+    {
+        Self::from_machine(FSM::default_with_transform(transform))
+    }
+
+    #[must_use]
+    #[inline]
+    /// Create a new Moore machine from a Finite State Machine.
+    pub const fn from_machine(machine: FSM<Domain, States>) -> Self {
+        Self { machine }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the output of the current state.
+    pub fn output(&self) -> States::Output {
+        self.machine.state().output()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current state.
+    pub const fn state(&self) -> &States {
+        self.machine.state()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the underlying Finite State Machine.
+    pub const fn machine(&self) -> &FSM<Domain, States> {
+        &self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the underlying Finite State Machine.
+    pub fn into_machine(self) -> FSM<Domain, States> {
+        self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is marked as accepted.
+    pub fn is_accepted(&self) -> bool
+    where
+        States: AcceptStates,
+    {
+        self.machine.is_accepted()
+    }
+
+    #[must_use = "this returns the result of the transformation, without modifying the original"]
+    #[inline]
+    /// Apply an input to the Moore machine, returning the new state of the machine.
+    pub fn apply(self, input: Domain) -> Self {
+        Self::from_machine(self.machine.apply(input))
+    }
+
+    #[inline]
+    /// Apply an input to the Moore machine in place, returning the output of the new state.
+    pub fn apply_assign(&mut self, input: Domain) -> States::Output
+    where
+        States: Clone,
+    {
+        self.machine.apply_assign(input);
+        self.output()
+    }
+
+    #[must_use = "this returns the result of the transformations, without modifying the original"]
+    /// Apply a set of inputs to the Moore machine, returning the new state of the machine.
+    pub fn run<I>(self, inputs: I) -> Self
+    where
+        I: IntoIterator<Item = Domain>,
+    {
+        Self::from_machine(self.machine.run(inputs))
+    }
+
+    /// Apply a set of inputs to the Moore machine in place, lazily.
+    /// The returned iterator yields the output of the state reached after each input.
+    ///
+    /// The output of the start state is not included, see [`output`](Self::output).
+    pub fn run_outputs<I>(&mut self, inputs: I) -> Outputs<'_, Domain, States, I::IntoIter>
+    where
+        States: Clone,
+        I: IntoIterator<Item = Domain>,
+    {
+        Outputs {
+            machine: self,
+            inputs: inputs.into_iter(),
+        }
+    }
+}
+
+impl<Domain, States> From<FSM<Domain, States>> for MooreMachine<Domain, States>
+where
+    States: StateOutput,
+{
+    #[inline]
+    fn from(machine: FSM<Domain, States>) -> Self {
+        Self::from_machine(machine)
+    }
+}
+
+#[derive(Debug)]
+/// An iterator over the outputs of a Moore machine, see [`MooreMachine::run_outputs`].
+pub struct Outputs<'a, Domain, States, I> {
+    machine: &'a mut MooreMachine<Domain, States>,
+    inputs: I,
+}
+
+impl<Domain, States, I> Iterator for Outputs<'_, Domain, States, I>
+where
+    States: StateOutput + Clone,
+    I: Iterator<Item = Domain>,
+{
+    type Item = States::Output;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.inputs.next()?;
+        Some(self.machine.apply_assign(input))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inputs.size_hint()
+    }
+}
@@ -0,0 +1,487 @@
+//! Orthogonal regions, where several machines run in parallel and all receive every input.
+//!
+//! This generalises [`union`](crate::union) and [`intersection`](crate::intersection) to any number
+//! of machines of any kind that implements [`Region`].
+//! A [`Parallel`] set of regions can be used on its own, or as a composite state inside a
+//! [`HierarchicalFSM`] with [`Orthogonal`].
+//!
+//! Inputs are broadcast to the regions in order: by index for arrays, and by position for tuples.
+//!
+//! An [`FSM`] region handles an input only if the input changes its state, so inputs that
+//! loop back to the same state are passed on to the hierarchical machine by [`Orthogonal`].
+//! To handle inputs explicitly, use a [`HierarchicalFSM`] as the region, whose transform
+//! function returns [`None`] for inputs it does not handle.
+//!
+//! # Examples
+//! ```
+//! use fsm::{regions::{Join, Parallel}, AcceptStates, DefineTransform, FSM};
+//!
+//! #[derive(Clone, Copy, Default, PartialEq, AcceptStates)]
+//! enum Seen {
+//!     #[default]
+//!     No,
+//!     #[accept]
+//!     Yes,
+//! }
+//!
+//! DefineTransform!(seen_a, Seen, char, (_, 'a') => Seen::Yes, (seen, _) => seen);
+//! DefineTransform!(seen_b, Seen, char, (_, 'b') => Seen::Yes, (seen, _) => seen);
+//!
+//! let machines = [FSM::new(Seen::No, seen_a), FSM::new(Seen::No, seen_b)];
+//!
+//! let mut all = Parallel::new(machines, Join::All);
+//! let mut any = Parallel::new(machines, Join::Any);
+//!
+//! for input in "xay".chars() {
+//!     all.dispatch(&input);
+//!     any.dispatch(&input);
+//! }
+//! assert!(!all.is_joined());
+//! assert!(any.is_joined());
+//!
+//! all.dispatch(&'b');
+//! assert!(all.is_joined());
+//! ```
+
+use crate::{
+    hierarchy::{HierarchicalFSM, Hierarchy, Memory},
+    AcceptStates, Hooks, FSM,
+};
+
+/// A machine that can be in a final state, such as a region of a [`Parallel`] set of regions.
+pub trait Final {
+    #[must_use]
+    /// Returns [`true`] if the machine is in a final state.
+    fn is_final(&self) -> bool;
+}
+
+/// A machine that can run as a region of a [`Parallel`] set of regions.
+///
+/// What counts as handling an input depends on the machine:
+/// - An [`FSM`] handles an input only if the input changes its state. Its transform function
+///   gives a new state for every input, so a self-loop can't be told apart from an input
+///   the machine ignores, and is not handled.
+/// - A [`HierarchicalFSM`] handles an input if its transform function returns [`Some`],
+///   including for self-loops, so use one as the region to handle inputs explicitly.
+/// - A [`Parallel`] set of regions handles an input if any region does.
+/// - An [`Orthogonal`] state handles an input if any of its regions does, and otherwise
+///   passes it on to its hierarchical machine, so an input that every [`FSM`] region
+///   loops on is handled by the hierarchical machine.
+///
+/// # Examples
+/// ```
+/// use fsm::{regions::Region, AcceptStates, DefineTransform, FSM};
+///
+/// #[derive(Clone, Copy, PartialEq, AcceptStates)]
+/// enum Door {
+///     Closed,
+///     #[accept]
+///     Open,
+/// }
+///
+/// DefineTransform!(door, Door, bool, (_, true) => Door::Open, (_, false) => Door::Closed);
+///
+/// let mut machine = FSM::new(Door::Closed, door);
+/// assert!(machine.dispatch(&true));
+///
+/// // Opening an open door loops back to the same state, so it is not handled
+/// assert!(!machine.dispatch(&true));
+/// ```
+pub trait Region<Domain>: Final {
+    /// Give an input to the region, returning [`true`] if it was handled.
+    fn dispatch(&mut self, input: &Domain) -> bool;
+}
+
+impl<Domain, States> Final for FSM<Domain, States>
+where
+    States: AcceptStates,
+{
+    /// Returns [`true`] if the machine is in an accept state.
+    #[inline]
+    fn is_final(&self) -> bool {
+        self.is_accepted()
+    }
+}
+
+impl<Domain, States> Region<Domain> for FSM<Domain, States>
+where
+    Domain: Clone,
+    States: AcceptStates + PartialEq + Clone,
+{
+    /// Apply the input to the machine.
+    /// The input is handled if the machine changes state, so inputs that loop back
+    /// to the same state are not handled.
+    #[inline]
+    fn dispatch(&mut self, input: &Domain) -> bool {
+        let old_state = self.state().clone();
+        self.apply_assign(input.clone());
+        self.state() != &old_state
+    }
+}
+
+impl<Domain, States, M> Final for HierarchicalFSM<Domain, States, M>
+where
+    States: Hierarchy + AcceptStates + PartialEq,
+    M: Memory<States>,
+{
+    /// Returns [`true`] if the leaf state is an accept state.
+    #[inline]
+    fn is_final(&self) -> bool {
+        self.is_accepted()
+    }
+}
+
+impl<Domain, States, M> Region<Domain> for HierarchicalFSM<Domain, States, M>
+where
+    States: Hierarchy + AcceptStates + PartialEq + Clone,
+    M: Memory<States>,
+{
+    /// Give the input to the machine, which handles it if its transform function
+    /// gives a transition for it.
+    #[inline]
+    fn dispatch(&mut self, input: &Domain) -> bool {
+        Self::dispatch(self, input)
+    }
+}
+
+/// A set of machines that can be in final states, such as the regions of a [`Parallel`] set.
+///
+/// This is implemented for arrays of machines and tuples of up to 4 machines.
+pub trait FinalRegions {
+    #[must_use]
+    /// Returns [`true`] if every region is in a final state.
+    fn all_final(&self) -> bool;
+
+    #[must_use]
+    /// Returns [`true`] if any region is in a final state.
+    fn any_final(&self) -> bool;
+}
+
+/// A set of regions, that inputs are broadcast to.
+///
+/// This is implemented for arrays of regions and tuples of up to 4 regions.
+pub trait Regions<Domain>: FinalRegions {
+    /// Give an input to every region in order, returning [`true`] if any region handled it.
+    fn broadcast(&mut self, input: &Domain) -> bool;
+}
+
+impl<R, const N: usize> FinalRegions for [R; N]
+where
+    R: Final,
+{
+    fn all_final(&self) -> bool {
+        self.iter().all(Final::is_final)
+    }
+
+    fn any_final(&self) -> bool {
+        self.iter().any(Final::is_final)
+    }
+}
+
+impl<Domain, R, const N: usize> Regions<Domain> for [R; N]
+where
+    R: Region<Domain>,
+{
+    fn broadcast(&mut self, input: &Domain) -> bool {
+        let mut handled = false;
+        for region in self {
+            handled |= region.dispatch(input);
+        }
+        handled
+    }
+}
+
+macro_rules! impl_regions_for_tuple {
+    ( $($region:ident $index:tt),+ ) => {
+        impl<$($region),+> FinalRegions for ($($region,)+)
+        where
+            $($region: Final),+
+        {
+            fn all_final(&self) -> bool {
+                $(self.$index.is_final())&&+
+            }
+
+            fn any_final(&self) -> bool {
+                $(self.$index.is_final())||+
+            }
+        }
+
+        impl<Domain, $($region),+> Regions<Domain> for ($($region,)+)
+        where
+            $($region: Region<Domain>),+
+        {
+            fn broadcast(&mut self, input: &Domain) -> bool {
+                let mut handled = false;
+                $(handled |= self.$index.dispatch(input);)+
+                handled
+            }
+        }
+    };
+}
+
+impl_regions_for_tuple!(A 0);
+impl_regions_for_tuple!(A 0, B 1);
+impl_regions_for_tuple!(A 0, B 1, C 2);
+impl_regions_for_tuple!(A 0, B 1, C 2, D 3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The condition for a set of regions to be finished.
+pub enum Join {
+    /// Finished when every region is in a final state.
+    All,
+    /// Finished when any region is in a final state.
+    Any,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// A set of regions, running in parallel, with a join condition.
+pub struct Parallel<T> {
+    regions: T,
+    join: Join,
+}
+
+impl<T> Parallel<T> {
+    #[must_use]
+    #[inline]
+    /// Create a new set of parallel regions, that is finished when `join` is met.
+    pub const fn new(regions: T, join: Join) -> Self {
+        Self { regions, join }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the regions.
+    pub const fn regions(&self) -> &T {
+        &self.regions
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the regions.
+    pub fn into_regions(self) -> T {
+        self.regions
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the join condition.
+    pub const fn join(&self) -> Join {
+        self.join
+    }
+
+    #[inline]
+    /// Give an input to every region in order, returning [`true`] if any region handled it.
+    pub fn dispatch<Domain>(&mut self, input: &Domain) -> bool
+    where
+        T: Regions<Domain>,
+    {
+        self.regions.broadcast(input)
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the join condition is met.
+    pub fn is_joined(&self) -> bool
+    where
+        T: FinalRegions,
+    {
+        match self.join {
+            Join::All => self.regions.all_final(),
+            Join::Any => self.regions.any_final(),
+        }
+    }
+}
+
+impl<T> Final for Parallel<T>
+where
+    T: FinalRegions,
+{
+    /// Returns [`true`] if the join condition is met.
+    #[inline]
+    fn is_final(&self) -> bool {
+        self.is_joined()
+    }
+}
+
+impl<Domain, T> Region<Domain> for Parallel<T>
+where
+    T: Regions<Domain>,
+{
+    #[inline]
+    fn dispatch(&mut self, input: &Domain) -> bool {
+        Self::dispatch(self, input)
+    }
+}
+
+#[derive(Clone, Debug)]
+/// A hierarchical machine with a composite state that contains parallel regions.
+///
+/// While the machine is in the composite state, inputs are given to the regions first,
+/// and only passed to the hierarchical machine if no region handles them.
+/// When the regions' join condition is met, the machine takes a completion transition
+/// from the composite state to the `completion` state.
+/// The regions are reset to their initial states each time the composite state is entered.
+pub struct Orthogonal<Domain, States, T, M = ()> {
+    machine: HierarchicalFSM<Domain, States, M>,
+    composite: States,
+    completion: States,
+    initial: Parallel<T>,
+    regions: Parallel<T>,
+}
+
+impl<Domain, States, T, M> Orthogonal<Domain, States, T, M>
+where
+    States: Hierarchy + PartialEq + Clone,
+    T: Regions<Domain> + Clone,
+    M: Memory<States>,
+{
+    #[must_use]
+    #[inline]
+    /// Add parallel `regions` to the `composite` state of `machine`, that complete by moving to `completion`.
+    pub fn new(
+        machine: HierarchicalFSM<Domain, States, M>,
+        composite: States,
+        regions: Parallel<T>,
+        completion: States,
+    ) -> Self {
+        Self {
+            machine,
+            composite,
+            completion,
+            initial: regions.clone(),
+            regions,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the hierarchical machine.
+    pub const fn machine(&self) -> &HierarchicalFSM<Domain, States, M> {
+        &self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current leaf state of the hierarchical machine.
+    pub const fn state(&self) -> &States {
+        self.machine.state()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the parallel regions of the composite state.
+    pub const fn regions(&self) -> &Parallel<T> {
+        &self.regions
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the machine is in the composite state, so its regions are active.
+    pub fn in_regions(&self) -> bool {
+        self.machine.is_in(&self.composite)
+    }
+
+    /// Give an input to the machine, returning [`true`] if it was handled.
+    pub fn dispatch(&mut self, input: &Domain) -> bool {
+        self.dispatch_with_hooks(input, &mut ())
+    }
+
+    /// Give an input to the machine, running `hooks` for the hierarchical machine,
+    /// and returning [`true`] if it was handled.
+    pub fn dispatch_with_hooks<H>(&mut self, input: &Domain, hooks: &mut H) -> bool
+    where
+        H: Hooks<Domain, States>,
+    {
+        if self.in_regions() && self.regions.dispatch(input) {
+            if self.regions.is_joined() {
+                let composite = self.composite.clone();
+                self.machine.transition_with_hooks(
+                    &composite,
+                    self.completion.clone(),
+                    input,
+                    hooks,
+                );
+            }
+            return true;
+        }
+
+        let mut watch = WatchEntry {
+            hooks,
+            state: &self.composite,
+            entered: false,
+        };
+
+        if !self.machine.dispatch_with_hooks(input, &mut watch) {
+            return false;
+        }
+
+        if watch.entered {
+            self.regions = self.initial.clone();
+        }
+
+        true
+    }
+
+    /// Give a set of inputs to the machine.
+    pub fn run<I>(&mut self, inputs: I)
+    where
+        I: IntoIterator<Item = Domain>,
+    {
+        for input in inputs {
+            self.dispatch(&input);
+        }
+    }
+}
+
+impl<Domain, States, T, M> Final for Orthogonal<Domain, States, T, M>
+where
+    States: Hierarchy + AcceptStates + PartialEq,
+    M: Memory<States>,
+{
+    /// Returns [`true`] if the leaf state of the hierarchical machine is an accept state.
+    #[inline]
+    fn is_final(&self) -> bool {
+        self.machine.is_accepted()
+    }
+}
+
+impl<Domain, States, T, M> Region<Domain> for Orthogonal<Domain, States, T, M>
+where
+    States: Hierarchy + AcceptStates + PartialEq + Clone,
+    T: Regions<Domain> + Clone,
+    M: Memory<States>,
+{
+    #[inline]
+    fn dispatch(&mut self, input: &Domain) -> bool {
+        Self::dispatch(self, input)
+    }
+}
+
+/// Hooks that record if a state is entered, passing every hook on.
+struct WatchEntry<'a, H, States> {
+    hooks: &'a mut H,
+    state: &'a States,
+    entered: bool,
+}
+
+impl<Domain, States, H> Hooks<Domain, States> for WatchEntry<'_, H, States>
+where
+    States: PartialEq,
+    H: Hooks<Domain, States>,
+{
+    #[inline]
+    fn on_exit(&mut self, state: &States) {
+        self.hooks.on_exit(state);
+    }
+
+    #[inline]
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        self.hooks.on_transition(from, input, to);
+    }
+
+    #[inline]
+    fn on_enter(&mut self, state: &States) {
+        self.entered |= state == self.state;
+        self.hooks.on_enter(state);
+    }
+}
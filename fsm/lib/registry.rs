@@ -0,0 +1,208 @@
+use core::{fmt, marker::PhantomData};
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::FSM;
+
+/// A trait for states with named transform functions, allowing their machines to be serialized.
+///
+/// A transform function can't be serialized, so a machine is saved as its state and the name
+/// of its transform function, which is looked up in the registry when the machine is restored.
+/// The names should be stable, as they are stored with the saved machines.
+///
+/// This is usually implemented with [`RegisterTransforms!`](crate::RegisterTransforms).
+///
+/// # Limitations
+/// A machine only holds a pointer to its transform function, so [`transform_name`](Self::transform_name)
+/// compares functions by address, which isn't fully reliable:
+/// - The compiler may merge functions with identical bodies, so each registered function should
+///   behave differently, or a machine may be saved with the name of another function.
+/// - A function may have more than one address, for example if it is generic, `#[inline]`,
+///   or from another crate, as it can be compiled into more than one codegen unit.
+///   Then its pointer may not match the registered one, and saving the machine fails
+///   with a "the transform function is not registered" error, as it would for an unregistered function.
+///   Plain functions in the same crate as the registry, such as those from
+///   [`DefineTransform!`](crate::DefineTransform), usually have a single address.
+///
+/// # Examples
+/// ```
+/// use fsm::{DefineTransform, RegisterTransforms, FSM};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// enum States {
+///     Off,
+///     On,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Press;
+///
+/// DefineTransform!(toggle, States, Press,
+///     (States::Off, Press) => States::On,
+///     (States::On, Press) => States::Off,
+/// );
+///
+/// RegisterTransforms!(States, Press, toggle);
+///
+/// let machine = FSM::new(States::Off, toggle).apply(Press);
+/// let json = serde_json::to_string(&machine).unwrap();
+/// assert_eq!(json, r#"{"transform":"toggle","state":"On"}"#);
+///
+/// let restored: FSM<Press, States> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored, machine);
+/// ```
+pub trait Registry<Domain: 'static>: Sized + 'static {
+    #[allow(clippy::type_complexity)]
+    /// The transform functions and their names.
+    const TRANSFORMS: &'static [(&'static str, fn(Self, Domain) -> Self)];
+
+    #[must_use]
+    /// Get the name of a transform function, if it is registered.
+    ///
+    /// This returns [`None`] if the function isn't found by its address,
+    /// see the [limitations](Self#limitations).
+    fn transform_name(transform: fn(Self, Domain) -> Self) -> Option<&'static str> {
+        Self::TRANSFORMS
+            .iter()
+            .find(|(_, registered)| core::ptr::fn_addr_eq(*registered, transform))
+            .map(|(name, _)| *name)
+    }
+
+    #[must_use]
+    /// Get the transform function with a name, if there is one.
+    fn transform(name: &str) -> Option<fn(Self, Domain) -> Self> {
+        Self::TRANSFORMS
+            .iter()
+            .find(|(registered, _)| *registered == name)
+            .map(|(_, transform)| *transform)
+    }
+}
+
+#[macro_export]
+/// A macro to implement [`Registry`](crate::Registry) for a set of states.
+///
+/// Each transform function is named by its identifier, unless another name is given with `as`,
+/// which allows functions to be renamed without changing the saved machines.
+///
+/// # Syntax
+/// ```text
+/// RegisterTransforms!(<states>, <input domain>, <transform> [as <name>], ...);
+/// ```
+///
+/// # Examples
+/// ```
+/// use fsm::{DefineTransform, RegisterTransforms, Registry};
+///
+/// #[derive(Clone, Copy)]
+/// enum States { A, B }
+///
+/// DefineTransform!(stay, States, bool, (state, _) => state);
+/// DefineTransform!(flip, States, bool,
+///     (States::A, true) => States::B,
+///     (States::B, true) => States::A,
+///     (state, false) => state,
+/// );
+///
+/// RegisterTransforms!(States, bool, stay, flip as "toggle");
+///
+/// assert_eq!(States::transform_name(flip), Some("toggle"));
+/// assert!(States::transform("flip").is_none());
+/// ```
+macro_rules! RegisterTransforms {
+    ( $states:ty, $dom:ty, $($transform:path $(as $name:literal)?),* $(,)? ) => {
+        impl $crate::Registry<$dom> for $states {
+            const TRANSFORMS: &'static [(&'static str, fn($states, $dom) -> $states)] = &[
+                $(($crate::RegisterTransforms!(@name $transform $(as $name)?), $transform)),*
+            ];
+        }
+    };
+    (@name $transform:path) => { stringify!($transform) };
+    (@name $transform:path as $name:literal) => { $name };
+}
+
+#[derive(Serialize)]
+#[serde(rename = "FSM")]
+/// A machine being saved.
+struct Saved<'a, States> {
+    transform: &'static str,
+    state: &'a States,
+}
+
+impl<Domain, States> Serialize for FSM<Domain, States>
+where
+    Domain: 'static,
+    States: Serialize + Registry<Domain>,
+{
+    /// Serialize the machine as its state and the name of its transform function.
+    ///
+    /// This fails if the transform function is not registered.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let transform = States::transform_name(self.transform_function())
+            .ok_or_else(|| ser::Error::custom("the transform function is not registered"))?;
+
+        Saved {
+            transform,
+            state: self.state(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A transform function, deserialized from its name.
+struct Transform<Domain, States>(fn(States, Domain) -> States);
+
+impl<'de, Domain, States> Deserialize<'de> for Transform<Domain, States>
+where
+    Domain: 'static,
+    States: Registry<Domain>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(TransformVisitor(PhantomData))
+    }
+}
+
+struct TransformVisitor<Domain, States>(PhantomData<fn(States, Domain) -> States>);
+
+impl<Domain, States> de::Visitor<'_> for TransformVisitor<Domain, States>
+where
+    Domain: 'static,
+    States: Registry<Domain>,
+{
+    type Value = Transform<Domain, States>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the name of a registered transform function")
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+        States::transform(name)
+            .map(Transform)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(name), &self))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(
+    rename = "FSM",
+    bound = "Domain: 'static, States: Deserialize<'de> + Registry<Domain>"
+)]
+/// A machine being restored.
+struct Restored<Domain, States> {
+    transform: Transform<Domain, States>,
+    state: States,
+}
+
+impl<'de, Domain, States> Deserialize<'de> for FSM<Domain, States>
+where
+    Domain: 'static,
+    States: Deserialize<'de> + Registry<Domain>,
+{
+    /// Deserialize a machine, looking up its transform function by name.
+    ///
+    /// This fails if there is no transform function with the name.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Restored { transform, state } = Restored::deserialize(deserializer)?;
+        Ok(Self::new(state, transform.0))
+    }
+}
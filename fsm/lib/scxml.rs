@@ -0,0 +1,1151 @@
+//! Importing and running [SCXML](https://www.w3.org/TR/scxml/) statecharts.
+//!
+//! A document is parsed into a [`Statechart`], which is run by an [`Interpreter`]
+//! following the SCXML event-processing algorithm.
+//!
+//! The supported elements are `<scxml>`, `<state>`, `<parallel>`, `<final>`, `<initial>`,
+//! `<history>`, `<transition>`, `<onentry>` and `<onexit>`, and the executable content
+//! `<raise>`, `<send>` (to the external or internal queue of the interpreter, without delays)
+//! and `<log>` (which is ignored).
+//! Only the null data model is supported, so conditions must use the `In()` predicate.
+//! Parsing fails with an [`Error`] for anything else.
+//! Running also fails with an [`Error`] if the statechart never becomes stable,
+//! for example when eventless transitions form a cycle.
+//!
+//! # Examples
+//! ```
+//! use fsm::scxml::{Interpreter, Statechart};
+//!
+//! let chart: Statechart = r#"
+//!     <scxml xmlns="http://www.w3.org/2005/07/scxml" version="1.0" initial="off">
+//!         <state id="off">
+//!             <transition event="power" target="on"/>
+//!         </state>
+//!         <parallel id="on">
+//!             <transition event="power" target="off"/>
+//!             <state id="light">
+//!                 <state id="dim">
+//!                     <transition event="brighten" target="bright"/>
+//!                 </state>
+//!                 <state id="bright"/>
+//!             </state>
+//!             <state id="fan">
+//!                 <state id="still">
+//!                     <transition event="spin" cond="In('bright')" target="spinning"/>
+//!                 </state>
+//!                 <state id="spinning"/>
+//!             </state>
+//!         </parallel>
+//!     </scxml>
+//! "#.parse().unwrap();
+//!
+//! let mut machine = Interpreter::new(chart).unwrap();
+//! assert_eq!(machine.configuration().collect::<Vec<_>>(), ["off"]);
+//!
+//! machine.run(["power", "spin"]).unwrap();
+//! assert_eq!(
+//!     machine.configuration().collect::<Vec<_>>(),
+//!     ["on", "light", "dim", "fan", "still"]
+//! );
+//!
+//! // Event names match by prefix, so `brighten` matches an event named `brighten.now`
+//! machine.run(["brighten.now", "spin"]).unwrap();
+//! assert!(machine.is_active("bright") && machine.is_active("spinning"));
+//! ```
+
+mod xml;
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use core::{fmt, str::FromStr};
+
+use crate::hierarchy::History;
+
+/// The index of the `<scxml>` element, which contains every state.
+const ROOT: usize = 0;
+
+/// The most microsteps taken while processing an event, before the statechart is assumed to loop forever.
+const MAX_MICROSTEPS: usize = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An error produced when a SCXML document cannot be parsed, or a statechart cannot be run.
+pub enum Error {
+    /// The document is not well-formed XML.
+    Xml {
+        line: usize,
+        column: usize,
+        message: &'static str,
+    },
+    /// An element is not valid SCXML.
+    Invalid {
+        element: String,
+        message: &'static str,
+    },
+    /// More than one state has the same id.
+    DuplicateId(String),
+    /// A state id is referenced, but no state has that id.
+    UnknownState(String),
+    /// The document uses a feature of SCXML that is not supported.
+    Unsupported(String),
+    /// The statechart took too many microsteps without becoming stable,
+    /// for example because eventless transitions or raised events form a cycle.
+    Livelock,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml {
+                line,
+                column,
+                message,
+            } => write!(f, "invalid XML at line {line}, column {column}: {message}"),
+            Self::Invalid { element, message } => {
+                write!(f, "invalid `<{element}>` element: {message}")
+            }
+            Self::DuplicateId(id) => write!(f, "the state id `{id}` is used more than once"),
+            Self::UnknownState(id) => write!(f, "there is no state with the id `{id}`"),
+            Self::Unsupported(feature) => write!(f, "{feature} is not supported"),
+            Self::Livelock => write!(
+                f,
+                "the statechart took more than {MAX_MICROSTEPS} microsteps without becoming stable"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Atomic,
+    /// A state with children, entering them through its `initial` transition.
+    Compound {
+        initial: usize,
+    },
+    Parallel,
+    Final,
+    /// A history state of its `parent`, with a `default` transition for when no history is recorded.
+    History {
+        history: History,
+        parent: usize,
+        default: usize,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct State {
+    id: String,
+    kind: Kind,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    transitions: Vec<usize>,
+    on_entry: Vec<Action>,
+    on_exit: Vec<Action>,
+}
+
+#[derive(Clone, Debug)]
+struct Transition {
+    source: usize,
+    events: Vec<String>,
+    /// A state that must be active for the transition to be enabled.
+    condition: Option<usize>,
+    targets: Vec<usize>,
+    internal: bool,
+    actions: Vec<Action>,
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    /// Add an event to the internal queue.
+    Raise(String),
+    /// Add an event to the external queue.
+    Send(String),
+}
+
+#[derive(Clone, Debug)]
+/// A statechart, parsed from a SCXML document.
+///
+/// States are stored in document order, which is the order that they are entered in.
+pub struct Statechart {
+    name: Option<String>,
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+}
+
+impl Statechart {
+    /// Parse a SCXML document.
+    ///
+    /// States without an `id` are given one of the form `#<n>`, which can not clash with a valid id.
+    ///
+    /// # Errors
+    /// This returns an [`Error`] if the document is not valid SCXML,
+    /// or it uses a feature that is not supported.
+    pub fn parse(document: &str) -> Result<Self, Error> {
+        let root = xml::parse(document)?;
+
+        if root.name != "scxml" {
+            return Err(Error::Invalid {
+                element: root.name,
+                message: "the root element must be `<scxml>`",
+            });
+        }
+
+        match root.attribute("datamodel") {
+            None | Some("null") => {}
+            Some(datamodel) => {
+                return Err(Error::Unsupported(format!("the `{datamodel}` data model")))
+            }
+        }
+
+        let mut builder = Builder::default();
+        builder.state(&root, None)?;
+        builder.finish(root.attribute("name"))
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the name of the statechart, given by the `name` attribute of the `<scxml>` element.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Iterate over the ids of the states, in document order.
+    ///
+    /// History states are not included.
+    pub fn states(&self) -> impl Iterator<Item = &str> + '_ {
+        self.states
+            .iter()
+            .skip(1)
+            .filter(|state| !matches!(state.kind, Kind::History { .. }))
+            .map(|state| state.id.as_str())
+    }
+
+    fn is_descendant(&self, state: usize, ancestor: usize) -> bool {
+        let mut state = self.states[state].parent;
+
+        while let Some(parent) = state {
+            if parent == ancestor {
+                return true;
+            }
+            state = self.states[parent].parent;
+        }
+
+        false
+    }
+
+    /// The ancestors of `state`, from the innermost outwards, stopping before `stop`.
+    fn proper_ancestors(&self, state: usize, stop: Option<usize>) -> Vec<usize> {
+        let mut ancestors = Vec::new();
+        let mut state = self.states[state].parent;
+
+        while let Some(parent) = state {
+            if Some(parent) == stop {
+                break;
+            }
+            ancestors.push(parent);
+            state = self.states[parent].parent;
+        }
+
+        ancestors
+    }
+
+    /// The children of `state` that are not history states.
+    fn child_states(&self, state: usize) -> impl Iterator<Item = usize> + '_ {
+        self.states[state]
+            .children
+            .iter()
+            .copied()
+            .filter(|&child| !matches!(self.states[child].kind, Kind::History { .. }))
+    }
+
+    fn is_atomic(&self, state: usize) -> bool {
+        matches!(self.states[state].kind, Kind::Atomic | Kind::Final)
+    }
+}
+
+impl FromStr for Statechart {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(document: &str) -> Result<Self, Self::Err> {
+        Self::parse(document)
+    }
+}
+
+#[derive(Default)]
+/// Builds a [`Statechart`], resolving state ids once every state has been parsed.
+struct Builder {
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+    /// The targets and condition of each transition, before they are resolved.
+    unresolved: Vec<(Vec<String>, Option<String>)>,
+}
+
+impl Builder {
+    fn state(&mut self, element: &xml::Element, parent: Option<usize>) -> Result<usize, Error> {
+        let index = self.states.len();
+        let invalid = |message| Error::Invalid {
+            element: element.name.clone(),
+            message,
+        };
+
+        let kind = match element.name.as_str() {
+            "scxml" | "state" => Kind::Atomic,
+            "parallel" => Kind::Parallel,
+            "final" => Kind::Final,
+            "history" => return self.history(element, parent),
+            name => return Err(Error::Unsupported(format!("the `<{name}>` element"))),
+        };
+
+        self.push_state(element, kind, parent);
+
+        let is_root = parent.is_none();
+        let mut initial_element = None;
+
+        for child in &element.children {
+            match (kind, child.name.as_str()) {
+                (Kind::Atomic | Kind::Parallel, "state" | "parallel" | "final") => {
+                    self.state(child, Some(index))?;
+                }
+                (Kind::Atomic | Kind::Parallel, "history") if !is_root => {
+                    self.state(child, Some(index))?;
+                }
+                (Kind::Atomic, "initial") if !is_root => {
+                    if initial_element.replace(child).is_some() {
+                        return Err(invalid("there can only be one `<initial>` element"));
+                    }
+                }
+                (Kind::Atomic | Kind::Parallel, "transition") if !is_root => {
+                    let transition = self.transition(child, index, false)?;
+                    self.states[index].transitions.push(transition);
+                }
+                (Kind::Atomic | Kind::Parallel | Kind::Final, "onentry") if !is_root => {
+                    let actions = actions(child)?;
+                    self.states[index].on_entry.extend(actions);
+                }
+                (Kind::Atomic | Kind::Parallel | Kind::Final, "onexit") if !is_root => {
+                    let actions = actions(child)?;
+                    self.states[index].on_exit.extend(actions);
+                }
+                (_, name @ ("datamodel" | "donedata" | "invoke" | "script")) => {
+                    return Err(Error::Unsupported(format!("the `<{name}>` element")));
+                }
+                (_, _) => {
+                    return Err(Error::Invalid {
+                        element: child.name.clone(),
+                        message: "this element is not allowed here",
+                    })
+                }
+            }
+        }
+
+        match kind {
+            Kind::Atomic if is_root || self.child_states(index).next().is_some() => {
+                let initial = self.initial(element, initial_element, index)?;
+                self.states[index].kind = Kind::Compound { initial };
+            }
+            Kind::Atomic if element.attribute("initial").is_some() || initial_element.is_some() => {
+                return Err(invalid("an atomic state can not have an initial state"));
+            }
+            _ => {}
+        }
+
+        Ok(index)
+    }
+
+    /// Create a history state, whose only child is its default transition.
+    fn history(&mut self, element: &xml::Element, parent: Option<usize>) -> Result<usize, Error> {
+        let index = self.states.len();
+        let invalid = |message| Error::Invalid {
+            element: element.name.clone(),
+            message,
+        };
+
+        let history = match element.attribute("type") {
+            None | Some("shallow") => History::Shallow,
+            Some("deep") => History::Deep,
+            Some(_) => return Err(invalid("the type must be `shallow` or `deep`")),
+        };
+        let parent = parent.ok_or_else(|| invalid("a history state must have a parent"))?;
+
+        // The default transition is created before the state, which takes the next index
+        let mut default = None;
+        for child in &element.children {
+            match child.name.as_str() {
+                "transition" => {
+                    if default.is_some() {
+                        return Err(invalid("there can only be one default transition"));
+                    }
+                    default = Some(self.transition(child, index, true)?);
+                }
+                name @ ("datamodel" | "donedata" | "invoke" | "script") => {
+                    return Err(Error::Unsupported(format!("the `<{name}>` element")));
+                }
+                _ => {
+                    return Err(Error::Invalid {
+                        element: child.name.clone(),
+                        message: "this element is not allowed here",
+                    })
+                }
+            }
+        }
+        let default =
+            default.ok_or_else(|| invalid("a history state must have a default transition"))?;
+
+        let kind = Kind::History {
+            history,
+            parent,
+            default,
+        };
+        Ok(self.push_state(element, kind, Some(parent)))
+    }
+
+    /// Add a state, with no transitions or actions yet, returning its index.
+    fn push_state(&mut self, element: &xml::Element, kind: Kind, parent: Option<usize>) -> usize {
+        let index = self.states.len();
+        self.states.push(State {
+            id: element
+                .attribute("id")
+                .map_or_else(|| format!("#{index}"), ToOwned::to_owned),
+            kind,
+            parent,
+            children: Vec::new(),
+            transitions: Vec::new(),
+            on_entry: Vec::new(),
+            on_exit: Vec::new(),
+        });
+        if let Some(parent) = parent {
+            self.states[parent].children.push(index);
+        }
+        index
+    }
+
+    fn child_states(&self, state: usize) -> impl Iterator<Item = usize> + '_ {
+        self.states[state]
+            .children
+            .iter()
+            .copied()
+            .filter(|&child| !matches!(self.states[child].kind, Kind::History { .. }))
+    }
+
+    /// Create the initial transition of a compound state.
+    fn initial(
+        &mut self,
+        element: &xml::Element,
+        initial_element: Option<&xml::Element>,
+        state: usize,
+    ) -> Result<usize, Error> {
+        let invalid = |message| Error::Invalid {
+            element: element.name.clone(),
+            message,
+        };
+
+        let targets = match (element.attribute("initial"), initial_element) {
+            (Some(_), Some(_)) => {
+                return Err(invalid(
+                    "the `initial` attribute and `<initial>` element can not both be used",
+                ))
+            }
+            (None, Some(initial)) => {
+                let [transition] = initial.children.as_slice() else {
+                    return Err(Error::Invalid {
+                        element: initial.name.clone(),
+                        message: "there must be exactly one `<transition>` element",
+                    });
+                };
+                if transition.name != "transition" {
+                    return Err(Error::Invalid {
+                        element: initial.name.clone(),
+                        message: "there must be exactly one `<transition>` element",
+                    });
+                }
+                return self.transition(transition, state, true);
+            }
+            (Some(initial), None) => initial.split_whitespace().map(ToOwned::to_owned).collect(),
+            (None, None) => {
+                let Some(first) = self.child_states(state).next() else {
+                    return Err(invalid("there must be at least one state"));
+                };
+                vec![self.states[first].id.clone()]
+            }
+        };
+
+        self.transitions.push(Transition {
+            source: state,
+            events: Vec::new(),
+            condition: None,
+            targets: Vec::new(),
+            internal: true,
+            actions: Vec::new(),
+        });
+        self.unresolved.push((targets, None));
+
+        Ok(self.transitions.len() - 1)
+    }
+
+    /// Create a transition, where `initial` is [`true`] for initial and history default transitions,
+    /// which must have a target, and no event or condition.
+    fn transition(
+        &mut self,
+        element: &xml::Element,
+        source: usize,
+        initial: bool,
+    ) -> Result<usize, Error> {
+        let invalid = |message| Error::Invalid {
+            element: element.name.clone(),
+            message,
+        };
+
+        let events: Vec<String> = element
+            .attribute("event")
+            .map(|events| events.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default();
+        let targets: Vec<String> = element
+            .attribute("target")
+            .map(|targets| targets.split_whitespace().map(ToOwned::to_owned).collect())
+            .unwrap_or_default();
+        let condition = element.attribute("cond").map(ToOwned::to_owned);
+
+        if initial && (!events.is_empty() || condition.is_some() || targets.is_empty()) {
+            return Err(invalid(
+                "an initial or default transition must have a target, and no event or condition",
+            ));
+        }
+
+        let internal = match element.attribute("type") {
+            None | Some("external") => initial,
+            Some("internal") => true,
+            Some(_) => return Err(invalid("the type must be `internal` or `external`")),
+        };
+
+        self.transitions.push(Transition {
+            source,
+            events,
+            condition: None,
+            targets: Vec::new(),
+            internal,
+            actions: actions(element)?,
+        });
+        self.unresolved.push((targets, condition));
+
+        Ok(self.transitions.len() - 1)
+    }
+
+    fn finish(mut self, name: Option<&str>) -> Result<Statechart, Error> {
+        let mut ids = BTreeMap::new();
+        for (index, state) in self.states.iter().enumerate().skip(1) {
+            if ids.insert(state.id.as_str(), index).is_some() {
+                return Err(Error::DuplicateId(state.id.clone()));
+            }
+        }
+
+        let resolve = |id: &str| {
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| Error::UnknownState(id.to_owned()))
+        };
+
+        for (transition, (targets, condition)) in self.transitions.iter_mut().zip(self.unresolved) {
+            transition.targets = targets
+                .iter()
+                .map(|id| resolve(id))
+                .collect::<Result<_, _>>()?;
+
+            if let Some(condition) = condition {
+                let id = condition
+                    .trim()
+                    .strip_prefix("In(")
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .map(str::trim)
+                    .and_then(|quoted| {
+                        quoted
+                            .strip_prefix('\'')
+                            .and_then(|id| id.strip_suffix('\''))
+                            .or_else(|| quoted.strip_prefix('"')?.strip_suffix('"'))
+                    })
+                    .ok_or_else(|| Error::Unsupported(format!("the condition `{condition}`")))?;
+
+                transition.condition = Some(resolve(id)?);
+            }
+        }
+
+        Ok(Statechart {
+            name: name.map(ToOwned::to_owned),
+            states: self.states,
+            transitions: self.transitions,
+        })
+    }
+}
+
+/// Parse the executable content of an element.
+fn actions(element: &xml::Element) -> Result<Vec<Action>, Error> {
+    let mut actions = Vec::new();
+
+    for child in &element.children {
+        let event = || {
+            child
+                .attribute("event")
+                .map(ToOwned::to_owned)
+                .ok_or_else(|| Error::Invalid {
+                    element: child.name.clone(),
+                    message: "the `event` attribute is required",
+                })
+        };
+
+        match child.name.as_str() {
+            "raise" => actions.push(Action::Raise(event()?)),
+            "send" => {
+                if let Some((attribute, _)) = child
+                    .attributes
+                    .iter()
+                    .find(|(attribute, _)| !matches!(attribute.as_str(), "event" | "target"))
+                {
+                    return Err(Error::Unsupported(format!(
+                        "the `{attribute}` attribute of `<send>`"
+                    )));
+                }
+                if !child.children.is_empty() {
+                    return Err(Error::Unsupported("content in `<send>`".to_owned()));
+                }
+
+                actions.push(match child.attribute("target") {
+                    None => Action::Send(event()?),
+                    Some("#_internal") => Action::Raise(event()?),
+                    Some(target) => {
+                        return Err(Error::Unsupported(format!("the send target `{target}`")))
+                    }
+                });
+            }
+            "log" => {}
+            name => return Err(Error::Unsupported(format!("the `<{name}>` element"))),
+        }
+    }
+
+    Ok(actions)
+}
+
+/// Returns [`true`] if `event` matches the event `descriptor` of a transition.
+///
+/// A descriptor matches an event with the same name, or any event whose name starts with
+/// the descriptor followed by a `.`, ignoring a trailing `.*` or `.` on the descriptor.
+fn matches(descriptor: &str, event: &str) -> bool {
+    if descriptor == "*" {
+        return true;
+    }
+
+    let descriptor = descriptor
+        .strip_suffix(".*")
+        .or_else(|| descriptor.strip_suffix('.'))
+        .unwrap_or(descriptor);
+
+    event
+        .strip_prefix(descriptor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+#[derive(Default)]
+/// The states to enter in a microstep, see [`Interpreter::enter_states`].
+struct Entry {
+    states: BTreeSet<usize>,
+    default_initial: BTreeSet<usize>,
+    default_history: BTreeMap<usize, usize>,
+}
+
+#[derive(Clone, Debug)]
+/// A running instance of a [`Statechart`].
+///
+/// Events are given to the interpreter with [`send`](Self::send), and are processed
+/// following the SCXML algorithm, until the statechart is stable:
+/// transitions without events are taken first, then events on the internal queue,
+/// then events on the external queue.
+///
+/// When a `<final>` child of the `<scxml>` element is entered, the interpreter stops,
+/// and its configuration is kept so that the final state can be inspected.
+/// The interpreter also stops, returning [`Error::Livelock`], if processing an event
+/// takes too many microsteps, so that a cycle of transitions can't hang the caller.
+pub struct Interpreter {
+    chart: Statechart,
+    configuration: BTreeSet<usize>,
+    history: BTreeMap<usize, Vec<usize>>,
+    internal: VecDeque<String>,
+    external: VecDeque<String>,
+    running: bool,
+}
+
+impl Interpreter {
+    /// Start running a statechart, entering its initial states.
+    ///
+    /// # Errors
+    /// This returns [`Error::Livelock`] if the statechart never becomes stable.
+    pub fn new(chart: Statechart) -> Result<Self, Error> {
+        let initial = match chart.states[ROOT].kind {
+            Kind::Compound { initial } => Some(initial),
+            _ => None,
+        };
+
+        let mut interpreter = Self {
+            chart,
+            configuration: BTreeSet::new(),
+            history: BTreeMap::new(),
+            internal: VecDeque::new(),
+            external: VecDeque::new(),
+            running: true,
+        };
+
+        interpreter.enter_states(initial.as_slice());
+        interpreter.process()?;
+        Ok(interpreter)
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the statechart being run.
+    pub const fn chart(&self) -> &Statechart {
+        &self.chart
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`false`] if the interpreter has stopped, after entering a top level final state
+    /// or returning [`Error::Livelock`].
+    pub const fn is_running(&self) -> bool {
+        self.running
+    }
+
+    #[must_use]
+    /// Returns [`true`] if the state with the id `id` is active.
+    pub fn is_active(&self, id: &str) -> bool {
+        self.configuration().any(|state| state == id)
+    }
+
+    /// Iterate over the ids of the active states, in document order.
+    pub fn configuration(&self) -> impl Iterator<Item = &str> + '_ {
+        self.configuration
+            .iter()
+            .map(|&state| self.chart.states[state].id.as_str())
+    }
+
+    /// Add an event to the external queue and process it,
+    /// along with any events that it causes.
+    ///
+    /// Events are ignored once the interpreter has stopped.
+    ///
+    /// # Errors
+    /// This returns [`Error::Livelock`], and stops the interpreter,
+    /// if the statechart doesn't become stable after the event.
+    pub fn send(&mut self, event: &str) -> Result<(), Error> {
+        if self.running {
+            self.external.push_back(event.to_owned());
+            self.process()?;
+        }
+        Ok(())
+    }
+
+    /// Send a set of events, in order.
+    ///
+    /// # Errors
+    /// This returns [`Error::Livelock`], and stops the interpreter,
+    /// if the statechart doesn't become stable after one of the events.
+    pub fn run<I>(&mut self, events: I) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for event in events {
+            self.send(event.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Take transitions until there are no eventless transitions enabled and the queues are empty,
+    /// stopping the interpreter if that takes more than [`MAX_MICROSTEPS`] microsteps.
+    fn process(&mut self) -> Result<(), Error> {
+        let mut microsteps = 0;
+
+        while self.running {
+            let mut enabled = self.select_transitions(None);
+
+            if enabled.is_empty() {
+                let Some(event) = self
+                    .internal
+                    .pop_front()
+                    .or_else(|| self.external.pop_front())
+                else {
+                    break;
+                };
+                enabled = self.select_transitions(Some(&event));
+            }
+
+            if !enabled.is_empty() {
+                if microsteps == MAX_MICROSTEPS {
+                    self.running = false;
+                    return Err(Error::Livelock);
+                }
+                microsteps += 1;
+                self.microstep(&enabled);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Select the transitions enabled by `event`, or the eventless transitions for [`None`].
+    fn select_transitions(&self, event: Option<&str>) -> Vec<usize> {
+        let chart = &self.chart;
+        let mut enabled = Vec::new();
+
+        for &state in self
+            .configuration
+            .iter()
+            .filter(|&&state| chart.is_atomic(state))
+        {
+            let found = core::iter::once(state)
+                .chain(chart.proper_ancestors(state, None))
+                .find_map(|state| {
+                    chart.states[state]
+                        .transitions
+                        .iter()
+                        .copied()
+                        .find(|&transition| {
+                            let transition = &chart.transitions[transition];
+                            let event_matches =
+                                event.map_or(transition.events.is_empty(), |event| {
+                                    transition
+                                        .events
+                                        .iter()
+                                        .any(|descriptor| matches(descriptor, event))
+                                });
+
+                            event_matches
+                                && transition
+                                    .condition
+                                    .is_none_or(|state| self.configuration.contains(&state))
+                        })
+                });
+
+            if let Some(transition) = found {
+                if !enabled.contains(&transition) {
+                    enabled.push(transition);
+                }
+            }
+        }
+
+        self.remove_conflicting_transitions(enabled)
+    }
+
+    /// Remove transitions that exit the same states as an earlier transition,
+    /// unless their source is a descendant of the earlier transition's source.
+    fn remove_conflicting_transitions(&self, enabled: Vec<usize>) -> Vec<usize> {
+        let mut filtered: Vec<usize> = Vec::new();
+
+        for transition in enabled {
+            let exit_set = self.compute_exit_set(&[transition]);
+            let source = self.chart.transitions[transition].source;
+            let mut preempted = false;
+            let mut to_remove = Vec::new();
+
+            for &other in &filtered {
+                if self
+                    .compute_exit_set(&[other])
+                    .intersection(&exit_set)
+                    .next()
+                    .is_some()
+                {
+                    if self
+                        .chart
+                        .is_descendant(source, self.chart.transitions[other].source)
+                    {
+                        to_remove.push(other);
+                    } else {
+                        preempted = true;
+                        break;
+                    }
+                }
+            }
+
+            if !preempted {
+                filtered.retain(|other| !to_remove.contains(other));
+                filtered.push(transition);
+            }
+        }
+
+        filtered
+    }
+
+    fn microstep(&mut self, enabled: &[usize]) {
+        self.exit_states(enabled);
+
+        for &transition in enabled {
+            execute(
+                &self.chart.transitions[transition].actions,
+                &mut self.internal,
+                &mut self.external,
+            );
+        }
+
+        self.enter_states(enabled);
+    }
+
+    fn exit_states(&mut self, enabled: &[usize]) {
+        let exit_set = self.compute_exit_set(enabled);
+        let chart = &self.chart;
+
+        for &state in &exit_set {
+            for &child in &chart.states[state].children {
+                let Kind::History { history, .. } = chart.states[child].kind else {
+                    continue;
+                };
+
+                let recorded = self
+                    .configuration
+                    .iter()
+                    .copied()
+                    .filter(|&active| match history {
+                        History::Shallow => chart.states[active].parent == Some(state),
+                        History::Deep => {
+                            chart.is_atomic(active) && chart.is_descendant(active, state)
+                        }
+                    })
+                    .collect();
+                self.history.insert(child, recorded);
+            }
+        }
+
+        for &state in exit_set.iter().rev() {
+            execute(
+                &chart.states[state].on_exit,
+                &mut self.internal,
+                &mut self.external,
+            );
+            self.configuration.remove(&state);
+        }
+    }
+
+    fn enter_states(&mut self, enabled: &[usize]) {
+        let entry = self.compute_entry_set(enabled);
+        let chart = &self.chart;
+
+        for &state in &entry.states {
+            self.configuration.insert(state);
+
+            let actions = &chart.states[state].on_entry;
+            execute(actions, &mut self.internal, &mut self.external);
+
+            if entry.default_initial.contains(&state) {
+                if let Kind::Compound { initial } = chart.states[state].kind {
+                    let actions = &chart.transitions[initial].actions;
+                    execute(actions, &mut self.internal, &mut self.external);
+                }
+            }
+            if let Some(&transition) = entry.default_history.get(&state) {
+                let actions = &chart.transitions[transition].actions;
+                execute(actions, &mut self.internal, &mut self.external);
+            }
+
+            if chart.states[state].kind != Kind::Final {
+                continue;
+            }
+
+            let Some(parent) = chart.states[state].parent else {
+                continue;
+            };
+            if parent == ROOT {
+                self.running = false;
+                continue;
+            }
+
+            self.internal
+                .push_back(format!("done.state.{}", chart.states[parent].id));
+
+            if let Some(grandparent) = chart.states[parent].parent {
+                if chart.states[grandparent].kind == Kind::Parallel
+                    && chart
+                        .child_states(grandparent)
+                        .all(|child| self.is_in_final_state(child))
+                {
+                    self.internal
+                        .push_back(format!("done.state.{}", chart.states[grandparent].id));
+                }
+            }
+        }
+    }
+
+    fn is_in_final_state(&self, state: usize) -> bool {
+        match self.chart.states[state].kind {
+            Kind::Compound { .. } => self.chart.child_states(state).any(|child| {
+                self.chart.states[child].kind == Kind::Final && self.configuration.contains(&child)
+            }),
+            Kind::Parallel => self
+                .chart
+                .child_states(state)
+                .all(|child| self.is_in_final_state(child)),
+            _ => false,
+        }
+    }
+
+    fn compute_exit_set(&self, transitions: &[usize]) -> BTreeSet<usize> {
+        let mut exit_set = BTreeSet::new();
+
+        for &transition in transitions {
+            if self.chart.transitions[transition].targets.is_empty() {
+                continue;
+            }
+
+            if let Some(domain) = self.transition_domain(transition) {
+                exit_set.extend(
+                    self.configuration
+                        .iter()
+                        .copied()
+                        .filter(|&state| self.chart.is_descendant(state, domain)),
+                );
+            }
+        }
+
+        exit_set
+    }
+
+    fn compute_entry_set(&self, transitions: &[usize]) -> Entry {
+        let mut entry = Entry::default();
+
+        for &transition in transitions {
+            for &target in &self.chart.transitions[transition].targets {
+                self.add_descendant_states_to_enter(target, &mut entry);
+            }
+
+            let Some(domain) = self.transition_domain(transition) else {
+                continue;
+            };
+            for state in self.effective_target_states(transition) {
+                self.add_ancestor_states_to_enter(state, domain, &mut entry);
+            }
+        }
+
+        entry
+    }
+
+    fn add_descendant_states_to_enter(&self, state: usize, entry: &mut Entry) {
+        let chart = &self.chart;
+
+        match chart.states[state].kind {
+            Kind::History {
+                parent, default, ..
+            } => {
+                let targets = if let Some(recorded) = self.history.get(&state) {
+                    recorded.clone()
+                } else {
+                    entry.default_history.insert(parent, default);
+                    chart.transitions[default].targets.clone()
+                };
+
+                for &target in &targets {
+                    self.add_descendant_states_to_enter(target, entry);
+                }
+                for &target in &targets {
+                    self.add_ancestor_states_to_enter(target, parent, entry);
+                }
+            }
+            Kind::Compound { initial } => {
+                entry.states.insert(state);
+                entry.default_initial.insert(state);
+
+                for &target in &chart.transitions[initial].targets {
+                    self.add_descendant_states_to_enter(target, entry);
+                }
+                for target in self.effective_target_states(initial) {
+                    self.add_ancestor_states_to_enter(target, state, entry);
+                }
+            }
+            Kind::Parallel => {
+                entry.states.insert(state);
+                self.add_parallel_children_to_enter(state, entry);
+            }
+            Kind::Atomic | Kind::Final => {
+                entry.states.insert(state);
+            }
+        }
+    }
+
+    fn add_ancestor_states_to_enter(&self, state: usize, ancestor: usize, entry: &mut Entry) {
+        for parent in self.chart.proper_ancestors(state, Some(ancestor)) {
+            entry.states.insert(parent);
+
+            if self.chart.states[parent].kind == Kind::Parallel {
+                self.add_parallel_children_to_enter(parent, entry);
+            }
+        }
+    }
+
+    /// Enter the children of a parallel state that have no descendants being entered.
+    fn add_parallel_children_to_enter(&self, state: usize, entry: &mut Entry) {
+        for child in self.chart.child_states(state) {
+            if !entry
+                .states
+                .iter()
+                .any(|&entered| self.chart.is_descendant(entered, child))
+            {
+                self.add_descendant_states_to_enter(child, entry);
+            }
+        }
+    }
+
+    /// The compound state whose descendants are exited and entered by a transition,
+    /// or [`None`] if it has no targets.
+    fn transition_domain(&self, transition: usize) -> Option<usize> {
+        let targets = self.effective_target_states(transition);
+        let source = self.chart.transitions[transition].source;
+
+        if targets.is_empty() {
+            None
+        } else if self.chart.transitions[transition].internal
+            && matches!(self.chart.states[source].kind, Kind::Compound { .. })
+            && targets
+                .iter()
+                .all(|&target| self.chart.is_descendant(target, source))
+        {
+            Some(source)
+        } else {
+            // The least common compound ancestor
+            self.chart
+                .proper_ancestors(source, None)
+                .into_iter()
+                .filter(|&ancestor| {
+                    matches!(self.chart.states[ancestor].kind, Kind::Compound { .. })
+                })
+                .find(|&ancestor| {
+                    targets
+                        .iter()
+                        .all(|&target| self.chart.is_descendant(target, ancestor))
+                })
+                .or(Some(ROOT))
+        }
+    }
+
+    /// The targets of a transition, with history states replaced by the states they restore.
+    fn effective_target_states(&self, transition: usize) -> BTreeSet<usize> {
+        let mut targets = BTreeSet::new();
+
+        for &target in &self.chart.transitions[transition].targets {
+            if let Kind::History { default, .. } = self.chart.states[target].kind {
+                if let Some(recorded) = self.history.get(&target) {
+                    targets.extend(recorded);
+                } else {
+                    targets.extend(self.effective_target_states(default));
+                }
+            } else {
+                targets.insert(target);
+            }
+        }
+
+        targets
+    }
+}
+
+/// Run executable content, adding any events raised or sent to the queues.
+fn execute(actions: &[Action], internal: &mut VecDeque<String>, external: &mut VecDeque<String>) {
+    for action in actions {
+        match action {
+            Action::Raise(event) => internal.push_back(event.clone()),
+            Action::Send(event) => external.push_back(event.clone()),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! A minimal XML parser, supporting the parts of XML used by SCXML documents.
+//!
+//! Namespace prefixes are removed from element names, and text content is ignored.
+//! Comments, processing instructions, CDATA sections and doctype declarations are skipped.
+
+use super::Error;
+use crate::cursor::Cursor;
+
+/// The deepest nesting of elements parsed, so that a document can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An element of an XML document.
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Self>,
+}
+
+impl Element {
+    /// Get the value of an attribute.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parse an XML document, returning its root element.
+pub fn parse(document: &str) -> Result<Element, Error> {
+    let mut parser = Parser {
+        cursor: Cursor::new(document),
+    };
+
+    parser.skip_misc()?;
+    let root = parser.element(0)?;
+    parser.skip_misc()?;
+
+    if !parser.cursor.rest().is_empty() {
+        return Err(parser.error("unexpected content after the root element"));
+    }
+
+    Ok(root)
+}
+
+struct Parser<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &'static str) -> Error {
+        let (line, column) = self.cursor.location();
+        Error::Xml {
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn expect(&mut self, prefix: &str, message: &'static str) -> Result<(), Error> {
+        if self.cursor.eat(prefix) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    /// Skip past the next `end`, returning the text before it.
+    fn skip_past(&mut self, end: &str, message: &'static str) -> Result<&'a str, Error> {
+        let Some(length) = self.cursor.rest().find(end) else {
+            return Err(self.error(message));
+        };
+
+        let skipped = &self.cursor.rest()[..length];
+        self.cursor.position += length + end.len();
+        Ok(skipped)
+    }
+
+    /// Skip comments, processing instructions, doctype declarations and whitespace.
+    fn skip_misc(&mut self) -> Result<(), Error> {
+        loop {
+            self.cursor.skip_whitespace();
+
+            if self.cursor.eat("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if self.cursor.eat("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if self.cursor.eat("<!DOCTYPE") {
+                self.skip_past(">", "unterminated doctype declaration")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, Error> {
+        let rest = self.cursor.rest();
+        let length = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+            .unwrap_or(rest.len());
+
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+
+        self.cursor.position += length;
+        Ok(&rest[..length])
+    }
+
+    /// Parse an element, nested inside `depth` others.
+    fn element(&mut self, depth: usize) -> Result<Element, Error> {
+        if depth == MAX_DEPTH {
+            return Err(self.error("elements are nested too deeply"));
+        }
+
+        self.expect("<", "expected an element")?;
+        let tag = self.name()?.to_owned();
+
+        let mut element = Element {
+            name: tag.rsplit(':').next().unwrap_or(&tag).to_owned(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+
+        loop {
+            self.cursor.skip_whitespace();
+
+            if self.cursor.eat("/>") {
+                return Ok(element);
+            }
+            if self.cursor.eat(">") {
+                break;
+            }
+
+            let name = self.name()?.to_owned();
+            self.cursor.skip_whitespace();
+            self.expect("=", "expected `=` after an attribute name")?;
+            self.cursor.skip_whitespace();
+
+            let value = self.attribute_value()?;
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            let start = self.cursor.position;
+            if self.cursor.eat("</") {
+                if self.name()? != tag {
+                    self.cursor.position = start;
+                    return Err(self.error("mismatched closing tag"));
+                }
+                self.cursor.skip_whitespace();
+                self.expect(">", "expected `>` after a closing tag")?;
+                return Ok(element);
+            }
+
+            if self.cursor.eat("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if self.cursor.eat("<![CDATA[") {
+                self.skip_past("]]>", "unterminated CDATA section")?;
+            } else if self.cursor.eat("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if self.cursor.rest().starts_with('<') {
+                element.children.push(self.element(depth + 1)?);
+            } else if self.cursor.rest().is_empty() {
+                return Err(self.error("unterminated element"));
+            } else {
+                // Text content is not used
+                let length = self
+                    .cursor
+                    .rest()
+                    .find('<')
+                    .unwrap_or_else(|| self.cursor.rest().len());
+                self.cursor.position += length;
+            }
+        }
+    }
+
+    fn attribute_value(&mut self) -> Result<String, Error> {
+        let quote = if self.cursor.eat("\"") {
+            "\""
+        } else if self.cursor.eat("'") {
+            "'"
+        } else {
+            return Err(self.error("expected a quoted attribute value"));
+        };
+
+        let start = self.cursor.position;
+        let raw = self.skip_past(quote, "unterminated attribute value")?;
+
+        let mut value = String::with_capacity(raw.len());
+        let mut rest = raw;
+        while let Some(ampersand) = rest.find('&') {
+            value.push_str(&rest[..ampersand]);
+            rest = &rest[ampersand + 1..];
+
+            let reference = rest.find(';').and_then(|end| {
+                let character = match &rest[..end] {
+                    "lt" => '<',
+                    "gt" => '>',
+                    "amp" => '&',
+                    "quot" => '"',
+                    "apos" => '\'',
+                    code => {
+                        let number = code.strip_prefix('#')?;
+                        let number = match number.strip_prefix('x') {
+                            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                            None => number.parse().ok()?,
+                        };
+                        char::from_u32(number)?
+                    }
+                };
+                Some((character, end))
+            });
+
+            let Some((character, end)) = reference else {
+                self.cursor.position = start + (raw.len() - rest.len()) - 1;
+                return Err(self.error("invalid character reference"));
+            };
+
+            value.push(character);
+            rest = &rest[end + 1..];
+        }
+        value.push_str(rest);
+
+        Ok(value)
+    }
+}
@@ -0,0 +1,12 @@
+#[allow(clippy::module_name_repetitions)]
+/// This trait defines outputs for the states of finite state machines, as in a Moore machine.
+///
+/// See [`MooreMachine`](crate::MooreMachine).
+pub trait StateOutput {
+    /// The type of the outputs.
+    type Output;
+
+    #[must_use]
+    /// Returns the output of the state.
+    fn output(&self) -> Self::Output;
+}
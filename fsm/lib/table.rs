@@ -0,0 +1,736 @@
+//! Table-driven machines, where the states, inputs and transitions are data.
+//!
+//! A [`Table`] can be built from any [`FSM`] with [`Finite`] states and inputs,
+//! written as [SCXML](Table::scxml) or [JSON](Table::json), read back from JSON,
+//! and run as a [`TableFSM`].
+//!
+//! States and inputs are named by their [`Debug`](fmt::Debug) representations,
+//! so for SCXML, the names should be valid XML ids and event names, without spaces or `.`s.
+//!
+//! # JSON schema
+//! A table is written as an object with the fields:
+//! - `states`: an array of the names of every state,
+//! - `inputs`: an array of the names of every input,
+//! - `start`: the name of the start state,
+//! - `accept`: an array of the names of the accept states,
+//! - `transitions`: an array with one transition for every state and input, as an object with the fields
+//!   `from` and `input`, and `to`, the name of the next state.
+//!
+//! Every name is a string, and the names of states, and the names of inputs, must be unique.
+//! Any other fields are ignored.
+//! ```json
+//! {
+//!   "states": ["Even", "Odd"],
+//!   "inputs": ["false", "true"],
+//!   "start": "Even",
+//!   "accept": ["Even"],
+//!   "transitions": [
+//!     {"from": "Even", "input": "false", "to": "Even"},
+//!     {"from": "Even", "input": "true", "to": "Odd"},
+//!     {"from": "Odd", "input": "false", "to": "Odd"},
+//!     {"from": "Odd", "input": "true", "to": "Even"}
+//!   ]
+//! }
+//! ```
+//!
+//! # Examples
+//! ```
+//! use fsm::{table::{Table, TableFSM}, AcceptStates, DefineTransform, Finite, FSM};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, AcceptStates, Finite)]
+//! enum Parity {
+//!     #[accept]
+//!     Even,
+//!     Odd,
+//! }
+//!
+//! DefineTransform!(parity, Parity, bool,
+//!     (state, false) => state,
+//!     (Parity::Even, true) => Parity::Odd,
+//!     (Parity::Odd, true) => Parity::Even,
+//! );
+//!
+//! let table = Table::from_fsm(&FSM::new(Parity::Even, parity)).unwrap();
+//!
+//! let json = table.json().to_string();
+//! assert_eq!(Table::from_json(&json), Ok(table.clone()));
+//!
+//! let mut machine = TableFSM::new(table);
+//! machine.run(["true", "false", "true", "true"]).unwrap();
+//! assert_eq!(machine.state(), "Odd");
+//! assert!(!machine.is_accepted());
+//! ```
+
+mod json;
+
+use alloc::collections::BTreeSet;
+use core::fmt;
+
+use crate::{AcceptStates, Finite, FSM};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An error produced when reading a table, or running a [`TableFSM`].
+pub enum Error {
+    /// The document is not valid JSON.
+    Json {
+        line: usize,
+        column: usize,
+        message: &'static str,
+    },
+    /// A field is missing, or has the wrong type.
+    InvalidField(&'static str),
+    /// A state or input name is used more than once.
+    DuplicateName(String),
+    /// A state name is referenced, but there is no state with that name.
+    UnknownState(String),
+    /// An input name is referenced, but there is no input with that name.
+    UnknownInput(String),
+    /// There is no transition for a state and input.
+    MissingTransition { state: String, input: String },
+    /// There is more than one transition for a state and input.
+    ConflictingTransitions { state: String, input: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json {
+                line,
+                column,
+                message,
+            } => write!(f, "invalid JSON at line {line}, column {column}: {message}"),
+            Self::InvalidField(field) => write!(f, "the `{field}` field is missing or invalid"),
+            Self::DuplicateName(name) => write!(f, "the name `{name}` is used more than once"),
+            Self::UnknownState(name) => write!(f, "there is no state named `{name}`"),
+            Self::UnknownInput(name) => write!(f, "there is no input named `{name}`"),
+            Self::MissingTransition { state, input } => {
+                write!(f, "there is no transition from `{state}` for `{input}`")
+            }
+            Self::ConflictingTransitions { state, input } => {
+                write!(
+                    f,
+                    "there is more than one transition from `{state}` for `{input}`"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Document", into = "Document")
+)]
+/// A transition table, with named states and inputs.
+///
+/// States and inputs are referred to by their indices, in the order they are listed.
+///
+/// With the `serde` feature, this is serialized following the [schema](self#json-schema).
+pub struct Table {
+    states: Vec<String>,
+    inputs: Vec<String>,
+    /// The indices of the inputs, sorted by name, to find an input by binary search.
+    order: Vec<usize>,
+    /// The next state for each state and input, by state then input.
+    transitions: Vec<usize>,
+    start: usize,
+    accept: Vec<bool>,
+}
+
+impl Table {
+    /// Create a table from a machine, listing every transition of its transform function,
+    /// and starting from its current state.
+    ///
+    /// The states and inputs are named by their [`Debug`](fmt::Debug) output.
+    ///
+    /// # Errors
+    /// This returns [`Error::DuplicateName`] if two states or two inputs have the same name.
+    pub fn from_fsm<Domain, States>(machine: &FSM<Domain, States>) -> Result<Self, Error>
+    where
+        Domain: Finite + Clone + fmt::Debug,
+        States: Finite + AcceptStates + Clone + fmt::Debug,
+    {
+        let transform = machine.transform_function();
+
+        let transitions = States::VALUES
+            .iter()
+            .flat_map(|state| {
+                Domain::VALUES
+                    .iter()
+                    .map(move |input| transform(state.clone(), input.clone()).index())
+            })
+            .collect();
+
+        let states = States::VALUES
+            .iter()
+            .map(|state| format!("{state:?}"))
+            .collect::<Vec<_>>();
+        let inputs = Domain::VALUES
+            .iter()
+            .map(|input| format!("{input:?}"))
+            .collect::<Vec<_>>();
+        check_unique(&states)?;
+        check_unique(&inputs)?;
+
+        Ok(Self {
+            order: order(&inputs),
+            states,
+            inputs,
+            transitions,
+            start: machine.state().index(),
+            accept: States::VALUES
+                .iter()
+                .map(AcceptStates::is_accepted)
+                .collect(),
+        })
+    }
+
+    /// Read a table from JSON, following the [schema](self#json-schema).
+    ///
+    /// # Errors
+    /// This returns an [`Error`] if the document is not valid JSON, or does not follow the schema.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let document = json::parse(json)?;
+
+        let name = |value: &json::Value, field| {
+            value
+                .as_str()
+                .map(ToOwned::to_owned)
+                .ok_or(Error::InvalidField(field))
+        };
+        let list = |field| {
+            document
+                .field(field)
+                .and_then(json::Value::as_array)
+                .ok_or(Error::InvalidField(field))
+        };
+        let names = |field| {
+            list(field)?
+                .iter()
+                .map(|value| name(value, field))
+                .collect::<Result<_, _>>()
+        };
+
+        let transitions = list("transitions")?
+            .iter()
+            .map(|transition| {
+                let field = |field| {
+                    name(
+                        transition
+                            .field(field)
+                            .ok_or(Error::InvalidField("transitions"))?,
+                        "transitions",
+                    )
+                };
+
+                Ok(Transition {
+                    from: field("from")?,
+                    input: field("input")?,
+                    to: field("to")?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Self::try_from(Document {
+            states: names("states")?,
+            inputs: names("inputs")?,
+            start: name(
+                document
+                    .field("start")
+                    .ok_or(Error::InvalidField("start"))?,
+                "start",
+            )?,
+            accept: names("accept")?,
+            transitions,
+        })
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the names of the states.
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the names of the inputs.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the start state.
+    pub const fn start(&self) -> usize {
+        self.start
+    }
+
+    #[must_use]
+    /// Get the index of the state named `name`.
+    pub fn state_index(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state == name)
+    }
+
+    #[must_use]
+    /// Get the index of the input named `name`.
+    pub fn input_index(&self, name: &str) -> Option<usize> {
+        self.order
+            .binary_search_by(|&other| self.inputs[other].as_str().cmp(name))
+            .ok()
+            .map(|position| self.order[position])
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the state after giving the input at index `input` to the state at index `state`.
+    ///
+    /// # Panics
+    /// This will panic if either index is out of range.
+    pub fn next(&self, state: usize, input: usize) -> usize {
+        assert!(input < self.inputs.len(), "the input index is out of range");
+        self.transitions[state * self.inputs.len() + input]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the state at index `state` is an accept state.
+    ///
+    /// # Panics
+    /// This will panic if the index is out of range.
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accept[state]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get a writer for the table as a SCXML document.
+    ///
+    /// Accept states are marked with an `accept="true"` attribute in the
+    /// `https://github.com/tomboddaert/fsm` namespace, which other tools will ignore.
+    /// Transitions from a state to the same target are combined.
+    pub const fn scxml(&self) -> Scxml<'_> {
+        Scxml { table: self }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get a writer for the table as JSON, following the [schema](self#json-schema).
+    pub const fn json(&self) -> Json<'_> {
+        Json { table: self }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A table, as described by the [schema](self#json-schema).
+struct Document {
+    states: Vec<String>,
+    inputs: Vec<String>,
+    start: String,
+    accept: Vec<String>,
+    transitions: Vec<Transition>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A transition, as described by the [schema](self#json-schema).
+struct Transition {
+    from: String,
+    input: String,
+    to: String,
+}
+
+/// Get the indices of some names, sorted by name, to find a name by binary search.
+fn order(names: &[String]) -> Vec<usize> {
+    let mut order = (0..names.len()).collect::<Vec<_>>();
+    order.sort_unstable_by(|&a, &b| names[a].cmp(&names[b]));
+    order
+}
+
+/// Check that no name is used more than once.
+fn check_unique(names: &[String]) -> Result<(), Error> {
+    let mut seen = BTreeSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(Error::DuplicateName(name.clone()));
+        }
+    }
+    Ok(())
+}
+
+impl TryFrom<Document> for Table {
+    type Error = Error;
+
+    fn try_from(document: Document) -> Result<Self, Self::Error> {
+        let Document {
+            states,
+            inputs,
+            start,
+            accept: accepted,
+            transitions: listed,
+        } = document;
+
+        check_unique(&states)?;
+        check_unique(&inputs)?;
+        let input_order = order(&inputs);
+
+        let state_index = |name: &str| {
+            states
+                .iter()
+                .position(|state| state == name)
+                .ok_or_else(|| Error::UnknownState(name.to_owned()))
+        };
+        let input_index = |name: &str| {
+            input_order
+                .binary_search_by(|&other| inputs[other].as_str().cmp(name))
+                .map(|position| input_order[position])
+                .map_err(|_| Error::UnknownInput(name.to_owned()))
+        };
+
+        let start = state_index(&start)?;
+
+        let mut accept = vec![false; states.len()];
+        for name in accepted {
+            accept[state_index(&name)?] = true;
+        }
+
+        let mut transitions = vec![None; states.len() * inputs.len()];
+        for transition in listed {
+            let from = state_index(&transition.from)?;
+            let input = input_index(&transition.input)?;
+            let to = state_index(&transition.to)?;
+
+            if transitions[from * inputs.len() + input]
+                .replace(to)
+                .is_some()
+            {
+                return Err(Error::ConflictingTransitions {
+                    state: transition.from,
+                    input: transition.input,
+                });
+            }
+        }
+
+        let transitions = transitions
+            .into_iter()
+            .enumerate()
+            .map(|(index, to)| {
+                to.ok_or_else(|| Error::MissingTransition {
+                    state: states[index / inputs.len()].clone(),
+                    input: inputs[index % inputs.len()].clone(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            states,
+            inputs,
+            order: input_order,
+            transitions,
+            start,
+            accept,
+        })
+    }
+}
+
+impl From<Table> for Document {
+    fn from(table: Table) -> Self {
+        let transitions = (0..table.states.len())
+            .flat_map(|state| (0..table.inputs.len()).map(move |input| (state, input)))
+            .map(|(state, input)| Transition {
+                from: table.states[state].clone(),
+                input: table.inputs[input].clone(),
+                to: table.states[table.next(state, input)].clone(),
+            })
+            .collect();
+
+        Self {
+            start: table.states[table.start].clone(),
+            accept: table
+                .states
+                .iter()
+                .zip(&table.accept)
+                .filter(|(_, &accept)| accept)
+                .map(|(name, _)| name.clone())
+                .collect(),
+            transitions,
+            states: table.states,
+            inputs: table.inputs,
+        }
+    }
+}
+
+/// Escape text for an XML attribute value.
+fn write_xml_escaped(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    for character in text.chars() {
+        match character {
+            '&' => f.write_str("&amp;")?,
+            '<' => f.write_str("&lt;")?,
+            '>' => f.write_str("&gt;")?,
+            '"' => f.write_str("&quot;")?,
+            '\'' => f.write_str("&apos;")?,
+            character => write!(f, "{character}")?,
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A writer for a [`Table`] as a SCXML document, see [`Table::scxml`].
+pub struct Scxml<'a> {
+    table: &'a Table,
+}
+
+impl fmt::Display for Scxml<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let table = self.table;
+
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        write!(
+            f,
+            r#"<scxml xmlns="http://www.w3.org/2005/07/scxml" xmlns:fsm="https://github.com/tomboddaert/fsm" version="1.0" datamodel="null" initial=""#
+        )?;
+        write_xml_escaped(f, &table.states[table.start])?;
+        writeln!(f, r#"">"#)?;
+
+        for (state, name) in table.states.iter().enumerate() {
+            f.write_str(r#"  <state id=""#)?;
+            write_xml_escaped(f, name)?;
+            if table.accept[state] {
+                f.write_str(r#"" fsm:accept="true">"#)?;
+            } else {
+                f.write_str(r#"">"#)?;
+            }
+            writeln!(f)?;
+
+            // Combine the inputs for each target, in the order the targets are first reached
+            let mut targets = Vec::new();
+            for input in 0..table.inputs.len() {
+                let target = table.next(state, input);
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+
+            for target in targets {
+                f.write_str(r#"    <transition event=""#)?;
+                let mut inputs = (0..table.inputs.len())
+                    .filter(|&input| table.next(state, input) == target)
+                    .map(|input| &table.inputs[input]);
+                if let Some(first) = inputs.next() {
+                    write_xml_escaped(f, first)?;
+                }
+                for input in inputs {
+                    f.write_str(" ")?;
+                    write_xml_escaped(f, input)?;
+                }
+                f.write_str(r#"" target=""#)?;
+                write_xml_escaped(f, &table.states[target])?;
+                writeln!(f, r#""/>"#)?;
+            }
+
+            writeln!(f, "  </state>")?;
+        }
+
+        writeln!(f, "</scxml>")
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A writer for a [`Table`] as JSON, see [`Table::json`].
+pub struct Json<'a> {
+    table: &'a Table,
+}
+
+impl fmt::Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list<'a>(
+            f: &mut fmt::Formatter<'_>,
+            names: impl IntoIterator<Item = &'a String>,
+        ) -> fmt::Result {
+            f.write_str("[")?;
+            for (index, name) in names.into_iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                json::write_string(f, name)?;
+            }
+            f.write_str("]")
+        }
+
+        let table = self.table;
+
+        f.write_str("{\n  \"states\": ")?;
+        list(f, &table.states)?;
+        f.write_str(",\n  \"inputs\": ")?;
+        list(f, &table.inputs)?;
+        f.write_str(",\n  \"start\": ")?;
+        json::write_string(f, &table.states[table.start])?;
+        f.write_str(",\n  \"accept\": ")?;
+        list(
+            f,
+            table
+                .states
+                .iter()
+                .zip(&table.accept)
+                .filter(|(_, &accept)| accept)
+                .map(|(name, _)| name),
+        )?;
+        f.write_str(",\n  \"transitions\": [")?;
+
+        for state in 0..table.states.len() {
+            for input in 0..table.inputs.len() {
+                if state > 0 || input > 0 {
+                    f.write_str(",")?;
+                }
+                f.write_str("\n    {\"from\": ")?;
+                json::write_string(f, &table.states[state])?;
+                f.write_str(", \"input\": ")?;
+                json::write_string(f, &table.inputs[input])?;
+                f.write_str(", \"to\": ")?;
+                json::write_string(f, &table.states[table.next(state, input)])?;
+                f.write_str("}")?;
+            }
+        }
+
+        f.write_str("\n  ]\n}\n")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Snapshot", into = "Snapshot")
+)]
+/// A table-driven Finite State Machine, running a [`Table`].
+///
+/// With the `serde` feature, this is serialized as an object with the fields
+/// `table`, following the [schema](self#json-schema), and `state`, the name of the current state.
+pub struct TableFSM {
+    table: Table,
+    state: usize,
+}
+
+impl TableFSM {
+    #[must_use]
+    #[inline]
+    /// Create a new machine, starting in the table's start state.
+    pub const fn new(table: Table) -> Self {
+        let state = table.start;
+        Self { table, state }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the table.
+    pub const fn table(&self) -> &Table {
+        &self.table
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the name of the current state.
+    pub fn state(&self) -> &str {
+        &self.table.states[self.state]
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the current state.
+    pub const fn state_index(&self) -> usize {
+        self.state
+    }
+
+    #[inline]
+    /// Move to the state at index `state`, for the [debugger](crate::debug) to undo steps.
+    pub(crate) const fn set_state_index(&mut self, state: usize) {
+        self.state = state;
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is an accept state.
+    pub fn is_accepted(&self) -> bool {
+        self.table.accept[self.state]
+    }
+
+    /// Apply the input named `input` to the machine in place.
+    ///
+    /// # Errors
+    /// This returns [`Error::UnknownInput`] if there is no input with that name,
+    /// leaving the machine unchanged.
+    pub fn apply_assign(&mut self, input: &str) -> Result<(), Error> {
+        let input = self
+            .table
+            .input_index(input)
+            .ok_or_else(|| Error::UnknownInput(input.to_owned()))?;
+
+        self.apply_index(input);
+        Ok(())
+    }
+
+    #[inline]
+    /// Apply the input at index `input` to the machine in place.
+    ///
+    /// # Panics
+    /// This will panic if the index is out of range.
+    pub fn apply_index(&mut self, input: usize) {
+        self.state = self.table.next(self.state, input);
+    }
+
+    /// Apply a set of named inputs to the machine in place.
+    ///
+    /// # Errors
+    /// This returns [`Error::UnknownInput`] for the first input that does not exist,
+    /// after applying the inputs before it.
+    pub fn run<I>(&mut self, inputs: I) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for input in inputs {
+            self.apply_assign(input.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+/// A table-driven machine being serialized.
+struct Snapshot {
+    table: Table,
+    state: String,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Snapshot> for TableFSM {
+    type Error = Error;
+
+    fn try_from(snapshot: Snapshot) -> Result<Self, Self::Error> {
+        let state = snapshot
+            .table
+            .state_index(&snapshot.state)
+            .ok_or(Error::UnknownState(snapshot.state))?;
+
+        Ok(Self {
+            table: snapshot.table,
+            state,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<TableFSM> for Snapshot {
+    fn from(machine: TableFSM) -> Self {
+        Self {
+            state: machine.state().to_owned(),
+            table: machine.table,
+        }
+    }
+}
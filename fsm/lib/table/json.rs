@@ -0,0 +1,276 @@
+//! A minimal JSON parser and string writer, for reading and writing tables.
+
+use core::fmt;
+
+use super::Error;
+use crate::cursor::Cursor;
+
+/// The deepest nesting of arrays and objects parsed, so that a document can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A JSON value.
+pub enum Value {
+    /// `null`, a boolean or a number, which are not used by tables.
+    Literal,
+    String(String),
+    Array(Vec<Self>),
+    Object(Vec<(String, Self)>),
+}
+
+impl Value {
+    /// Get a field of an object.
+    pub fn field(&self, name: &str) -> Option<&Self> {
+        let Self::Object(fields) = self else {
+            return None;
+        };
+
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a JSON document.
+pub fn parse(document: &str) -> Result<Value, Error> {
+    let mut parser = Parser {
+        cursor: Cursor::new(document),
+    };
+
+    let value = parser.value(0)?;
+    parser.cursor.skip_whitespace();
+
+    if !parser.cursor.rest().is_empty() {
+        return Err(parser.error("unexpected content after the value"));
+    }
+
+    Ok(value)
+}
+
+/// Write a string as a quoted JSON string.
+pub fn write_string(f: &mut fmt::Formatter<'_>, string: &str) -> fmt::Result {
+    f.write_str("\"")?;
+
+    for character in string.chars() {
+        match character {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            control if control.is_control() => write!(f, "\\u{:04x}", u32::from(control))?,
+            character => write!(f, "{character}")?,
+        }
+    }
+
+    f.write_str("\"")
+}
+
+struct Parser<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> Error {
+        let (line, column) = self.cursor.location();
+        Error::Json {
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// Parse a value, nested inside `depth` arrays and objects.
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        self.cursor.skip_whitespace();
+
+        if self.cursor.eat("{") {
+            self.object(depth)
+        } else if self.cursor.eat("[") {
+            self.array(depth)
+        } else if self.cursor.eat("\"") {
+            self.string().map(Value::String)
+        } else if self.cursor.eat("null") || self.cursor.eat("true") || self.cursor.eat("false") {
+            Ok(Value::Literal)
+        } else if self
+            .cursor
+            .rest()
+            .starts_with(|c: char| c == '-' || c.is_ascii_digit())
+        {
+            self.number()
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth == MAX_DEPTH {
+            return Err(self.error("arrays and objects are nested too deeply"));
+        }
+
+        let mut fields = Vec::new();
+
+        self.cursor.skip_whitespace();
+        if self.cursor.eat("}") {
+            return Ok(Value::Object(fields));
+        }
+
+        loop {
+            self.cursor.skip_whitespace();
+            if !self.cursor.eat("\"") {
+                return Err(self.error("expected a field name"));
+            }
+            let name = self.string()?;
+
+            self.cursor.skip_whitespace();
+            if !self.cursor.eat(":") {
+                return Err(self.error("expected `:` after a field name"));
+            }
+
+            fields.push((name, self.value(depth + 1)?));
+
+            self.cursor.skip_whitespace();
+            if self.cursor.eat("}") {
+                return Ok(Value::Object(fields));
+            }
+            if !self.cursor.eat(",") {
+                return Err(self.error("expected `,` or `}` after a field"));
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth == MAX_DEPTH {
+            return Err(self.error("arrays and objects are nested too deeply"));
+        }
+
+        let mut values = Vec::new();
+
+        self.cursor.skip_whitespace();
+        if self.cursor.eat("]") {
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value(depth + 1)?);
+
+            self.cursor.skip_whitespace();
+            if self.cursor.eat("]") {
+                return Ok(Value::Array(values));
+            }
+            if !self.cursor.eat(",") {
+                return Err(self.error("expected `,` or `]` after a value"));
+            }
+        }
+    }
+
+    /// Parse the rest of a string, after the opening quote.
+    fn string(&mut self) -> Result<String, Error> {
+        let mut string = String::new();
+
+        loop {
+            let Some(character) = self.cursor.rest().chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+
+            match character {
+                '"' => {
+                    self.cursor.position += 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    self.cursor.position += 1;
+                    string.push(self.escape()?);
+                }
+                control if control.is_control() => {
+                    return Err(self.error("control characters must be escaped"));
+                }
+                character => {
+                    self.cursor.position += character.len_utf8();
+                    string.push(character);
+                }
+            }
+        }
+    }
+
+    /// Parse an escape sequence, after the backslash.
+    fn escape(&mut self) -> Result<char, Error> {
+        let character = match self.cursor.rest().chars().next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                self.cursor.position += 1;
+                let high = self.hex()?;
+
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    if !self.cursor.eat("\\u") {
+                        return Err(self.error("expected a low surrogate"));
+                    }
+                    let low = self.hex()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("expected a low surrogate"));
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+
+                return char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"));
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+
+        self.cursor.position += 1;
+        Ok(character)
+    }
+
+    fn hex(&mut self) -> Result<u32, Error> {
+        let digits = self
+            .cursor
+            .rest()
+            .get(..4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("expected 4 hexadecimal digits"))?;
+
+        let code =
+            u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid unicode escape"))?;
+        self.cursor.position += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value, Error> {
+        let length = self
+            .cursor
+            .rest()
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+            .unwrap_or_else(|| self.cursor.rest().len());
+
+        if self.cursor.rest()[..length].parse::<f64>().is_err() {
+            return Err(self.error("invalid number"));
+        }
+
+        self.cursor.position += length;
+        Ok(Value::Literal)
+    }
+}
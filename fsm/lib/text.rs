@@ -0,0 +1,443 @@
+//! A text format for machines over characters, parsed at runtime into a [`DynFSM`].
+//!
+//! # Format
+//! Each line is a declaration, a transition, or a comment starting with `//`.
+//! - `states <state>, ...` adds states, which are otherwise added in the order they are first used,
+//! - `start <state>` sets the start state,
+//! - `accept <state>, ...` marks accept states,
+//! - `<from> <pattern> | ... -> <to>` adds a transition, like an arm of [`DefineTransform!`](crate::DefineTransform).
+//!
+//! States are named by identifiers, and patterns are character literals, such as `'h'` or `'\n'`,
+//! inclusive ranges of characters, such as `'a'..='z'`, or the wildcard `_`,
+//! which matches any character without another transition from the state.
+//! Unlike a `match`, the other patterns from a state must not overlap.
+//! Ranges are expanded to each of their characters, which each take a column of the machine's table,
+//! so large ranges take memory in proportion to their length.
+//!
+//! # Examples
+//! ```
+//! use fsm::text;
+//!
+//! const NUMBER: &str = "
+//!     // An optionally signed integer
+//!     start Empty
+//!     accept Digits
+//!
+//!     Empty '+' | '-' -> Sign
+//!     Empty | Sign '0'..='9' -> Digits
+//!     Digits '0'..='9' -> Digits
+//!     Empty | Sign | Digits _ -> Invalid
+//!     Invalid _ -> Invalid
+//! ";
+//!
+//! let machine = text::parse(NUMBER).unwrap();
+//! assert!(machine.clone().run("-42".chars()).is_accepted());
+//! assert!(!machine.clone().run("4-2".chars()).is_accepted());
+//!
+//! // Machines can be written back out in the same format
+//! let formatted = text::display(&machine).to_string();
+//! assert!(formatted.contains("Sign '0'..='9' -> Digits"));
+//! assert_eq!(text::display(&text::parse(&formatted).unwrap()).to_string(), formatted);
+//!
+//! // Each character in a range is an input, and the surrogates `0xD800..=0xDFFF` are not characters
+//! let machine = text::parse("start A\nA '\\0'..='\\u{FFFF}' -> A").unwrap();
+//! assert_eq!(machine.inputs().len(), 0x10000 - 0x800);
+//!
+//! let error = text::parse("start A\nA 'a' => A").unwrap_err();
+//! assert_eq!(error.to_string(), "syntax error at line 2, column 7: expected `->` or `|`");
+//! ```
+
+use core::fmt;
+
+use crate::{cursor::Cursor, dynamic, DynFSM};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An error produced when parsing a machine.
+pub enum Error {
+    /// The text is not valid.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: &'static str,
+    },
+    /// The machine is not valid, such as if it is missing transitions.
+    Machine(dynamic::Error<char>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "syntax error at line {line}, column {column}: {message}"),
+            Self::Machine(error) => error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl From<dynamic::Error<char>> for Error {
+    fn from(error: dynamic::Error<char>) -> Self {
+        Self::Machine(error)
+    }
+}
+
+/// A pattern of a transition.
+enum Pattern {
+    Range(char, char),
+    Wildcard,
+}
+
+#[derive(Default)]
+/// The declarations and transitions parsed so far.
+struct Definition<'a> {
+    states: Vec<&'a str>,
+    start: Vec<&'a str>,
+    accept: Vec<&'a str>,
+    transitions: Vec<(Vec<&'a str>, Vec<Pattern>, &'a str)>,
+}
+
+impl<'a> Definition<'a> {
+    fn add_state(&mut self, name: &'a str) {
+        if !self.states.contains(&name) {
+            self.states.push(name);
+        }
+    }
+}
+
+/// Parse a machine from the [format](self#format).
+///
+/// # Errors
+/// This returns [`Error::Syntax`] if the text is not valid, or [`Error::Machine`]
+/// if it does not describe a valid machine, as checked by [`dynamic::Builder::build`].
+pub fn parse(text: &str) -> Result<DynFSM<char>, Error> {
+    Ok(builder(text)?.build()?)
+}
+
+/// Parse a machine from the [format](self#format), allowing states that can't be reached
+/// from the start state.
+///
+/// # Errors
+/// This returns [`Error::Syntax`] if the text is not valid, or [`Error::Machine`]
+/// if it does not describe a valid machine, as checked by
+/// [`dynamic::Builder::build_allowing_unreachable`].
+pub fn parse_allowing_unreachable(text: &str) -> Result<DynFSM<char>, Error> {
+    Ok(builder(text)?.build_allowing_unreachable()?)
+}
+
+/// Parse the text into a builder for the machine it describes.
+fn builder(text: &str) -> Result<dynamic::Builder<char>, Error> {
+    let mut definition = Definition::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let mut parser = Parser {
+            line: index + 1,
+            cursor: Cursor::new(line),
+        };
+        parser.line_into(&mut definition)?;
+    }
+
+    let mut builder = DynFSM::builder();
+    for state in definition.states {
+        builder = builder.add_state(state);
+    }
+    for state in definition.start {
+        builder = builder.set_start(state);
+    }
+    for state in definition.accept {
+        builder = builder.set_accepting(state);
+    }
+
+    for (sources, patterns, to) in definition.transitions {
+        for from in sources {
+            for pattern in &patterns {
+                builder = match *pattern {
+                    Pattern::Range(low, high) => (low..=high).fold(builder, |builder, input| {
+                        builder.add_transition(from, input, to)
+                    }),
+                    Pattern::Wildcard => builder.add_otherwise(from, to),
+                };
+            }
+        }
+    }
+
+    Ok(builder)
+}
+
+/// A parser for a single line.
+struct Parser<'a> {
+    line: usize,
+    cursor: Cursor<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &'static str) -> Error {
+        let (_, column) = self.cursor.location();
+        Error::Syntax {
+            line: self.line,
+            column,
+            message,
+        }
+    }
+
+    /// Move past `prefix` after any whitespace, returning [`true`] if it is next.
+    fn eat(&mut self, prefix: &str) -> bool {
+        self.cursor.skip_whitespace();
+        self.cursor.eat(prefix)
+    }
+
+    /// Returns [`true`] if the rest of the line is whitespace or a comment.
+    fn at_end(&mut self) -> bool {
+        self.cursor.skip_whitespace();
+        self.cursor.rest().is_empty() || self.cursor.rest().starts_with("//")
+    }
+
+    fn identifier(&mut self) -> Result<&'a str, Error> {
+        self.cursor.skip_whitespace();
+
+        let rest = self.cursor.rest();
+        let length = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+
+        if length == 0 || &rest[..length] == "_" || rest.starts_with(|c: char| c.is_numeric()) {
+            return Err(self.error("expected a state name"));
+        }
+
+        self.cursor.position += length;
+        Ok(&rest[..length])
+    }
+
+    /// Parse a list of identifiers, separated by `separator`.
+    fn identifiers(&mut self, separator: &str) -> Result<Vec<&'a str>, Error> {
+        let mut identifiers = vec![self.identifier()?];
+        while self.eat(separator) {
+            identifiers.push(self.identifier()?);
+        }
+        Ok(identifiers)
+    }
+
+    fn character(&mut self) -> Result<char, Error> {
+        if !self.eat("'") {
+            return Err(self.error("expected a character literal, or `_`"));
+        }
+
+        let rest = self.cursor.rest();
+        let (character, length) = if let Some(escape) = rest.strip_prefix('\\') {
+            let simple = match escape.chars().next() {
+                Some('n') => Some('\n'),
+                Some('r') => Some('\r'),
+                Some('t') => Some('\t'),
+                Some('0') => Some('\0'),
+                Some(c @ ('\\' | '\'' | '"')) => Some(c),
+                _ => None,
+            };
+
+            if let Some(character) = simple {
+                (character, 2)
+            } else {
+                // A unicode escape, such as `\u{1F600}`
+                let character = escape
+                    .strip_prefix("u{")
+                    .and_then(|unicode| unicode.split_once('}'))
+                    .and_then(|(hex, _)| {
+                        let code = u32::from_str_radix(hex, 16).ok()?;
+                        Some((char::from_u32(code)?, hex.len() + 4))
+                    });
+                character.ok_or_else(|| self.error("invalid escape"))?
+            }
+        } else {
+            match rest.chars().next() {
+                Some('\'') | None => return Err(self.error("expected a character")),
+                Some(character) => (character, character.len_utf8()),
+            }
+        };
+
+        self.cursor.position += length;
+        if !self.cursor.rest().starts_with('\'') {
+            return Err(self.error("expected `'`"));
+        }
+        self.cursor.position += 1;
+
+        Ok(character)
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, Error> {
+        self.cursor.skip_whitespace();
+
+        let rest = self.cursor.rest();
+        if rest.starts_with('_')
+            && !rest[1..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        {
+            self.cursor.position += 1;
+            return Ok(Pattern::Wildcard);
+        }
+
+        let low = self.character()?;
+        if !self.eat("..=") {
+            return Ok(Pattern::Range(low, low));
+        }
+
+        let start = self.cursor.position;
+        let high = self.character()?;
+        if high < low {
+            self.cursor.position = start;
+            self.cursor.skip_whitespace();
+            return Err(self.error("the range is empty"));
+        }
+
+        Ok(Pattern::Range(low, high))
+    }
+
+    /// Parse the line, adding its declaration or transition to `definition`.
+    fn line_into(&mut self, definition: &mut Definition<'a>) -> Result<(), Error> {
+        if self.at_end() {
+            return Ok(());
+        }
+
+        let first = self.identifier()?;
+        let declaration = match first {
+            "states" | "start" | "accept"
+                if !self.at_end() && !self.cursor.rest().starts_with(['\'', '_', '|']) =>
+            {
+                let states = if first == "start" {
+                    vec![self.identifier()?]
+                } else {
+                    self.identifiers(",")?
+                };
+
+                for state in &states {
+                    definition.add_state(state);
+                }
+                match first {
+                    "start" => definition.start.extend(states),
+                    "accept" => definition.accept.extend(states),
+                    _ => {}
+                }
+                true
+            }
+            _ => false,
+        };
+
+        if !declaration {
+            let mut sources = vec![first];
+            while self.eat("|") {
+                sources.push(self.identifier()?);
+            }
+
+            let mut patterns = vec![self.pattern()?];
+            while self.eat("|") {
+                patterns.push(self.pattern()?);
+            }
+
+            if !self.eat("->") {
+                return Err(self.error("expected `->` or `|`"));
+            }
+            let to = self.identifier()?;
+
+            for state in sources.iter().chain([&to]) {
+                definition.add_state(state);
+            }
+            definition.transitions.push((sources, patterns, to));
+        }
+
+        if !self.at_end() {
+            return Err(self.error("expected the end of the line"));
+        }
+        Ok(())
+    }
+}
+
+#[must_use]
+#[inline]
+/// Get a writer for a machine in the [format](self#format).
+///
+/// Characters with the same target are combined into ranges, and transitions covered by the
+/// wildcard are left out. The states are declared first, so their order is kept.
+/// State names are written as they are, so should be identifiers.
+pub const fn display(machine: &DynFSM<char>) -> Display<'_> {
+    Display { machine }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A writer for a machine in the [format](self#format), see [`display`].
+pub struct Display<'a> {
+    machine: &'a DynFSM<char>,
+}
+
+impl fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let machine = self.machine;
+        let states = machine.states();
+
+        writeln!(f, "states {}", states.join(", "))?;
+        writeln!(f, "start {}", states[machine.start()])?;
+
+        let accept = (0..states.len())
+            .filter(|&state| machine.is_accepting(state))
+            .map(|state| states[state].as_str())
+            .collect::<Vec<_>>();
+        if !accept.is_empty() {
+            writeln!(f, "accept {}", accept.join(", "))?;
+        }
+
+        for (state, name) in states.iter().enumerate() {
+            let otherwise = machine.otherwise(state);
+
+            // The inputs for each target, in the order the targets are first reached
+            let mut targets: Vec<(usize, Vec<char>)> = Vec::new();
+            for (input, &character) in machine.inputs().iter().enumerate() {
+                let target = machine.next(state, input);
+                if Some(target) == otherwise {
+                    continue;
+                }
+
+                match targets.iter_mut().find(|(other, _)| *other == target) {
+                    Some((_, characters)) => characters.push(character),
+                    None => targets.push((target, vec![character])),
+                }
+            }
+
+            for (target, mut characters) in targets {
+                characters.sort_unstable();
+                write!(f, "{name} ")?;
+
+                let mut first = true;
+                let mut index = 0;
+                while index < characters.len() {
+                    let low = characters[index];
+                    let mut high = low;
+                    while index + 1 < characters.len()
+                        && u32::from(characters[index + 1]) == u32::from(high) + 1
+                    {
+                        index += 1;
+                        high = characters[index];
+                    }
+                    index += 1;
+
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    first = false;
+
+                    if low == high {
+                        write!(f, "{low:?}")?;
+                    } else {
+                        write!(f, "{low:?}..={high:?}")?;
+                    }
+                }
+
+                writeln!(f, " -> {}", states[target])?;
+            }
+
+            if let Some(target) = otherwise {
+                writeln!(f, "{name} _ -> {}", states[target])?;
+            }
+        }
+
+        Ok(())
+    }
+}
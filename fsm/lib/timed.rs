@@ -0,0 +1,334 @@
+//! Timeout transitions, taken when no input is applied for a while.
+//!
+//! The time is read from a [`Clock`]: a [`StdClock`] with the `std` feature,
+//! or a [`ManualClock`] counting ticks given by the caller, for tests and `no_std`.
+//!
+//! # Examples
+//! ```
+//! use fsm::{
+//!     timed::{Clock, ManualClock},
+//!     DefineTransform, TimedFSM, FSM,
+//! };
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum Session { Idle, Active, Warned }
+//!
+//! #[derive(Clone, Copy)]
+//! enum Event { Request }
+//!
+//! DefineTransform!(session, Session, Event,
+//!     (_, Event::Request) => Session::Active,
+//! );
+//!
+//! // Warn after 20 ticks without a request, then go idle 10 ticks after that
+//! fn timeout(state: &Session) -> Option<(u64, Session)> {
+//!     match state {
+//!         Session::Active => Some((20, Session::Warned)),
+//!         Session::Warned => Some((10, Session::Idle)),
+//!         Session::Idle => None,
+//!     }
+//! }
+//!
+//! let clock = ManualClock::new(0);
+//! let mut machine = TimedFSM::new(FSM::new(Session::Active, session), timeout, &clock);
+//! assert_eq!(machine.next_deadline(), Some(20));
+//!
+//! // Each request restarts the timeout
+//! clock.advance(15);
+//! machine.apply(Event::Request);
+//! assert_eq!(machine.next_deadline(), Some(35));
+//!
+//! // Timeouts that expired together are taken in order
+//! assert_eq!(machine.poll(clock.now() + 30), 2);
+//! assert_eq!(machine.state(), &Session::Idle);
+//! assert_eq!(machine.next_deadline(), None);
+//! ```
+
+use core::{cell::Cell, ops::Add};
+
+use crate::{AcceptStates, FSM};
+
+/// A source of the current time, for a [`TimedFSM`].
+pub trait Clock {
+    /// A point in time.
+    type Instant: Copy + Ord + Add<Self::Duration, Output = Self::Instant>;
+    /// A length of time.
+    type Duration: Copy;
+
+    #[must_use]
+    /// Get the current time, which should never be earlier than a time returned before.
+    fn now(&self) -> Self::Instant;
+}
+
+impl<C> Clock for &C
+where
+    C: Clock + ?Sized,
+{
+    type Instant = C::Instant;
+    type Duration = C::Duration;
+
+    #[inline]
+    fn now(&self) -> Self::Instant {
+        (**self).now()
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// The monotonic system clock, from [`std::time::Instant`].
+pub struct StdClock;
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    type Instant = std::time::Instant;
+    type Duration = core::time::Duration;
+
+    #[inline]
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// A clock counting ticks, that only moves when told to.
+///
+/// This can be used to test timeouts, or in `no_std`, with ticks from a hardware timer.
+pub struct ManualClock {
+    ticks: Cell<u64>,
+}
+
+impl ManualClock {
+    #[must_use]
+    #[inline]
+    /// Create a new clock, starting at `ticks`.
+    pub const fn new(ticks: u64) -> Self {
+        Self {
+            ticks: Cell::new(ticks),
+        }
+    }
+
+    #[inline]
+    /// Move the clock forwards by `ticks`.
+    pub fn advance(&self, ticks: u64) {
+        self.ticks.set(self.ticks.get() + ticks);
+    }
+
+    #[inline]
+    /// Set the clock to `ticks`.
+    ///
+    /// # Panics
+    /// This will panic if `ticks` is earlier than the current time.
+    pub fn set(&self, ticks: u64) {
+        assert!(
+            ticks >= self.ticks.get(),
+            "the clock can't be moved backwards"
+        );
+        self.ticks.set(ticks);
+    }
+}
+
+impl Clock for ManualClock {
+    type Instant = u64;
+    type Duration = u64;
+
+    #[inline]
+    fn now(&self) -> Self::Instant {
+        self.ticks.get()
+    }
+}
+
+/// A function giving the timeout for a state, as how long the machine can stay in the state
+/// without an input, and the state to move to after that, or [`None`] for no timeout.
+pub type Timeout<States, Duration> = fn(&States) -> Option<(Duration, States)>;
+
+#[derive(Clone, Debug)]
+/// A Finite State Machine with timeout transitions.
+///
+/// The timeout for each state is given by a [`Timeout`] function. The timeout starts when the state is entered, and restarts on every input, even if it doesn't
+/// change the state.
+///
+/// Timeouts are only taken when the machine is polled, so they should be polled at the
+/// [`next_deadline`](Self::next_deadline). A timeout taken late still starts the next timeout
+/// from its deadline, rather than from when it was polled.
+pub struct TimedFSM<Domain, States, C>
+where
+    C: Clock,
+{
+    machine: FSM<Domain, States>,
+    timeout: Timeout<States, C::Duration>,
+    clock: C,
+    deadline: Option<(C::Instant, States)>,
+}
+
+impl<Domain, States, C> TimedFSM<Domain, States, C>
+where
+    States: Clone,
+    C: Clock,
+{
+    #[must_use]
+    /// Create a new timed machine, with the `timeout` function, starting the timeout for
+    /// the machine's state now.
+    pub fn new(
+        machine: FSM<Domain, States>,
+        timeout: Timeout<States, C::Duration>,
+        clock: C,
+    ) -> Self {
+        let now = clock.now();
+        let deadline = Self::deadline_from(timeout, machine.state(), now);
+
+        Self {
+            machine,
+            timeout,
+            clock,
+            deadline,
+        }
+    }
+
+    fn deadline_from(
+        timeout: Timeout<States, C::Duration>,
+        state: &States,
+        start: C::Instant,
+    ) -> Option<(C::Instant, States)> {
+        timeout(state).map(|(duration, target)| (start + duration, target))
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current state.
+    pub const fn state(&self) -> &States {
+        self.machine.state()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the underlying Finite State Machine.
+    pub const fn machine(&self) -> &FSM<Domain, States> {
+        &self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the underlying Finite State Machine, discarding the timeout.
+    pub fn into_machine(self) -> FSM<Domain, States> {
+        self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the clock.
+    pub const fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is marked as accepted.
+    pub fn is_accepted(&self) -> bool
+    where
+        States: AcceptStates,
+    {
+        self.machine.is_accepted()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the time the current state times out, or [`None`] if it has no timeout.
+    pub fn next_deadline(&self) -> Option<C::Instant> {
+        self.deadline.as_ref().map(|(deadline, _)| *deadline)
+    }
+
+    /// Apply an input to the machine, restarting the timeout now.
+    ///
+    /// Timeouts that have expired are taken first, so the input is applied to the state
+    /// the machine is in now.
+    pub fn apply(&mut self, input: Domain)
+    where
+        States: PartialEq,
+    {
+        let now = self.clock.now();
+        self.apply_at(input, now);
+    }
+
+    /// Apply an input to the machine at `now`, taking the timeouts that have expired by then first,
+    /// and restarting the timeout.
+    pub fn apply_at(&mut self, input: Domain, now: C::Instant)
+    where
+        States: PartialEq,
+    {
+        self.poll(now);
+        self.machine.apply_assign(input);
+        self.deadline = Self::deadline_from(self.timeout, self.machine.state(), now);
+    }
+
+    /// Apply a set of inputs to the machine, taking the timeouts that have expired first,
+    /// and restarting the timeout now.
+    pub fn run<I>(&mut self, inputs: I)
+    where
+        States: PartialEq,
+        I: IntoIterator<Item = Domain>,
+    {
+        let now = self.clock.now();
+        self.poll(now);
+        self.machine.run_assign(inputs);
+        self.deadline = Self::deadline_from(self.timeout, self.machine.state(), now);
+    }
+
+    /// Take the timeouts that have expired by `now`, returning how many were taken.
+    ///
+    /// If the state reached by a timeout has also timed out by `now`, that timeout is taken too.
+    /// Timeouts with zero duration can lead round a cycle of states without time passing,
+    /// so polling stops when such a cycle is found, leaving its next timeout due.
+    pub fn poll(&mut self, now: C::Instant) -> usize
+    where
+        States: PartialEq,
+    {
+        let mut taken = 0;
+
+        // A state reached at a deadline, and the number of timeouts since, to find cycles
+        // of timeouts at the same time with Brent's algorithm, without storing every state
+        let mut mark: Option<(C::Instant, States)> = None;
+        let mut since_mark = 0_usize;
+        let mut limit = 1_usize;
+
+        while let Some((deadline, target)) = self.deadline.take() {
+            let cycled = mark
+                .as_ref()
+                .is_some_and(|(instant, state)| *instant == deadline && *state == target);
+            if deadline > now || cycled {
+                self.deadline = Some((deadline, target));
+                break;
+            }
+
+            self.deadline = Self::deadline_from(self.timeout, &target, deadline);
+
+            since_mark += 1;
+            match &mark {
+                Some((instant, _)) if *instant == deadline && since_mark < limit => {}
+                Some((instant, _)) if *instant == deadline => {
+                    limit *= 2;
+                    since_mark = 0;
+                    mark = Some((deadline, target.clone()));
+                }
+                _ => {
+                    limit = 1;
+                    since_mark = 0;
+                    mark = Some((deadline, target.clone()));
+                }
+            }
+
+            self.machine = FSM::new(target, self.machine.transform_function());
+            taken += 1;
+        }
+
+        taken
+    }
+
+    /// Take the timeouts that have expired by the clock's current time, returning how many were taken.
+    pub fn update(&mut self) -> usize
+    where
+        States: PartialEq,
+    {
+        let now = self.clock.now();
+        self.poll(now)
+    }
+}
@@ -0,0 +1,429 @@
+//! Tracing, for recording the path a machine takes through its states.
+//!
+//! Use [`FSM::run_traced`] to record each [`Step`] into a [`Buffer`], such as a [`RingBuffer`],
+//! or a [`Vec`] with the `std` feature.
+//! A trace can be printed with [`Table`], or replayed against another transform function with [`replay`].
+//!
+//! # Examples
+//! ```
+//! use fsm::{trace::{self, RingBuffer}, DefineTransform, FSM};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum States { Even, Odd }
+//!
+//! DefineTransform!(parity, States, u8,
+//!     (States::Even, n) if n % 2 == 1 => States::Odd,
+//!     (States::Odd, n) if n % 2 == 1 => States::Even,
+//!     (state, _) => state,
+//! );
+//!
+//! // A faulty version, that ignores 3s
+//! DefineTransform!(faulty_parity, States, u8,
+//!     (state, 3) => state,
+//!     (States::Even, n) if n % 2 == 1 => States::Odd,
+//!     (States::Odd, n) if n % 2 == 1 => States::Even,
+//!     (state, _) => state,
+//! );
+//!
+//! // Only keep the last 3 steps
+//! let mut buffer = RingBuffer::<_, 3>::new();
+//! let machine = FSM::new(States::Even, parity).run_traced([1, 2, 3, 4], &mut buffer);
+//! assert_eq!(machine.state(), &States::Even);
+//!
+//! assert_eq!(
+//!     trace::Table::new(&buffer).to_string(),
+//!     "\
+//! | # | from | input | to   |
+//! |---|------|-------|------|
+//! | 1 | Odd  | 2     | Odd  |
+//! | 2 | Odd  | 3     | Even |
+//! | 3 | Even | 4     | Even |
+//! "
+//! );
+//!
+//! let divergence = trace::replay(&buffer, faulty_parity).unwrap();
+//! assert_eq!(divergence.step().index(), 2);
+//! assert_eq!(divergence.state(), &States::Odd);
+//! ```
+
+use core::fmt;
+
+use crate::{Hooks, FSM};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// A single transition taken by a machine.
+pub struct Step<Domain, States> {
+    index: usize,
+    from: States,
+    input: Domain,
+    to: States,
+}
+
+impl<Domain, States> Step<Domain, States> {
+    #[must_use]
+    #[inline]
+    /// Create a new step.
+    pub const fn new(index: usize, from: States, input: Domain, to: States) -> Self {
+        Self {
+            index,
+            from,
+            input,
+            to,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the index of the input that caused the transition.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the state before the transition.
+    pub const fn from(&self) -> &States {
+        &self.from
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the input that caused the transition.
+    pub const fn input(&self) -> &Domain {
+        &self.input
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the state after the transition.
+    pub const fn to(&self) -> &States {
+        &self.to
+    }
+}
+
+/// A buffer that steps can be recorded into.
+pub trait Buffer<Domain, States> {
+    /// Record a step.
+    fn record(&mut self, step: Step<Domain, States>);
+}
+
+impl<Domain, States, B> Buffer<Domain, States> for &mut B
+where
+    B: Buffer<Domain, States> + ?Sized,
+{
+    #[inline]
+    fn record(&mut self, step: Step<Domain, States>) {
+        (**self).record(step);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Domain, States> Buffer<Domain, States> for Vec<Step<Domain, States>> {
+    #[inline]
+    fn record(&mut self, step: Step<Domain, States>) {
+        self.push(step);
+    }
+}
+
+#[derive(Clone, Debug)]
+/// A fixed size buffer that keeps the last `N` items pushed into it, without allocating.
+pub struct RingBuffer<T, const N: usize> {
+    items: [Option<T>; N],
+    start: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    #[must_use]
+    #[inline]
+    /// Create a new, empty ring buffer.
+    pub const fn new() -> Self {
+        Self {
+            items: [const { None }; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the number of items in the buffer.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the buffer is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Push an item into the buffer, removing and returning the oldest item if it is full.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if N == 0 {
+            return Some(item);
+        }
+
+        if self.len < N {
+            self.items[(self.start + self.len) % N] = Some(item);
+            self.len += 1;
+            None
+        } else {
+            let oldest = self.items[self.start].replace(item);
+            self.start = (self.start + 1) % N;
+            oldest
+        }
+    }
+
+    /// Remove every item from the buffer.
+    pub fn clear(&mut self) {
+        for item in &mut self.items {
+            *item = None;
+        }
+        self.start = 0;
+        self.len = 0;
+    }
+
+    #[inline]
+    /// Iterate over the items in the buffer, from oldest to newest.
+    pub const fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            buffer: self,
+            offset: 0,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a RingBuffer<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, N>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// An iterator over the items of a [`RingBuffer`], from oldest to newest.
+pub struct Iter<'a, T, const N: usize> {
+    buffer: &'a RingBuffer<T, N>,
+    offset: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.buffer.len {
+            return None;
+        }
+
+        let item = self.buffer.items[(self.buffer.start + self.offset) % N].as_ref();
+        self.offset += 1;
+        item
+    }
+}
+
+impl<Domain, States, const N: usize> Buffer<Domain, States>
+    for RingBuffer<Step<Domain, States>, N>
+{
+    #[inline]
+    fn record(&mut self, step: Step<Domain, States>) {
+        self.push(step);
+    }
+}
+
+#[derive(Debug)]
+/// Hooks that record each transition into a [`Buffer`].
+pub struct Tracer<B> {
+    buffer: B,
+    index: usize,
+}
+
+impl<B> Tracer<B> {
+    #[must_use]
+    #[inline]
+    /// Create a new tracer, recording into `buffer` with indices starting at 0.
+    pub const fn new(buffer: B) -> Self {
+        Self { buffer, index: 0 }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the buffer.
+    pub fn into_buffer(self) -> B {
+        self.buffer
+    }
+}
+
+impl<Domain, States, B> Hooks<Domain, States> for Tracer<B>
+where
+    Domain: Clone,
+    States: Clone,
+    B: Buffer<Domain, States>,
+{
+    #[inline]
+    fn on_transition(&mut self, from: &States, input: &Domain, to: &States) {
+        self.buffer.record(Step::new(
+            self.index,
+            from.clone(),
+            input.clone(),
+            to.clone(),
+        ));
+        self.index += 1;
+    }
+}
+
+impl<Domain, States> FSM<Domain, States> {
+    #[must_use = "this returns the result of the transformations, without modifying the original"]
+    /// Apply a set of inputs to the Finite State Machine, recording each step into `buffer`,
+    /// and returning the new state of the machine.
+    ///
+    /// Steps are indexed from 0, for the first input given.
+    pub fn run_traced<I, B>(self, inputs: I, buffer: &mut B) -> Self
+    where
+        States: Clone,
+        Domain: Clone,
+        I: IntoIterator<Item = Domain>,
+        B: Buffer<Domain, States>,
+    {
+        self.run_with_hooks(inputs, &mut Tracer::new(buffer))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// A step where a transform function disagrees with a trace, see [`replay`].
+pub struct Divergence<'a, Domain, States> {
+    step: &'a Step<Domain, States>,
+    state: States,
+}
+
+impl<'a, Domain, States> Divergence<'a, Domain, States> {
+    #[must_use]
+    #[inline]
+    /// Get the step in the trace.
+    pub const fn step(&self) -> &'a Step<Domain, States> {
+        self.step
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the state that the transform function moved to instead.
+    pub const fn state(&self) -> &States {
+        &self.state
+    }
+}
+
+/// Replay a trace against `transform`, returning the first step where it moves to a different state.
+///
+/// Each step is replayed from its recorded `from` state, so every step is checked,
+/// even if the trace does not start from the start state.
+pub fn replay<'a, Domain, States, I>(
+    steps: I,
+    transform: fn(States, Domain) -> States,
+) -> Option<Divergence<'a, Domain, States>>
+where
+    Domain: Clone + 'a,
+    States: Clone + PartialEq + 'a,
+    I: IntoIterator<Item = &'a Step<Domain, States>>,
+{
+    steps.into_iter().find_map(|step| {
+        let state = transform(step.from.clone(), step.input.clone());
+        (state != step.to).then_some(Divergence { step, state })
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+/// A pretty-printer for traces, that displays them as a Markdown table.
+pub struct Table<I> {
+    steps: I,
+}
+
+impl<I> Table<I> {
+    #[must_use]
+    #[inline]
+    /// Create a new table from the steps of a trace.
+    pub const fn new(steps: I) -> Self {
+        Self { steps }
+    }
+}
+
+/// Counts the width of formatted text, without storing it.
+struct Width(usize);
+
+impl fmt::Write for Width {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
+
+fn width(value: impl fmt::Display) -> usize {
+    let mut width = Width(0);
+    // Writing to `Width` never fails
+    let _ = fmt::write(&mut width, format_args!("{value}"));
+    width.0
+}
+
+/// Write a table cell, padded to `width`.
+fn cell(f: &mut fmt::Formatter<'_>, value: impl fmt::Display, width: usize) -> fmt::Result {
+    let padding = width.saturating_sub(self::width(&value));
+    write!(f, " {value}{:padding$} |", "")
+}
+
+impl<'a, Domain, States, I> fmt::Display for Table<I>
+where
+    Domain: fmt::Debug + 'a,
+    States: fmt::Debug + 'a,
+    I: IntoIterator<Item = &'a Step<Domain, States>> + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut widths = [width("#"), width("from"), width("input"), width("to")];
+
+        for step in self.steps.clone() {
+            let row = [
+                width(step.index),
+                width(format_args!("{:?}", step.from)),
+                width(format_args!("{:?}", step.input)),
+                width(format_args!("{:?}", step.to)),
+            ];
+
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell);
+            }
+        }
+
+        f.write_str("|")?;
+        for (heading, width) in ["#", "from", "input", "to"].into_iter().zip(widths) {
+            cell(f, heading, width)?;
+        }
+
+        f.write_str("\n|")?;
+        for width in widths {
+            write!(f, "-{:-<width$}-|", "")?;
+        }
+        f.write_str("\n")?;
+
+        for step in self.steps.clone() {
+            f.write_str("|")?;
+            cell(f, step.index, widths[0])?;
+            cell(f, format_args!("{:?}", step.from), widths[1])?;
+            cell(f, format_args!("{:?}", step.input), widths[2])?;
+            cell(f, format_args!("{:?}", step.to), widths[3])?;
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
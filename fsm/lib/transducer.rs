@@ -0,0 +1,190 @@
+//! Mealy machines (finite state transducers), where each transition produces an output.
+
+use crate::AcceptStates;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(unpredictable_function_pointer_comparisons)]
+/// A finite state transducer.
+///
+/// This contains a state and a transform function that returns the new state and an output.
+/// The transform function can be defined with [`DefineTransducer!`](crate::DefineTransducer).
+pub struct Transducer<Domain, States, Output> {
+    state: States,
+    transform: fn(States, Domain) -> (States, Output),
+}
+
+impl<Domain, States, Output> Transducer<Domain, States, Output> {
+    #[must_use]
+    #[inline]
+    /// Create a new transducer starting with the `start_state` and with the `transform` function.
+    pub const fn new(
+        start_state: States,
+        transform: fn(States, Domain) -> (States, Output),
+    ) -> Self {
+        Self {
+            state: start_state,
+            transform,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Create a new transducer starting with the default state and with the `transform` function.
+    pub fn default_with_transform(transform: fn(States, Domain) -> (States, Output)) -> Self
+    where
+        States: Default,
+    {
+        Self::new(States::default(), transform)
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current state.
+    pub const fn state(&self) -> &States {
+        &self.state
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the transform function.
+    pub const fn transform_function(&self) -> fn(States, Domain) -> (States, Output) {
+        self.transform
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the current state.
+    pub fn into_state(self) -> States {
+        self.state
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is marked as accepted.
+    pub fn is_accepted(&self) -> bool
+    where
+        States: AcceptStates,
+    {
+        self.state.is_accepted()
+    }
+
+    #[must_use = "this returns the result of the transformation, without modifying the original"]
+    #[inline]
+    /// Apply an input to the transducer, returning the new state of the transducer and the output.
+    pub fn apply(mut self, input: Domain) -> (Self, Output) {
+        let (new_state, output) = (self.transform)(self.state, input);
+        self.state = new_state;
+        (self, output)
+    }
+
+    #[inline]
+    /// Apply an input to the transducer in place, returning the output.
+    pub fn apply_assign(&mut self, input: Domain) -> Output
+    where
+        States: Clone,
+    {
+        let state = self.state.clone();
+        let (new_state, output) = (self.transform)(state, input);
+        self.state = new_state;
+        output
+    }
+
+    /// Apply a set of inputs to the transducer in place, lazily.
+    /// The returned iterator yields the output of each transition.
+    pub fn run<I>(&mut self, inputs: I) -> Run<'_, Domain, States, Output, I::IntoIter>
+    where
+        States: Clone,
+        I: IntoIterator<Item = Domain>,
+    {
+        Run {
+            transducer: self,
+            inputs: inputs.into_iter(),
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Compose this transducer with `next`, feeding each output of this transducer into `next` as its input.
+    pub const fn then<NextStates, NextOutput>(
+        self,
+        next: Transducer<Output, NextStates, NextOutput>,
+    ) -> Transducer<Domain, Then<Domain, States, Output, NextStates, NextOutput>, NextOutput> {
+        Transducer::new(
+            Then {
+                first: self,
+                second: next,
+            },
+            Then::apply,
+        )
+    }
+}
+
+#[derive(Debug)]
+/// An iterator over the outputs of a transducer, see [`Transducer::run`].
+pub struct Run<'a, Domain, States, Output, I> {
+    transducer: &'a mut Transducer<Domain, States, Output>,
+    inputs: I,
+}
+
+impl<Domain, States, Output, I> Iterator for Run<'_, Domain, States, Output, I>
+where
+    States: Clone,
+    I: Iterator<Item = Domain>,
+{
+    type Item = Output;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.inputs.next()?;
+        Some(self.transducer.apply_assign(input))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inputs.size_hint()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The state of two transducers composed in sequence, see [`Transducer::then`].
+///
+/// If `StatesA` and `StatesB` implement [`AcceptStates`], this will be accepted if both transducers are in an accept state.
+pub struct Then<Domain, StatesA, Intermediate, StatesB, Output> {
+    first: Transducer<Domain, StatesA, Intermediate>,
+    second: Transducer<Intermediate, StatesB, Output>,
+}
+
+impl<Domain, StatesA, Intermediate, StatesB, Output>
+    Then<Domain, StatesA, Intermediate, StatesB, Output>
+{
+    #[must_use]
+    #[inline]
+    /// Get the first transducer.
+    pub const fn first(&self) -> &Transducer<Domain, StatesA, Intermediate> {
+        &self.first
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the second transducer.
+    pub const fn second(&self) -> &Transducer<Intermediate, StatesB, Output> {
+        &self.second
+    }
+
+    #[must_use]
+    #[inline]
+    fn apply(self, input: Domain) -> (Self, Output) {
+        let (first, intermediate) = self.first.apply(input);
+        let (second, output) = self.second.apply(intermediate);
+        (Self { first, second }, output)
+    }
+}
+
+impl<Domain, StatesA: AcceptStates, Intermediate, StatesB: AcceptStates, Output> AcceptStates
+    for Then<Domain, StatesA, Intermediate, StatesB, Output>
+{
+    #[inline]
+    fn is_accepted(&self) -> bool {
+        self.first.is_accepted() && self.second.is_accepted()
+    }
+}
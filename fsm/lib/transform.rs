@@ -42,6 +42,11 @@
 /// ```
 ///
 /// There are more examples in the examples directory.
+///
+/// With the `derive` feature, `ValidatedTransform!` takes the same arguments,
+/// and also checks for arms that never match and states that can't be reached.
+/// `Typestate!` also takes the same arguments, and gives a type for each state,
+/// so that invalid transitions fail to compile.
 macro_rules! DefineTransform {
     ( $(#[$attr:meta])* $id:ident, $states:path, $dom:path, $($matcher:pat $(if $test:expr)? => $result:expr),* $(,)? ) => {
         $(#[$attr])*
@@ -53,3 +58,126 @@ macro_rules! DefineTransform {
         }
     };
 }
+
+#[macro_export]
+#[allow(clippy::module_name_repetitions)]
+/// A macro to define a transducer's transformation function with a match-like syntax.
+///
+/// This mirrors [`DefineTransform!`], with an extra output for each case.
+///
+/// # Syntax
+/// ```text
+/// DefineTransducer!([attributes] <name>, <states>, <input domain>, <output>,
+///     <match-like case statements, that take (<current state>, <input>), and return the new state and an output>,
+///     (<current>, <input>) => (<new>, <output>),
+/// );
+/// ```
+///
+/// # Examples
+///
+/// This example is a turnstile, with `Coin` and `Push` inputs,
+/// that outputs the action to take.
+/// ```
+/// use fsm;
+///
+/// enum Inputs { Coin, Push };
+/// enum States { Locked, Unlocked };
+/// enum Actions { Unlock, Lock, Refund, Nothing };
+///
+/// fsm::DefineTransducer!(turnstile, States, Inputs, Actions,
+///     (States::Locked, Inputs::Coin) => (States::Unlocked, Actions::Unlock),
+///     (States::Locked, Inputs::Push) => (States::Locked, Actions::Nothing),
+///     (States::Unlocked, Inputs::Coin) => (States::Unlocked, Actions::Refund),
+///     (States::Unlocked, Inputs::Push) => (States::Locked, Actions::Lock),
+/// );
+/// ```
+macro_rules! DefineTransducer {
+    ( $(#[$attr:meta])* $id:ident, $states:path, $dom:path, $out:path, $($matcher:pat $(if $test:expr)? => ($result:expr, $output:expr)),* $(,)? ) => {
+        $(#[$attr])*
+        #[allow(clippy::missing_const_for_fn)]
+        fn $id(state: $states, input: $dom) -> ($states, $out) {
+            match (state, input) {
+                $($matcher $(if $test)? => ($result, $output)),*
+            }
+        }
+    };
+}
+
+#[macro_export]
+#[allow(clippy::module_name_repetitions)]
+/// A macro to define a hierarchical state machine's transformation function with a match-like syntax.
+///
+/// This mirrors [`DefineTransform!`], but the cases do not need to be exhaustive.
+/// If no case matches, the state has no transition for the input, and the input is passed to its parent.
+///
+/// # Syntax
+/// ```text
+/// DefineHierarchicalTransform!([attributes] <name>, <states>, <input domain>,
+///     <match-like case statements, that take (<current state>, <input>), and return the new state>,
+///     (<current>, <input>) => <new>,
+/// );
+/// ```
+///
+/// See the [`hierarchy`](crate::hierarchy) module for an example.
+macro_rules! DefineHierarchicalTransform {
+    ( $(#[$attr:meta])* $id:ident, $states:path, $dom:path, $($matcher:pat $(if $test:expr)? => $result:expr),* $(,)? ) => {
+        $(#[$attr])*
+        #[allow(clippy::missing_const_for_fn, unreachable_patterns)]
+        fn $id(state: &$states, input: &$dom) -> Option<$states> {
+            match (state, input) {
+                $($matcher $(if $test)? => Some($result),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+#[macro_export]
+/// A macro to implement [`Hierarchy`](crate::hierarchy::Hierarchy), listing the children of each parent state.
+///
+/// The first child listed for a parent is its initial child.
+/// History states for a parent are listed after its children, with the state entered the first
+/// time that the parent is entered through them, which defaults to the parent's initial child.
+///
+/// # Syntax
+/// ```text
+/// DefineHierarchy!(<states>,
+///     <parent> => [<initial child>, <other children>...],
+///     <parent> => [<initial child>, <other children>...; <Shallow | Deep>(<history state> [=> <default>])...],
+/// );
+/// ```
+///
+/// See the [`hierarchy`](crate::hierarchy) module for an example.
+macro_rules! DefineHierarchy {
+    ( $states:ty, $($parent:path => [
+        $initial:path $(, $child:path)* $(,)?
+        $(; $($kind:ident($history:path $(=> $default:path)?)),+ $(,)?)?
+    ]),* $(,)? ) => {
+        impl $crate::hierarchy::Hierarchy for $states {
+            #[allow(unreachable_patterns)]
+            fn parent(&self) -> Option<Self> {
+                match self {
+                    $($initial $(| $child)* $($(| $history)+)? => Some($parent),)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn initial(&self) -> Option<Self> {
+                match self {
+                    $($parent => Some($initial),)*
+                    $($($($($history => Some($default),)?)+)?)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn history(&self) -> Option<$crate::hierarchy::History> {
+                match self {
+                    $($($($history => Some($crate::hierarchy::History::$kind),)+)?)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
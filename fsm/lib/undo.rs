@@ -0,0 +1,343 @@
+//! Undo and redo for running machines.
+//!
+//! # Examples
+//! ```
+//! use fsm::{DefineTransform, HistoryFSM, FSM};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq)]
+//! enum Mode { Normal, Insert, Visual }
+//!
+//! #[derive(Clone, Copy)]
+//! enum Key { I, V, Escape }
+//!
+//! DefineTransform!(editor, Mode, Key,
+//!     (Mode::Normal, Key::I) => Mode::Insert,
+//!     (Mode::Normal, Key::V) => Mode::Visual,
+//!     (_, Key::Escape) => Mode::Normal,
+//!     (mode, _) => mode,
+//! );
+//!
+//! let mut machine = HistoryFSM::new(FSM::new(Mode::Normal, editor), 10);
+//! machine.apply(Key::I);
+//! machine.apply(Key::Escape);
+//! assert_eq!(machine.state(), &Mode::Normal);
+//!
+//! assert!(machine.undo());
+//! assert_eq!(machine.state(), &Mode::Insert);
+//!
+//! // Applying an input after undoing discards the redo history
+//! machine.apply(Key::Escape);
+//! machine.apply(Key::V);
+//! assert_eq!(machine.state(), &Mode::Visual);
+//! assert!(!machine.redo());
+//!
+//! assert!(machine.goto(0));
+//! assert_eq!(machine.state(), &Mode::Normal);
+//! ```
+
+use alloc::collections::VecDeque;
+
+use crate::{AcceptStates, FSM};
+
+#[derive(Clone, Debug)]
+/// How the history of a [`HistoryFSM`] is stored.
+enum Record<Domain, States> {
+    /// Every state is stored.
+    States(VecDeque<States>),
+    /// Every input is stored, with the oldest state, and a snapshot of the state every `interval` steps after it.
+    Inputs {
+        inputs: VecDeque<Domain>,
+        oldest: States,
+        snapshots: VecDeque<(usize, States)>,
+        interval: usize,
+    },
+}
+
+#[derive(Clone, Debug)]
+/// A Finite State Machine that records its history, so inputs can be undone and redone.
+///
+/// Each input applied is a step, numbered from 0 for the start state.
+/// Up to `depth` steps before the latest step are kept, with older steps being forgotten.
+/// Applying an input after undoing discards the steps that could have been redone.
+pub struct HistoryFSM<Domain, States> {
+    machine: FSM<Domain, States>,
+    record: Record<Domain, States>,
+    depth: usize,
+    oldest: usize,
+    current: usize,
+    latest: usize,
+}
+
+impl<Domain, States> HistoryFSM<Domain, States>
+where
+    Domain: Clone,
+    States: Clone,
+{
+    #[must_use]
+    /// Create a new history machine, keeping up to `depth` steps by storing every state.
+    pub fn new(machine: FSM<Domain, States>, depth: usize) -> Self {
+        let mut states = VecDeque::new();
+        states.push_back(machine.state().clone());
+
+        Self::with_record(machine, Record::States(states), depth)
+    }
+
+    #[must_use]
+    /// Create a new history machine, keeping up to `depth` steps by storing every input,
+    /// and a snapshot of the state every `interval` steps.
+    ///
+    /// Moving through the history replays up to `interval` inputs from the nearest snapshot.
+    ///
+    /// # Panics
+    /// This will panic if `interval` is 0.
+    pub fn with_snapshots(machine: FSM<Domain, States>, depth: usize, interval: usize) -> Self {
+        assert!(interval > 0, "the snapshot interval must be at least 1");
+
+        let oldest = machine.state().clone();
+
+        Self::with_record(
+            machine,
+            Record::Inputs {
+                inputs: VecDeque::new(),
+                oldest,
+                snapshots: VecDeque::new(),
+                interval,
+            },
+            depth,
+        )
+    }
+
+    const fn with_record(
+        machine: FSM<Domain, States>,
+        record: Record<Domain, States>,
+        depth: usize,
+    ) -> Self {
+        Self {
+            machine,
+            record,
+            depth,
+            oldest: 0,
+            current: 0,
+            latest: 0,
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the current state.
+    pub const fn state(&self) -> &States {
+        self.machine.state()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the underlying Finite State Machine.
+    pub const fn machine(&self) -> &FSM<Domain, States> {
+        &self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Extract the underlying Finite State Machine, discarding the history.
+    pub fn into_machine(self) -> FSM<Domain, States> {
+        self.machine
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if the current state is marked as accepted.
+    pub fn is_accepted(&self) -> bool
+    where
+        States: AcceptStates,
+    {
+        self.machine.is_accepted()
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the number of the current step.
+    pub const fn step(&self) -> usize {
+        self.current
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the number of the oldest step that is still recorded.
+    pub const fn oldest_step(&self) -> usize {
+        self.oldest
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the number of the latest step, that can be redone up to.
+    pub const fn latest_step(&self) -> usize {
+        self.latest
+    }
+
+    #[must_use]
+    #[inline]
+    /// Get the maximum number of steps kept before the latest step.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if there is a step to undo.
+    pub const fn can_undo(&self) -> bool {
+        self.current > self.oldest
+    }
+
+    #[must_use]
+    #[inline]
+    /// Returns [`true`] if there is a step to redo.
+    pub const fn can_redo(&self) -> bool {
+        self.current < self.latest
+    }
+
+    /// Apply an input to the machine, discarding any steps that could have been redone.
+    pub fn apply(&mut self, input: Domain) {
+        self.truncate();
+
+        match &mut self.record {
+            Record::States(states) => {
+                self.machine.apply_assign(input);
+                states.push_back(self.machine.state().clone());
+            }
+            Record::Inputs {
+                inputs,
+                snapshots,
+                interval,
+                ..
+            } => {
+                inputs.push_back(input.clone());
+                self.machine.apply_assign(input);
+
+                if (self.current + 1).is_multiple_of(*interval) {
+                    snapshots.push_back((self.current + 1, self.machine.state().clone()));
+                }
+            }
+        }
+
+        self.current += 1;
+        self.latest = self.current;
+
+        while self.latest - self.oldest > self.depth {
+            self.forget_oldest();
+        }
+    }
+
+    /// Apply a set of inputs to the machine, discarding any steps that could have been redone.
+    pub fn run<I>(&mut self, inputs: I)
+    where
+        I: IntoIterator<Item = Domain>,
+    {
+        for input in inputs {
+            self.apply(input);
+        }
+    }
+
+    /// Move back one step, returning [`false`] if there is no step to undo.
+    pub fn undo(&mut self) -> bool {
+        self.can_undo() && self.goto(self.current - 1)
+    }
+
+    /// Move forward one step, returning [`false`] if there is no step to redo.
+    pub fn redo(&mut self) -> bool {
+        self.can_redo() && self.goto(self.current + 1)
+    }
+
+    /// Move to `step`, returning [`false`] if the step is not recorded.
+    pub fn goto(&mut self, step: usize) -> bool {
+        if step < self.oldest || step > self.latest {
+            return false;
+        }
+
+        let state = self.state_at(step);
+        self.machine = FSM::new(state, self.machine.transform_function());
+        self.current = step;
+        true
+    }
+
+    /// Get the state at a recorded step.
+    fn state_at(&self, step: usize) -> States {
+        match &self.record {
+            Record::States(states) => states[step - self.oldest].clone(),
+            Record::Inputs {
+                inputs,
+                oldest,
+                snapshots,
+                ..
+            } => {
+                let (snapshot_step, snapshot) = snapshots
+                    .iter()
+                    .rev()
+                    .find(|(snapshot_step, _)| *snapshot_step <= step)
+                    .map_or((self.oldest, oldest), |(snapshot_step, snapshot)| {
+                        (*snapshot_step, snapshot)
+                    });
+
+                let transform = self.machine.transform_function();
+                inputs
+                    .range(snapshot_step - self.oldest..step - self.oldest)
+                    .cloned()
+                    .fold(snapshot.clone(), transform)
+            }
+        }
+    }
+
+    /// Forget every step after the current step.
+    fn truncate(&mut self) {
+        let kept = self.current - self.oldest;
+
+        match &mut self.record {
+            Record::States(states) => states.truncate(kept + 1),
+            Record::Inputs {
+                inputs, snapshots, ..
+            } => {
+                inputs.truncate(kept);
+                while snapshots
+                    .back()
+                    .is_some_and(|(step, _)| *step > self.current)
+                {
+                    snapshots.pop_back();
+                }
+            }
+        }
+
+        self.latest = self.current;
+    }
+
+    /// Forget the oldest step.
+    fn forget_oldest(&mut self) {
+        match &mut self.record {
+            Record::States(states) => {
+                states.pop_front();
+            }
+            Record::Inputs {
+                inputs,
+                oldest,
+                snapshots,
+                ..
+            } => {
+                // The new oldest state is the next snapshot, if there is one for it,
+                // or otherwise the result of the oldest input
+                if let Some(input) = inputs.pop_front() {
+                    if snapshots
+                        .front()
+                        .is_some_and(|(step, _)| *step == self.oldest + 1)
+                    {
+                        if let Some((_, state)) = snapshots.pop_front() {
+                            *oldest = state;
+                        }
+                    } else {
+                        let transform = self.machine.transform_function();
+                        *oldest = transform(oldest.clone(), input);
+                    }
+                }
+            }
+        }
+
+        self.oldest += 1;
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{AcceptStates, FSM};
+use crate::{AcceptStates, ClassifyStates, StateOutput, FSM};
 
 #[must_use]
 #[inline]
@@ -92,9 +92,51 @@ where
     )
 }
 
+#[must_use]
+#[inline]
+/// Create a union machine from its state, such as one that has been deserialized.
+///
+/// This variant clones each input. For a version that copies each input, see [`from_state_copy`].
+pub const fn from_state<Domain, StatesA, StatesB>(
+    state: State<Domain, StatesA, StatesB>,
+) -> FSM<Domain, State<Domain, StatesA, StatesB>>
+where
+    Domain: Clone,
+{
+    FSM::new(state, State::apply)
+}
+
+#[must_use]
+#[inline]
+/// Create a union machine from its state, such as one that has been deserialized.
+///
+/// This variant copies each input. For a version that clones each input, see [`from_state`].
+pub const fn from_state_copy<Domain, StatesA, StatesB>(
+    state: State<Domain, StatesA, StatesB>,
+) -> FSM<Domain, State<Domain, StatesA, StatesB>>
+where
+    Domain: Copy,
+{
+    FSM::new(state, State::apply_copy)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "FSM<Domain, StatesA>: serde::Serialize, FSM<Domain, StatesB>: serde::Serialize",
+        deserialize = "FSM<Domain, StatesA>: serde::Deserialize<'de>, FSM<Domain, StatesB>: serde::Deserialize<'de>"
+    ))
+)]
 /// The state of a union machine.
-/// If `StatesA` and `StatesB` implement [`HasAcceptState`], this will be accepted if either machine is in an accept state.
+///
+/// If `StatesA` and `StatesB` implement [`AcceptStates`], this will be accepted if either machine is in an accept state.
+/// If `StatesA` and `StatesB` implement [`ClassifyStates`], this will be classified by the machines that are in an accept state.
+/// If `StatesA` and `StatesB` implement [`StateOutput`], this will output both machines' outputs.
+///
+/// With the `serde` feature, this can be serialized if both machines can,
+/// and restored as a machine with [`from_state`] or [`from_state_copy`].
 pub struct State<Domain, StatesA, StatesB> {
     a: FSM<Domain, StatesA>,
     b: FSM<Domain, StatesB>,
@@ -134,3 +176,41 @@ impl<Domain, StatesA: AcceptStates, StatesB: AcceptStates> AcceptStates
         self.a.is_accepted() || self.b.is_accepted()
     }
 }
+
+impl<Domain, StatesA: StateOutput, StatesB: StateOutput> StateOutput
+    for State<Domain, StatesA, StatesB>
+{
+    type Output = (StatesA::Output, StatesB::Output);
+
+    #[inline]
+    fn output(&self) -> Self::Output {
+        (self.a.state().output(), self.b.state().output())
+    }
+}
+
+impl<Domain, StatesA: ClassifyStates, StatesB: ClassifyStates> ClassifyStates
+    for State<Domain, StatesA, StatesB>
+{
+    type Class = Class<StatesA::Class, StatesB::Class>;
+
+    #[inline]
+    fn class(&self) -> Option<Self::Class> {
+        match (self.a.state().class(), self.b.state().class()) {
+            (Some(a), Some(b)) => Some(Class::Both(a, b)),
+            (Some(a), None) => Some(Class::A(a)),
+            (None, Some(b)) => Some(Class::B(b)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The class of an accepted union machine state.
+pub enum Class<A, B> {
+    /// Only the first machine is in an accept state.
+    A(A),
+    /// Only the second machine is in an accept state.
+    B(B),
+    /// Both machines are in accept states.
+    Both(A, B),
+}
@@ -0,0 +1,108 @@
+//! Checks that `AtomicFSM` applies inputs atomically when threads race.
+//!
+//! With `--cfg loom`, loom checks every interleaving of the threads, with
+//! `RUSTFLAGS="--cfg loom" cargo test --release --test atomic`.
+//! Otherwise, the threads are run many times, to catch some of the interleavings.
+
+#[cfg(loom)]
+use loom::{model, sync::Arc, thread};
+#[cfg(not(loom))]
+use std::{sync::Arc, thread};
+
+use fsm::{AtomicFSM, DefineTransform, Finite};
+
+#[cfg(not(loom))]
+fn model<F>(f: F)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    for _ in 0..1000 {
+        f();
+    }
+}
+
+const fn increment(count: u8, (): ()) -> u8 {
+    count.wrapping_add(1)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Finite)]
+enum Door {
+    Closed,
+    Open,
+    Locked,
+}
+
+#[derive(Clone, Copy, Debug, Finite)]
+enum Action {
+    Push,
+    Pull,
+    Lock,
+}
+
+DefineTransform!(door, Door, Action,
+    (Door::Closed, Action::Push) => Door::Open,
+    (Door::Open, Action::Pull) => Door::Closed,
+    (Door::Closed, Action::Lock) => Door::Locked,
+    (state, _) => state,
+);
+
+#[test]
+fn apply() {
+    model(|| {
+        let machine = Arc::new(AtomicFSM::new(0, increment));
+
+        let threads = (0..2)
+            .map(|_| {
+                let machine = Arc::clone(&machine);
+                thread::spawn(move || machine.apply(()))
+            })
+            .collect::<Vec<_>>();
+        let mut transitions = vec![machine.apply(())];
+        transitions.extend(threads.into_iter().map(|thread| thread.join().unwrap()));
+
+        // Each input is applied to the state left by another
+        transitions.sort_unstable();
+        assert_eq!(transitions, [(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(machine.state(), 3);
+    });
+}
+
+#[test]
+fn apply_if() {
+    model(|| {
+        let machine = Arc::new(AtomicFSM::new(Door::Closed, door));
+
+        let other = Arc::clone(&machine);
+        let thread = thread::spawn(move || other.apply_if(Door::Closed, Action::Push));
+        let lock = machine.apply_if(Door::Closed, Action::Lock);
+        let push = thread.join().unwrap();
+
+        // Only one of the inputs is applied to the closed door
+        match (push, lock) {
+            (Ok(Door::Open), Err(Door::Open)) => assert_eq!(machine.state(), Door::Open),
+            (Err(Door::Locked), Ok(Door::Locked)) => assert_eq!(machine.state(), Door::Locked),
+            results => panic!("unexpected results {results:?}"),
+        }
+    });
+}
+
+#[test]
+fn apply_and_swap() {
+    model(|| {
+        let machine = Arc::new(AtomicFSM::new(Door::Closed, door));
+
+        let other = Arc::clone(&machine);
+        let thread = thread::spawn(move || other.apply(Action::Push));
+        let swapped = machine.swap(Door::Locked);
+        let (old, new) = thread.join().unwrap();
+
+        if swapped == Door::Closed {
+            // The swap came first, so the locked door can't be pushed open
+            assert_eq!((old, new), (Door::Locked, Door::Locked));
+            assert_eq!(machine.state(), Door::Locked);
+        } else {
+            assert_eq!((swapped, old, new), (Door::Open, Door::Closed, Door::Open));
+            assert_eq!(machine.state(), Door::Locked);
+        }
+    });
+}
@@ -0,0 +1,13 @@
+//! Checks the diagnostics of the derive and function-like macros, with the cases in `tests/ui`.
+//!
+//! Each case in `tests/ui/fail` must fail to compile with the errors in the `.stderr` file beside it,
+//! and each case in `tests/ui/pass` must compile. To update the `.stderr` files after changing
+//! a diagnostic, run the tests with `TRYBUILD=overwrite`.
+#![cfg(feature = "derive")]
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/fail/*.rs");
+    cases.pass("tests/ui/pass/*.rs");
+}
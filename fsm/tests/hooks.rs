@@ -0,0 +1,123 @@
+//! Checks the order in which `FSM` runs hooks, including for inputs that lead back to the same state.
+
+use fsm::{DefineTransform, Hooks, FSM};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Door {
+    Closed,
+    Open,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Push,
+    Knock,
+}
+
+DefineTransform!(door, Door, Action,
+    (Door::Closed, Action::Push) => Door::Open,
+    (Door::Open, Action::Push) => Door::Closed,
+    (state, Action::Knock) => state,
+);
+
+/// An input that isn't `Clone`.
+struct Token(Action);
+
+DefineTransform!(token, Door, Token,
+    (state, Token(action)) => door(state, action),
+);
+
+/// Gets the action of either kind of input.
+trait Act {
+    fn action(&self) -> Action;
+}
+
+impl Act for Action {
+    fn action(&self) -> Action {
+        *self
+    }
+}
+
+impl Act for Token {
+    fn action(&self) -> Action {
+        self.0
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Call {
+    Exit(Door),
+    Transition(Door, Action, Door),
+    Enter(Door),
+}
+
+/// Records every hook that is run, in order.
+struct Recorder(Vec<Call>);
+
+impl<Domain: Act> Hooks<Domain, Door> for Recorder {
+    fn on_exit(&mut self, state: &Door) {
+        self.0.push(Call::Exit(*state));
+    }
+
+    fn on_transition(&mut self, from: &Door, input: &Domain, to: &Door) {
+        self.0.push(Call::Transition(*from, input.action(), *to));
+    }
+
+    fn on_enter(&mut self, state: &Door) {
+        self.0.push(Call::Enter(*state));
+    }
+}
+
+#[test]
+fn order() {
+    let mut recorder = Recorder(Vec::new());
+    let fsm =
+        FSM::new(Door::Closed, door).run_with_hooks([Action::Push, Action::Push], &mut recorder);
+
+    assert_eq!(*fsm.state(), Door::Closed);
+    assert_eq!(
+        recorder.0,
+        [
+            Call::Exit(Door::Closed),
+            Call::Transition(Door::Closed, Action::Push, Door::Open),
+            Call::Enter(Door::Open),
+            Call::Exit(Door::Open),
+            Call::Transition(Door::Open, Action::Push, Door::Closed),
+            Call::Enter(Door::Closed),
+        ]
+    );
+}
+
+#[test]
+fn self_loop() {
+    let mut recorder = Recorder(Vec::new());
+    let fsm = FSM::new(Door::Open, door).apply_with_hooks(Action::Knock, &mut recorder);
+
+    assert_eq!(*fsm.state(), Door::Open);
+    assert_eq!(
+        recorder.0,
+        [
+            Call::Exit(Door::Open),
+            Call::Transition(Door::Open, Action::Knock, Door::Open),
+            Call::Enter(Door::Open),
+        ]
+    );
+}
+
+#[test]
+fn state_hooks() {
+    let mut recorder = Recorder(Vec::new());
+    let fsm = FSM::new(Door::Closed, token)
+        .run_with_state_hooks([Token(Action::Push), Token(Action::Knock)], &mut recorder);
+
+    assert_eq!(*fsm.state(), Door::Open);
+    assert_eq!(
+        recorder.0,
+        [
+            Call::Exit(Door::Closed),
+            Call::Enter(Door::Open),
+            Call::Exit(Door::Open),
+            Call::Enter(Door::Open),
+        ]
+    );
+}
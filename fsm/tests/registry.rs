@@ -0,0 +1,55 @@
+//! Checks how machines are saved when their transform function can't be found in the registry.
+#![cfg(feature = "serde")]
+
+use fsm::{DefineTransform, RegisterTransforms, Registry, FSM};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum States {
+    Off,
+    On,
+}
+
+DefineTransform!(toggle, States, bool,
+    (States::Off, true) => States::On,
+    (States::On, true) => States::Off,
+    (state, false) => state,
+);
+
+DefineTransform!(switch_on, States, bool,
+    (_, true) => States::On,
+    (state, false) => state,
+);
+
+RegisterTransforms!(States, bool, toggle);
+
+#[test]
+fn registered() {
+    let machine = FSM::new(States::Off, toggle).apply(true);
+    assert_eq!(States::transform_name(toggle), Some("toggle"));
+
+    let json = serde_json::to_string(&machine).unwrap();
+    let restored: FSM<bool, States> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, machine);
+}
+
+#[test]
+fn not_found() {
+    // A function that isn't found by its address, whether it is unregistered or has another address,
+    // fails to save rather than being saved with the wrong name
+    let machine = FSM::new(States::Off, switch_on);
+    assert_eq!(States::transform_name(switch_on), None);
+
+    let error = serde_json::to_string(&machine).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the transform function is not registered"
+    );
+
+    let error =
+        serde_json::from_str::<FSM<bool, States>>(r#"{"transform":"switch_on","state":"Off"}"#)
+            .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("the name of a registered transform function"));
+}
@@ -0,0 +1,116 @@
+//! Runs the SCXML conformance tests in `tests/scxml`.
+//!
+//! These are adapted from the W3C SCXML 1.0 Implementation Report Plan tests,
+//! rewritten for the null data model, as the originals use the ECMAScript data model.
+//! Each test passes by reaching the top level final state `pass`.
+#![cfg(feature = "std")]
+
+use std::{fs, path::Path};
+
+use fsm::scxml::{Interpreter, Statechart};
+
+#[test]
+fn w3c() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scxml");
+    let mut paths = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "scxml")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let mut failures = Vec::new();
+
+    for path in paths {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let document = fs::read_to_string(&path).unwrap();
+
+        match document.parse::<Statechart>().and_then(Interpreter::new) {
+            Ok(machine) => {
+                if machine.is_running() || !machine.is_active("pass") {
+                    let configuration = machine.configuration().collect::<Vec<_>>();
+                    failures.push(format!("{name}: finished in {configuration:?}"));
+                }
+            }
+            Err(error) => failures.push(format!("{name}: {error}")),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "failed tests:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn unsupported() {
+    let errors = [
+        r#"<scxml datamodel="ecmascript"><state/></scxml>"#,
+        r#"<scxml><state><transition cond="x > 1"/></state></scxml>"#,
+        r#"<scxml><state><onentry><send event="e" delay="1s"/></onentry></state></scxml>"#,
+        r"<scxml><state><invoke/></state></scxml>",
+    ];
+
+    for document in errors {
+        assert!(matches!(
+            document.parse::<Statechart>(),
+            Err(fsm::scxml::Error::Unsupported(_))
+        ));
+    }
+}
+
+#[test]
+fn nesting() {
+    // Deeply nested documents are rejected, rather than overflowing the stack
+    let document = format!("<scxml>{}", "<state>".repeat(100_000));
+    assert!(matches!(
+        document.parse::<Statechart>(),
+        Err(fsm::scxml::Error::Xml {
+            message: "elements are nested too deeply",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn livelock() {
+    // Cycles of eventless transitions, or of raised events, never become stable
+    let eventless = r#"
+        <scxml initial="a">
+            <state id="a"><transition target="b"/></state>
+            <state id="b"><transition target="a"/></state>
+        </scxml>
+    "#;
+    let chart = eventless.parse::<Statechart>().unwrap();
+    assert_eq!(
+        Interpreter::new(chart).unwrap_err(),
+        fsm::scxml::Error::Livelock
+    );
+
+    let raised = r#"
+        <scxml initial="a">
+            <state id="a">
+                <transition event="ping" target="b"/>
+            </state>
+            <state id="b">
+                <onentry><raise event="pong"/></onentry>
+                <transition event="pong" target="c"/>
+            </state>
+            <state id="c">
+                <onentry><raise event="ping"/></onentry>
+                <transition event="ping" target="b"/>
+            </state>
+        </scxml>
+    "#;
+    let mut machine = Interpreter::new(raised.parse().unwrap()).unwrap();
+    assert_eq!(machine.send("ping"), Err(fsm::scxml::Error::Livelock));
+
+    // The interpreter stops, so later events are ignored
+    assert!(!machine.is_running());
+    assert_eq!(machine.send("ping"), Ok(()));
+}
@@ -0,0 +1,78 @@
+use fsm::AcceptStates;
+
+#[derive(AcceptStates)]
+union Union {
+    a: u8,
+    b: i8,
+}
+
+#[derive(AcceptStates)]
+#[accept]
+enum OnEnum {
+    A,
+}
+
+#[derive(AcceptStates)]
+enum NameValue {
+    #[accept = "A"]
+    A,
+}
+
+#[derive(AcceptStates)]
+enum UnknownArgument {
+    #[accept(when = "true")]
+    A,
+}
+
+#[derive(AcceptStates)]
+enum RepeatedArgument {
+    #[accept(if = "true", if = "false")]
+    A,
+}
+
+#[derive(AcceptStates)]
+enum NotAnExpression {
+    #[accept(if = "count >")]
+    A { count: u8 },
+}
+
+#[derive(AcceptStates)]
+enum Repeated {
+    #[accept]
+    #[accept]
+    A,
+}
+
+#[derive(AcceptStates)]
+enum RepeatedTrap {
+    #[trap]
+    #[trap(if = "true")]
+    A,
+}
+
+#[derive(AcceptStates)]
+struct Missing(u8);
+
+#[derive(AcceptStates)]
+#[accept]
+struct PathOnly(u8);
+
+#[derive(AcceptStates)]
+#[accept(if = "self.0 == 0")]
+struct Guard(u8);
+
+#[derive(AcceptStates)]
+#[accept(with = Self::is_accepted)]
+#[trap(with = Self::is_trap)]
+#[trap(with = Self::is_trap)]
+struct RepeatedStruct(u8);
+
+#[derive(AcceptStates)]
+enum AcceptAndTrap {
+    #[accept]
+    #[trap]
+    A,
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use fsm::{AtomicFSM, Finite};
+
+#[derive(Clone, Copy)]
+struct Large(u16);
+
+impl Finite for Large {
+    const VALUES: &'static [Self] = &{
+        let mut values = [Self(0); 257];
+        let mut index = 0;
+        while index < values.len() {
+            values[index] = Self(index as u16);
+            index += 1;
+        }
+        values
+    };
+
+    fn index(&self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+fn main() {
+    let _ = AtomicFSM::new(Large(0), |state, ()| state);
+}
@@ -0,0 +1,24 @@
+use fsm::AtomicState;
+
+#[derive(Clone, Copy, AtomicState)]
+struct Struct;
+
+#[derive(Clone, Copy, AtomicState)]
+enum MissingRepr {
+    A,
+}
+
+#[derive(Clone, Copy, AtomicState)]
+#[repr(u16)]
+enum WrongRepr {
+    A,
+}
+
+#[derive(Clone, Copy, AtomicState)]
+#[repr(u8)]
+enum Fields {
+    A,
+    B(u8),
+}
+
+fn main() {}
@@ -0,0 +1,67 @@
+use fsm::ClassifyStates;
+
+#[derive(ClassifyStates)]
+enum MissingClassType {
+    #[accept(0)]
+    A,
+}
+
+#[derive(ClassifyStates)]
+#[class_type(0)]
+enum NotAType {
+    #[accept(0)]
+    A,
+}
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+#[class_type(u8)]
+enum RepeatedClassType {
+    #[accept(0)]
+    A,
+}
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+struct Struct(u8);
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+enum MissingClass {
+    #[accept]
+    A,
+}
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+enum BadGuard {
+    #[accept(0, when = "*_0 > 1")]
+    A(u8),
+}
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+enum Repeated {
+    #[accept(0)]
+    #[accept(1)]
+    A,
+}
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+enum TrapNameValue {
+    #[accept(0)]
+    A,
+    #[trap = "B"]
+    B,
+}
+
+#[derive(ClassifyStates)]
+#[class_type(u8)]
+enum AcceptAndTrap {
+    #[accept(0)]
+    #[trap]
+    A,
+}
+
+fn main() {}
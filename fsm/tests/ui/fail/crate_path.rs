@@ -0,0 +1,29 @@
+use fsm::{AcceptStates, Finite};
+
+#[derive(AcceptStates)]
+#[fsm(path = fsm)]
+enum UnknownArgument {
+    #[accept]
+    A,
+}
+
+#[derive(Finite)]
+#[fsm]
+enum PathOnly {
+    A,
+}
+
+#[derive(Finite)]
+#[fsm(crate = "fsm")]
+enum NotAPath {
+    A,
+}
+
+#[derive(Finite)]
+#[fsm(crate = fsm)]
+#[fsm(crate = fsm)]
+enum Repeated {
+    A,
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use fsm::Finite;
+
+#[derive(Finite)]
+struct Struct;
+
+#[derive(Finite)]
+union Union {
+    a: u8,
+    b: i8,
+}
+
+#[derive(Finite)]
+enum Fields {
+    A,
+    B(u8),
+}
+
+fn main() {}
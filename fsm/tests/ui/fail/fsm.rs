@@ -0,0 +1,165 @@
+mod missing {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A,
+            B,
+        }
+
+        enum Inputs {
+            X,
+            Y,
+        }
+
+        fn missing;
+
+        (A, _) => B,
+        (B, X) => A,
+    }
+}
+
+mod duplicate {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A,
+            B,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn duplicate;
+
+        (A, X) => B,
+        (A | B, X) => A,
+    }
+}
+
+mod shadowed {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A,
+            B,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn shadowed;
+
+        (A, X) => B,
+        (B, X) => A,
+        (_, X) => A,
+    }
+}
+
+mod unreachable {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A,
+            B,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn unreachable;
+
+        (_, _) => A,
+    }
+}
+
+mod unknown {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn unknown;
+
+        (A, Y) => B,
+    }
+}
+
+mod no_start {
+    fsm::fsm! {
+        enum States {
+            A,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn no_start;
+
+        (_, _) => A,
+    }
+}
+
+mod two_starts {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A,
+            #[start]
+            B,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn two_starts;
+
+        (_, _) => A,
+    }
+}
+
+mod fields {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            A(u8),
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn fields;
+
+        (_, _) => A,
+    }
+}
+
+mod accept_argument {
+    fsm::fsm! {
+        enum States {
+            #[start]
+            #[accept(always)]
+            A,
+        }
+
+        enum Inputs {
+            X,
+        }
+
+        fn accept_argument;
+
+        (_, _) => A,
+    }
+}
+
+fn main() {}
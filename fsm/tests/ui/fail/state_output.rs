@@ -0,0 +1,50 @@
+use fsm::StateOutput;
+
+#[derive(StateOutput)]
+enum MissingOutputType {
+    #[output(0)]
+    A,
+}
+
+#[derive(StateOutput)]
+#[output_type(0)]
+enum NotAType {
+    #[output(0)]
+    A,
+}
+
+#[derive(StateOutput)]
+#[output_type(u8)]
+struct Struct(u8);
+
+#[derive(StateOutput)]
+#[output_type(u8)]
+enum MissingOutput {
+    #[output(0)]
+    A,
+    B,
+}
+
+#[derive(StateOutput)]
+#[output_type(u8)]
+enum PathOnly {
+    #[output]
+    A,
+}
+
+#[derive(StateOutput)]
+#[output_type(u8)]
+enum NotAnExpression {
+    #[output(0 +)]
+    A,
+}
+
+#[derive(StateOutput)]
+#[output_type(u8)]
+enum Repeated {
+    #[output(0)]
+    #[output = 1]
+    A,
+}
+
+fn main() {}
@@ -0,0 +1,72 @@
+use fsm::Transitions;
+
+#[derive(Clone, Copy)]
+enum Inputs {
+    Next,
+}
+
+#[derive(Clone, Copy, Transitions)]
+enum MissingInputType {
+    #[on(Next => B)]
+    A,
+    B,
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+struct Struct;
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+enum NotATransition {
+    #[on(Next)]
+    A,
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+enum NotAState {
+    #[default_transition(0)]
+    A,
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+#[default_transition(Self)]
+#[default_transition(Self)]
+enum RepeatedDefault {
+    A,
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+enum Fields {
+    #[on(Next => Self)]
+    A(u8),
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+enum Duplicate {
+    #[on(Next => A, Next => B)]
+    A,
+    B,
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+enum Shadowed {
+    #[on(Next => B)]
+    #[on(Inputs::Next => A)]
+    A,
+    B,
+}
+
+#[derive(Clone, Copy, Transitions)]
+#[input_type(Inputs)]
+#[default_transition = 0]
+enum NotAString {
+    A,
+}
+
+fn main() {}
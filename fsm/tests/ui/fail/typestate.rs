@@ -0,0 +1,43 @@
+use fsm::Typestate;
+
+#[derive(Clone, Copy)]
+enum Door {
+    Closed,
+    Open,
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    Push,
+    Pull,
+}
+
+Typestate!(#[typestate(start = Door::Closed)] door, Door, Action,
+    (Door::Closed, Action::Push) => Door::Open,
+    (Door::Open, Action::Pull) => Door::Closed,
+    (state, _) => state,
+);
+
+Typestate!(after_wildcard, Door, Action,
+    (Door::Closed, Action::Push) => Door::Open,
+    (Door::Closed, _) => Door::Closed,
+    (Door::Open, Action::Pull) => Door::Closed,
+    (state, _) => state,
+);
+
+Typestate!(repeated, Door, Action,
+    (Door::Closed, Action::Push) => Door::Open,
+    (Door::Closed | Door::Open, Action::Push | Action::Pull) => Door::Closed,
+);
+
+Typestate!(#[typestate(begin = Door::Closed)] bad_setting, Door, Action,
+    (state, _) => state,
+);
+
+fn main() {
+    // There is no transition from `Closed` for `Pull`
+    let _ = door::Machine::<door::Closed>::new().pull();
+
+    // Only the start state can be created directly
+    let _ = door::Machine::<door::Open>::new();
+}
@@ -0,0 +1,39 @@
+use fsm::Typestate;
+
+#[derive(Clone, Copy)]
+enum Power {
+    Off,
+    On,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+enum Switch {
+    Enter,
+    FromState,
+    New,
+    PowerOff,
+    Power_Off,
+}
+
+Typestate!(enter, Power, Switch,
+    (Power::Off, Switch::Enter) => Power::On,
+    (state, _) => state,
+);
+
+Typestate!(from_state, Power, Switch,
+    (Power::Off, Switch::FromState) => Power::On,
+    (state, _) => state,
+);
+
+Typestate!(#[typestate(start = Power::Off)] new, Power, Switch,
+    (Power::Off, Switch::New) => Power::On,
+    (state, _) => state,
+);
+
+Typestate!(same_method, Power, Switch,
+    (Power::On, Switch::PowerOff | Switch::Power_Off) => Power::Off,
+    (state, _) => state,
+);
+
+fn main() {}
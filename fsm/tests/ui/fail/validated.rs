@@ -0,0 +1,45 @@
+use fsm::{Finite, ValidatedTransform};
+
+#[derive(Clone, Copy, Debug, PartialEq, Finite)]
+enum Cycle {
+    Start,
+    Stuck,
+    Ping,
+    Pong,
+}
+
+#[derive(Clone, Copy, Debug, Finite)]
+enum Tick {
+    Tick,
+}
+
+// `Ping` and `Pong` reach each other, but can't be reached from `Start`
+ValidatedTransform!(#[validate(deny, start = Cycle::Start)] unreachable_cycle, Cycle, Tick,
+    (Cycle::Start, Tick::Tick) => Cycle::Stuck,
+    (Cycle::Stuck, Tick::Tick) => Cycle::Stuck,
+    (Cycle::Ping, Tick::Tick) => Cycle::Pong,
+    (Cycle::Pong, Tick::Tick) => Cycle::Ping,
+);
+
+// The last arm is shadowed by the ones before it
+ValidatedTransform!(#[validate(deny)] shadowed, Cycle, Tick,
+    (Cycle::Start | Cycle::Stuck, Tick::Tick) => Cycle::Ping,
+    (Cycle::Ping | Cycle::Pong, Tick::Tick) => Cycle::Stuck,
+    (_, Tick::Tick) => Cycle::Start,
+);
+
+// Failed checks are only warnings by default
+ValidatedTransform!(warned, Cycle, Tick,
+    (_, Tick::Tick) => Cycle::Start,
+    (Cycle::Start, Tick::Tick) => Cycle::Stuck,
+);
+
+ValidatedTransform!(#[validate(strict)] unknown_setting, Cycle, Tick,
+    (_, _) => Cycle::Start,
+);
+
+ValidatedTransform!(#[validate(start)] missing_start, Cycle, Tick,
+    (_, _) => Cycle::Start,
+);
+
+fn main() {}
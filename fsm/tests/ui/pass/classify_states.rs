@@ -0,0 +1,29 @@
+use fsm::{AcceptStates, ClassifyStates};
+
+#[derive(ClassifyStates)]
+#[class_type(&'static str)]
+enum Token {
+    Empty,
+    #[accept("word")]
+    Word(char),
+    #[accept("number", if = "*digits > 0")]
+    Number { digits: u8 },
+}
+
+fn main() {
+    assert_eq!(Token::Empty.class(), None);
+    assert_eq!(Token::Word('a').class(), Some("word"));
+    assert_eq!(Token::Number { digits: 2 }.class(), Some("number"));
+    assert_eq!(Token::Number { digits: 0 }.class(), None);
+
+    // The derived `AcceptStates` agrees with the classes
+    let tokens = [
+        Token::Empty,
+        Token::Word('a'),
+        Token::Number { digits: 0 },
+        Token::Number { digits: 2 },
+    ];
+    for token in tokens {
+        assert_eq!(AcceptStates::is_accepted(&token), token.is_classified());
+    }
+}
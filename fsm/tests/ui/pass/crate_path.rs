@@ -0,0 +1,67 @@
+//! The macros can find the library through a renamed or reexported path.
+
+mod reexport {
+    pub use ::fsm as machines;
+}
+
+/// Shadows the library, so that only the given paths to it work.
+mod fsm {}
+
+use reexport::machines::{
+    fsm, AcceptStates, ClassifyStates, Finite, StateOutput, ValidatedTransform,
+};
+
+#[derive(Clone, Copy, AcceptStates, Finite, StateOutput)]
+#[fsm(crate = reexport::machines)]
+#[output_type(u8)]
+enum States {
+    #[output(0)]
+    A,
+    #[accept]
+    #[output(1)]
+    B,
+}
+
+#[derive(ClassifyStates)]
+#[fsm(crate = crate::reexport::machines)]
+#[class_type(char)]
+enum Classified {
+    #[accept('a')]
+    A,
+    B,
+}
+
+fsm! {
+    #[fsm(crate = reexport::machines)]
+    enum Light {
+        #[start]
+        Off,
+        #[accept]
+        On,
+    }
+
+    enum Switch {
+        Toggle,
+    }
+
+    fn light;
+
+    (Off, Toggle) => On,
+    (On, Toggle) => Off,
+}
+
+ValidatedTransform!(#[fsm(crate = reexport::machines)] #[validate(deny)] flip, Light, Switch,
+    (Light::Off, Switch::Toggle) => Light::On,
+    (Light::On, Switch::Toggle) => Light::Off,
+);
+
+fn main() {
+    assert_eq!(light(Light::Off, Switch::Toggle), Light::On);
+    assert_eq!(flip(Light::On, Switch::Toggle), Light::Off);
+    assert!(Light::On.is_accepted());
+    assert!(States::B.is_accepted());
+    assert_eq!(States::B.index(), 1);
+    assert_eq!(States::A.output(), 0);
+    assert_eq!(Classified::A.class(), Some('a'));
+    assert!(!Classified::B.is_accepted());
+}
@@ -0,0 +1,16 @@
+use fsm::Finite;
+
+// Fieldless enums can only have const generics, which the impl keeps
+#[derive(Clone, Copy, Debug, PartialEq, Finite)]
+enum Channel<const WIDTH: usize>
+where
+    [u8; WIDTH]: Sized,
+{
+    Left,
+    Right,
+}
+
+fn main() {
+    assert_eq!(Channel::<8>::VALUES, [Channel::Left, Channel::Right]);
+    assert_eq!(Channel::<16>::Right.index(), 1);
+}
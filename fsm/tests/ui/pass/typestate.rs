@@ -0,0 +1,49 @@
+//! Bare variant names, method names from acronyms and keywords, and a renamed crate.
+
+mod machine {
+    use fsm::Typestate;
+
+    mod reexport {
+        pub use fsm as renamed;
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum States {
+        Idle,
+        Waiting,
+    }
+    use States::*;
+
+    #[derive(Clone, Copy)]
+    pub enum Inputs {
+        HTTPRequest,
+        Move,
+        Reset2Idle,
+    }
+    use Inputs::*;
+
+    Typestate!(
+        #[typestate(start = Idle)]
+        #[fsm(crate = reexport::renamed)]
+        pub server, States, Inputs,
+        (Idle, HTTPRequest) => Waiting,
+        (Waiting, Move) => Waiting,
+        (Waiting, Reset2Idle) => Idle,
+        (state, _) => state,
+    );
+}
+
+use machine::{server, States};
+
+fn main() {
+    let machine = server::Machine::<server::Idle>::new()
+        .http_request()
+        .r#move()
+        .reset2_idle();
+    assert_eq!(server::Machine::<server::Idle>::STATE, States::Idle);
+    assert_eq!(fsm::FSM::from(machine).state(), &States::Idle);
+
+    // Other states can be entered from a matching runtime state
+    assert!(server::Machine::<server::Waiting>::from_state(&States::Waiting).is_some());
+    assert!(server::Machine::<server::Waiting>::from_state(&States::Idle).is_none());
+}
@@ -0,0 +1,27 @@
+//! Bare variant names brought in with `use`, which are not variables bound to the current state.
+
+use fsm::{Finite, ValidatedTransform};
+
+#[derive(Clone, Copy, Debug, PartialEq, Finite)]
+enum Lamp {
+    Off,
+    On,
+}
+use Lamp::*;
+
+#[derive(Clone, Copy, Debug, Finite)]
+enum Wire {
+    Toggle,
+    Trip,
+}
+use Wire::*;
+
+ValidatedTransform!(#[validate(deny, start = Off)] lamp, Lamp, Wire,
+    (Off, Toggle) => On,
+    (Off | On, Trip) => On,
+    (On, Toggle) => Off,
+);
+
+fn main() {
+    assert_eq!(lamp(Off, Trip), On);
+}
@@ -0,0 +1,234 @@
+//! Analysis of machines: reachability, dead states, minimization and comparison.
+//!
+//! Inputs without a transition lead to an implicit rejecting state, which is never left.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::machine::{Kind, Machine};
+
+/// The inputs that a machine can distinguish: each named input,
+/// then any other input, if the machine has `otherwise` transitions.
+fn symbols(machine: &Machine) -> Vec<Option<String>> {
+    let mut symbols = machine.inputs().into_iter().map(Some).collect::<Vec<_>>();
+
+    if (0..machine.states().len()).any(|state| machine.otherwise(state).is_some()) {
+        symbols.push(None);
+    }
+    symbols
+}
+
+/// Get the states that can't be reached from the start state.
+pub fn unreachable(machine: &Machine) -> Vec<usize> {
+    let symbols = symbols(machine);
+
+    let mut reached = vec![false; machine.states().len()];
+    reached[machine.start()] = true;
+
+    let mut queue = VecDeque::from([machine.start()]);
+    while let Some(state) = queue.pop_front() {
+        for symbol in &symbols {
+            if let Some(next) = machine.step(state, symbol.as_deref()) {
+                if !reached[next] {
+                    reached[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    (0..reached.len())
+        .filter(|&state| !reached[state])
+        .collect()
+}
+
+/// Get the states that can't reach an accept state.
+pub fn dead(machine: &Machine) -> Vec<usize> {
+    let symbols = symbols(machine);
+    let states = machine.states().len();
+
+    let mut live = (0..states)
+        .map(|state| machine.is_accepting(state))
+        .collect::<Vec<_>>();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for state in 0..states {
+            let reaches_live = symbols.iter().any(|symbol| {
+                machine
+                    .step(state, symbol.as_deref())
+                    .is_some_and(|next| live[next])
+            });
+            if !live[state] && reaches_live {
+                live[state] = true;
+                changed = true;
+            }
+        }
+    }
+
+    (0..states).filter(|&state| !live[state]).collect()
+}
+
+/// Get the class of each state, where states in the same class accept the same inputs.
+///
+/// There is an extra class at the end for the implicit rejecting state.
+fn classes(machine: &Machine) -> Vec<usize> {
+    let symbols = symbols(machine);
+    let states = machine.states().len();
+    let sink = states;
+
+    let successors = (0..states)
+        .map(|state| {
+            symbols
+                .iter()
+                .map(|symbol| machine.step(state, symbol.as_deref()).unwrap_or(sink))
+                .collect::<Vec<_>>()
+        })
+        .chain([vec![sink; symbols.len()]])
+        .collect::<Vec<_>>();
+
+    let mut classes = (0..states)
+        .map(|state| usize::from(machine.is_accepting(state)))
+        .chain([0])
+        .collect::<Vec<_>>();
+    let mut count = 0;
+
+    // Split the classes until every state in a class has successors in the same classes
+    loop {
+        let mut signatures: Vec<(usize, Vec<usize>)> = Vec::new();
+        let refined = successors
+            .iter()
+            .enumerate()
+            .map(|(state, successors)| {
+                let signature = (
+                    classes[state],
+                    successors.iter().map(|&next| classes[next]).collect(),
+                );
+                signatures
+                    .iter()
+                    .position(|other| *other == signature)
+                    .unwrap_or_else(|| {
+                        signatures.push(signature);
+                        signatures.len() - 1
+                    })
+            })
+            .collect();
+
+        classes = refined;
+        if signatures.len() == count {
+            return classes;
+        }
+        count = signatures.len();
+    }
+}
+
+/// Get the groups of reachable states that accept the same inputs, and could be merged.
+pub fn equivalent(machine: &Machine) -> Vec<Vec<usize>> {
+    let classes = classes(machine);
+    let unreachable = unreachable(machine);
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for state in (0..machine.states().len()).filter(|state| !unreachable.contains(state)) {
+        match groups
+            .iter_mut()
+            .find(|group| classes[group[0]] == classes[state])
+        {
+            Some(group) => group.push(state),
+            None => groups.push(vec![state]),
+        }
+    }
+
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Get the number of states in the smallest equivalent machine.
+pub fn minimized(machine: &Machine) -> usize {
+    let classes = classes(machine);
+    let unreachable = unreachable(machine);
+
+    let mut seen = Vec::new();
+    for state in (0..machine.states().len()).filter(|state| !unreachable.contains(state)) {
+        if !seen.contains(&classes[state]) {
+            seen.push(classes[state]);
+        }
+    }
+    seen.len()
+}
+
+/// Find the shortest input accepted by exactly one of the machines, if there is one.
+///
+/// # Errors
+/// This returns an error if the machines have different kinds of input.
+pub fn difference(a: &Machine, b: &Machine) -> Result<Option<Vec<String>>, String> {
+    if a.kind() != b.kind() {
+        return Err("machines from the text and JSON formats can't be compared".to_owned());
+    }
+
+    let mut inputs = a.inputs();
+    let mut known = inputs.iter().cloned().collect::<HashSet<_>>();
+    for input in b.inputs() {
+        if known.insert(input.clone()) {
+            inputs.push(input);
+        }
+    }
+
+    // Any other character is handled by the `otherwise` transitions
+    let other = (a.kind() == Kind::Characters)
+        .then(|| {
+            ('a'..='z')
+                .chain('A'..='Z')
+                .chain('0'..='9')
+                .chain(' '..='~')
+                .chain('\u{a1}'..=char::MAX)
+                .map(String::from)
+                .find(|character| !known.contains(character))
+        })
+        .flatten();
+
+    let mut symbols = inputs
+        .iter()
+        .map(|input| Some(input.as_str()))
+        .collect::<Vec<_>>();
+    if other.is_some() {
+        symbols.push(None);
+    }
+
+    let step = |machine: &Machine, state: Option<usize>, symbol| {
+        state.and_then(|state| machine.step(state, symbol))
+    };
+    let accepts = |machine: &Machine, state: Option<usize>| {
+        state.is_some_and(|state| machine.is_accepting(state))
+    };
+
+    // Search the pairs of states, recording how each pair was first reached
+    let start = (Some(a.start()), Some(b.start()));
+    let mut parents: HashMap<_, Option<(_, usize)>> = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(pair) = queue.pop_front() {
+        if accepts(a, pair.0) != accepts(b, pair.1) {
+            let mut word = Vec::new();
+            let mut current = pair;
+            while let Some(&Some((parent, symbol))) = parents.get(&current) {
+                word.push(
+                    symbols[symbol]
+                        .map_or_else(|| other.clone().unwrap_or_default(), str::to_owned),
+                );
+                current = parent;
+            }
+            word.reverse();
+            return Ok(Some(word));
+        }
+
+        for (index, &symbol) in symbols.iter().enumerate() {
+            let next = (step(a, pair.0, symbol), step(b, pair.1, symbol));
+            parents.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                Some((pair, index))
+            });
+        }
+    }
+
+    Ok(None)
+}
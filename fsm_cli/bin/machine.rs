@@ -0,0 +1,169 @@
+//! Machines loaded from either format, as the library's machine types.
+
+use fsm::{
+    table::{Table, TableFSM},
+    text, DynFSM,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// What the inputs of a machine are.
+pub enum Kind {
+    /// Characters, from the text format.
+    Characters,
+    /// Names, from the JSON format.
+    Names,
+}
+
+#[derive(Clone, Debug)]
+/// A machine, with named states and inputs, in its start state.
+pub enum Machine {
+    /// A machine from the text format.
+    Text(DynFSM<char>),
+    /// A machine from the JSON format.
+    Json(TableFSM),
+}
+
+/// The result of running a machine.
+pub struct Run {
+    /// The last state reached.
+    pub state: usize,
+    /// The input with no transition from the last state, if the machine stopped early.
+    pub stuck: Option<String>,
+}
+
+/// Returns [`true`] if a machine's source is JSON, rather than the text format.
+fn is_json(source: &str) -> bool {
+    source.trim_start().starts_with('{')
+}
+
+impl Machine {
+    /// Load a machine from JSON, if the source starts with `{`, otherwise from the text format.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if is_json(source) {
+            Table::from_json(source)
+                .map(|table| Self::Json(TableFSM::new(table)))
+                .map_err(|error| error.to_string())
+        } else {
+            // Unreachable states are allowed, so `analyze` can report them
+            text::parse_allowing_unreachable(source)
+                .map(Self::Text)
+                .map_err(|error| error.to_string())
+        }
+    }
+
+    pub const fn kind(&self) -> Kind {
+        match self {
+            Self::Text(_) => Kind::Characters,
+            Self::Json(_) => Kind::Names,
+        }
+    }
+
+    pub fn states(&self) -> &[String] {
+        match self {
+            Self::Text(machine) => machine.states(),
+            Self::Json(machine) => machine.table().states(),
+        }
+    }
+
+    /// Get the names of the inputs, in the order of their indices.
+    pub fn inputs(&self) -> Vec<String> {
+        match self {
+            Self::Text(machine) => machine.inputs().iter().map(char::to_string).collect(),
+            Self::Json(machine) => machine.table().inputs().to_vec(),
+        }
+    }
+
+    pub const fn start(&self) -> usize {
+        match self {
+            Self::Text(machine) => machine.start(),
+            Self::Json(machine) => machine.table().start(),
+        }
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        match self {
+            Self::Text(machine) => machine.is_accepting(state),
+            Self::Json(machine) => machine.table().is_accepting(state),
+        }
+    }
+
+    pub fn next(&self, state: usize, input: usize) -> usize {
+        match self {
+            Self::Text(machine) => machine.next(state, input),
+            Self::Json(machine) => machine.table().next(state, input),
+        }
+    }
+
+    pub fn otherwise(&self, state: usize) -> Option<usize> {
+        match self {
+            Self::Text(machine) => machine.otherwise(state),
+            Self::Json(_) => None,
+        }
+    }
+
+    /// Get the next state for a named input, or for any other input if `input` is [`None`].
+    pub fn step(&self, state: usize, input: Option<&str>) -> Option<usize> {
+        let index = input.and_then(|input| match self {
+            Self::Text(machine) => {
+                let mut characters = input.chars();
+                match (characters.next(), characters.next()) {
+                    (Some(character), None) => machine.input_index(&character),
+                    _ => None,
+                }
+            }
+            Self::Json(machine) => machine.table().input_index(input),
+        });
+
+        index.map_or_else(
+            || self.otherwise(state),
+            |input| Some(self.next(state, input)),
+        )
+    }
+
+    /// Run the machine over some text, as characters or as whitespace separated names.
+    pub fn run(&self, text: &str) -> Run {
+        match self {
+            Self::Text(machine) => {
+                let mut machine = machine.clone();
+                let stuck = text
+                    .chars()
+                    .find(|&character| machine.try_apply_assign(character).is_err())
+                    .map(String::from);
+                Run {
+                    state: machine.state_index(),
+                    stuck,
+                }
+            }
+            Self::Json(machine) => {
+                let mut machine = machine.clone();
+                let stuck = text
+                    .split_whitespace()
+                    .find(|input| machine.apply_assign(input).is_err())
+                    .map(str::to_owned);
+                Run {
+                    state: machine.state_index(),
+                    stuck,
+                }
+            }
+        }
+    }
+
+    /// Returns [`true`] if the machine accepts a sequence of inputs.
+    pub fn accepts(&self, inputs: &[String]) -> bool {
+        inputs
+            .iter()
+            .try_fold(self.start(), |state, input| self.step(state, Some(input)))
+            .is_some_and(|state| self.is_accepting(state))
+    }
+
+    /// Write an input as it would appear in the machine's source.
+    pub fn quote(&self, input: &str) -> String {
+        match self {
+            Self::Text(_) => input
+                .chars()
+                .next()
+                .map_or_else(String::new, |character| format!("{character:?}")),
+            Self::Json(_) => input.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! A command-line tool for inspecting and running machines, written in the
+//! [text format](fsm::text) or the [JSON format](fsm::table).
+//!
+//! The exit code is 0 for success, 1 for a negative result, such as a rejected input,
+//! and 2 for an error, such as an invalid machine.
+
+#![warn(
+    clippy::all,
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::perf,
+    clippy::cargo,
+    clippy::get_unwrap,
+    clippy::panic_in_result_fn,
+    clippy::todo,
+    clippy::undocumented_unsafe_blocks
+)]
+
+mod analysis;
+mod machine;
+mod render;
+
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Read, Write},
+    process::ExitCode,
+};
+
+use fsm::debug::{Debugger, Target};
+use machine::Machine;
+
+const USAGE: &str = "\
+Usage: fsm-cli <command> [options]
+
+Machines are read from JSON if they start with `{`, otherwise from the text format.
+A path of `-` reads from standard input.
+
+Commands:
+  render <machine> [--dot | --mermaid]
+      Print the machine as a Graphviz DOT (the default) or Mermaid diagram.
+  run <machine> [--lines] [<input>...]
+      Run the machine over each input file, or standard input, printing `accept` or
+      `reject` with the final state. Text machines read characters, with a final newline
+      removed, and JSON machines read whitespace separated input names.
+      With `--lines`, each line is run separately.
+      Exits with 1 if any input is rejected.
+  analyze <machine>
+      Print the unreachable, dead and equivalent states, and the size of the minimized machine.
+      Exits with 1 if there are unreachable or equivalent states.
+  repl <machine>
+      Step through the machine with commands read from standard input, until `quit`.
+      Type `help` for the commands.
+  diff <machine> <machine>
+      Print the shortest input accepted by only one of the machines, if there is one.
+      Exits with 1 if the machines differ.
+";
+
+/// The result of a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Success,
+    Failure,
+}
+
+fn main() -> ExitCode {
+    let arguments = env::args().skip(1).collect::<Vec<_>>();
+    let arguments = arguments.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match command(&arguments) {
+        Ok(Outcome::Success) => ExitCode::SUCCESS,
+        Ok(Outcome::Failure) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("fsm-cli: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn command(arguments: &[&str]) -> Result<Outcome, String> {
+    match arguments {
+        ["render", path, options @ ..] => {
+            let machine = load(path)?;
+            let diagram = match options {
+                [] | ["--dot"] => render::dot(&machine),
+                ["--mermaid"] => render::mermaid(&machine),
+                _ => return Err(usage()),
+            };
+            print!("{diagram}");
+            Ok(Outcome::Success)
+        }
+        ["run", path, options @ ..] => {
+            let (lines, inputs) = match options {
+                ["--lines", inputs @ ..] => (true, inputs),
+                inputs => (false, inputs),
+            };
+            run(&load(path)?, lines, inputs)
+        }
+        ["analyze", path] => Ok(analyze(&load(path)?)),
+        ["repl", path] => repl(path),
+        ["diff", a, b] => diff(a, &load(a)?, b, &load(b)?),
+        ["help" | "--help" | "-h"] => {
+            print!("{USAGE}");
+            Ok(Outcome::Success)
+        }
+        _ => Err(usage()),
+    }
+}
+
+fn usage() -> String {
+    format!("invalid arguments\n\n{USAGE}")
+}
+
+/// Read a file, or standard input for `-`.
+fn read(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("failed to read standard input: {error}"))?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).map_err(|error| format!("failed to read `{path}`: {error}"))
+    }
+}
+
+fn load(path: &str) -> Result<Machine, String> {
+    Machine::parse(&read(path)?).map_err(|error| format!("invalid machine `{path}`: {error}"))
+}
+
+fn run(machine: &Machine, lines: bool, inputs: &[&str]) -> Result<Outcome, String> {
+    let paths = if inputs.is_empty() { &["-"] } else { inputs };
+
+    let mut outcome = Outcome::Success;
+    for path in paths {
+        let text = read(path)?;
+        let runs = if lines {
+            text.lines().collect()
+        } else {
+            let text = text.strip_suffix('\n').unwrap_or(&text);
+            vec![text.strip_suffix('\r').unwrap_or(text)]
+        };
+
+        for input in runs {
+            let run = machine.run(input);
+            let accepted = run.stuck.is_none() && machine.is_accepting(run.state);
+            if !accepted {
+                outcome = Outcome::Failure;
+            }
+
+            if paths.len() > 1 {
+                print!("{path}: ");
+            }
+            print!(
+                "{} {}",
+                if accepted { "accept" } else { "reject" },
+                machine.states()[run.state]
+            );
+            if let Some(input) = run.stuck {
+                print!(" (no transition for {})", machine.quote(&input));
+            }
+            println!();
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Join the names of some states, or `none`.
+fn names(machine: &Machine, states: &[usize]) -> String {
+    if states.is_empty() {
+        return "none".to_owned();
+    }
+
+    states
+        .iter()
+        .map(|&state| machine.states()[state].as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn analyze(machine: &Machine) -> Outcome {
+    let unreachable = analysis::unreachable(machine);
+    let dead = analysis::dead(machine);
+    let equivalent = analysis::equivalent(machine);
+
+    println!("states: {}", machine.states().len());
+    println!("unreachable: {}", names(machine, &unreachable));
+    println!("dead: {}", names(machine, &dead));
+
+    if equivalent.is_empty() {
+        println!("equivalent: none");
+    } else {
+        let groups = equivalent
+            .iter()
+            .map(|group| format!("{{{}}}", names(machine, group)))
+            .collect::<Vec<_>>();
+        println!("equivalent: {}", groups.join(", "));
+    }
+    println!("minimized: {}", analysis::minimized(machine));
+
+    if unreachable.is_empty() && equivalent.is_empty() {
+        Outcome::Success
+    } else {
+        Outcome::Failure
+    }
+}
+
+fn repl(path: &str) -> Result<Outcome, String> {
+    if path == "-" {
+        return Err(
+            "`repl` reads commands from standard input, so can't read the machine from it"
+                .to_owned(),
+        );
+    }
+
+    match load(path)? {
+        Machine::Text(machine) => session(Debugger::new(machine)),
+        Machine::Json(machine) => session(Debugger::new(machine)),
+    }
+}
+
+/// Run debugger commands from standard input, prompting for them if it is a terminal.
+fn session<M: Target>(mut debugger: Debugger<M>) -> Result<Outcome, String> {
+    let stdin = io::stdin();
+    let prompt = stdin.is_terminal();
+
+    loop {
+        if prompt {
+            print!("> ");
+            let _ = io::stdout().flush();
+        }
+
+        let mut line = String::new();
+        let read = stdin
+            .read_line(&mut line)
+            .map_err(|error| format!("failed to read standard input: {error}"))?;
+        if read == 0 {
+            return Ok(Outcome::Success);
+        }
+
+        match line.trim() {
+            "quit" | "exit" => return Ok(Outcome::Success),
+            command => match debugger.execute(command) {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => println!("{output}"),
+                Err(error) => println!("error: {error}"),
+            },
+        }
+    }
+}
+
+fn diff(path_a: &str, a: &Machine, path_b: &str, b: &Machine) -> Result<Outcome, String> {
+    let Some(word) = analysis::difference(a, b)? else {
+        println!("equivalent");
+        return Ok(Outcome::Success);
+    };
+
+    let (accepted, rejected) = if a.accepts(&word) {
+        (path_a, path_b)
+    } else {
+        (path_b, path_a)
+    };
+
+    let input = match a.kind() {
+        machine::Kind::Characters => format!("{:?}", word.concat()),
+        machine::Kind::Names => format!("{:?}", word.join(" ")),
+    };
+    println!("differ: {input} is accepted by `{accepted}` and rejected by `{rejected}`");
+    Ok(Outcome::Failure)
+}
@@ -0,0 +1,164 @@
+//! Rendering machines as Graphviz DOT and Mermaid diagrams.
+
+use std::fmt::Write;
+
+use crate::machine::{Kind, Machine};
+
+/// Get the edges from a state, as the target and a label for the inputs leading to it.
+///
+/// Inputs covered by the `otherwise` transition are left out, and the `otherwise`
+/// transition is labelled `_`.
+fn edges(machine: &Machine, state: usize) -> Vec<(usize, String)> {
+    let otherwise = machine.otherwise(state);
+
+    let names = machine.inputs();
+    let mut targets: Vec<(usize, Vec<&str>)> = Vec::new();
+    for (input, name) in names.iter().enumerate() {
+        let target = machine.next(state, input);
+        if Some(target) == otherwise {
+            continue;
+        }
+
+        match targets.iter_mut().find(|(other, _)| *other == target) {
+            Some((_, inputs)) => inputs.push(name),
+            None => targets.push((target, vec![name])),
+        }
+    }
+
+    let mut edges = targets
+        .into_iter()
+        .map(|(target, inputs)| {
+            let label = match machine.kind() {
+                Kind::Characters => characters_label(&inputs),
+                Kind::Names => inputs.join(", "),
+            };
+            (target, label)
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(target) = otherwise {
+        edges.push((target, "_".to_owned()));
+    }
+    edges
+}
+
+/// Label a set of characters, combining consecutive characters into ranges.
+fn characters_label(inputs: &[&str]) -> String {
+    let mut characters = inputs
+        .iter()
+        .filter_map(|input| input.chars().next())
+        .collect::<Vec<_>>();
+    characters.sort_unstable();
+
+    let mut ranges: Vec<(char, char)> = Vec::new();
+    for character in characters {
+        match ranges.last_mut() {
+            Some((_, high)) if u32::from(*high) + 1 == u32::from(character) => *high = character,
+            _ => ranges.push((character, character)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(low, high)| {
+            if low == high {
+                format!("{low:?}")
+            } else {
+                format!("{low:?}..={high:?}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Escape text for a quoted DOT string.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Render a machine as a Graphviz DOT digraph.
+pub fn dot(machine: &Machine) -> String {
+    let states = machine.states();
+    let mut dot = String::from("digraph {\n    rankdir=LR;\n    node [shape=circle];\n");
+
+    // An invisible node, with an arrow to the start state
+    let _ = writeln!(dot, "    \"\" [shape=none, width=0, height=0];");
+    let _ = writeln!(
+        dot,
+        "    \"\" -> \"{}\";",
+        dot_escape(&states[machine.start()])
+    );
+
+    for (state, name) in states.iter().enumerate() {
+        if machine.is_accepting(state) {
+            let _ = writeln!(dot, "    \"{}\" [shape=doublecircle];", dot_escape(name));
+        }
+    }
+
+    for (state, name) in states.iter().enumerate() {
+        for (target, label) in edges(machine, state) {
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                dot_escape(name),
+                dot_escape(&states[target]),
+                dot_escape(&label)
+            );
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Escape text for Mermaid, where `#` starts an entity code, and `"`, `:` and `;` end a name or label.
+fn mermaid_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '#' => escaped.push_str("#35;"),
+            '"' => escaped.push_str("#quot;"),
+            ':' => escaped.push_str("#58;"),
+            ';' => escaped.push_str("#59;"),
+            '\n' => escaped.push_str("#10;"),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Render a machine as a Mermaid state diagram.
+///
+/// States are declared with ids `s0`, `s1` and so on, with their names as descriptions,
+/// so names don't need to be valid ids.
+pub fn mermaid(machine: &Machine) -> String {
+    let states = machine.states();
+    let mut mermaid = String::from("stateDiagram-v2\n");
+
+    for (state, name) in states.iter().enumerate() {
+        let _ = writeln!(
+            mermaid,
+            "    state \"{}\" as s{state}",
+            mermaid_escape(name)
+        );
+    }
+
+    let _ = writeln!(mermaid, "    [*] --> s{}", machine.start());
+    for state in 0..states.len() {
+        if machine.is_accepting(state) {
+            let _ = writeln!(mermaid, "    s{state} --> [*]");
+        }
+    }
+
+    for state in 0..states.len() {
+        for (target, label) in edges(machine, state) {
+            let _ = writeln!(
+                mermaid,
+                "    s{state} --> s{target}: {}",
+                mermaid_escape(&label)
+            );
+        }
+    }
+
+    mermaid
+}
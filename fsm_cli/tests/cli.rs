@@ -0,0 +1,160 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Run the tool from the `machines` directory, with some standard input.
+fn fsm_cli(arguments: &[&str], stdin: &str) -> (Option<i32>, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fsm-cli"))
+        .args(arguments)
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/machines"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    let Output {
+        status,
+        stdout,
+        stderr,
+    } = child.wait_with_output().unwrap();
+    (
+        status.code(),
+        String::from_utf8(stdout).unwrap(),
+        String::from_utf8(stderr).unwrap(),
+    )
+}
+
+#[test]
+fn render() {
+    let (code, dot, _) = fsm_cli(&["render", "turnstile.json"], "");
+    assert_eq!(code, Some(0));
+    assert!(dot.starts_with("digraph {\n"));
+    assert!(dot.contains("\"\" -> \"Locked\";"));
+    assert!(dot.contains("\"Locked\" [shape=doublecircle];"));
+    assert!(dot.contains("\"Broken\" -> \"Broken\" [label=\"Coin, Push, Kick\"];"));
+
+    let (code, mermaid, _) = fsm_cli(&["render", "three.fsm", "--mermaid"], "");
+    assert_eq!(code, Some(0));
+    assert!(mermaid.starts_with("stateDiagram-v2\n    state \"R0\" as s0\n"));
+    assert!(mermaid.contains("    [*] --> s0\n    s0 --> [*]\n"));
+    assert!(mermaid.contains("    s1 --> s2: '0'\n"));
+
+    // Names are escaped, as they may not be valid Mermaid ids
+    let json = r#"{"states": ["on: \"high\"", "end"], "inputs": ["a;b"], "start": "end",
+        "accept": [], "transitions": [{"from": "on: \"high\"", "input": "a;b", "to": "end"},
+        {"from": "end", "input": "a;b", "to": "end"}]}"#;
+    let (code, mermaid, _) = fsm_cli(&["render", "-", "--mermaid"], json);
+    assert_eq!(code, Some(0));
+    assert!(mermaid.contains("    state \"on#58; #quot;high#quot;\" as s0\n"));
+    assert!(mermaid.contains("    s0 --> s1: a#59;b\n"));
+}
+
+#[test]
+fn run() {
+    assert_eq!(
+        fsm_cli(&["run", "three.fsm"], "110\n"),
+        (Some(0), "accept R0\n".to_owned(), String::new())
+    );
+    assert_eq!(
+        fsm_cli(&["run", "turnstile.json", "-"], "Coin Push Coin"),
+        (Some(1), "reject Unlocked\n".to_owned(), String::new())
+    );
+
+    // Each line separately, stopping at inputs without a transition
+    let (code, output, _) = fsm_cli(&["run", "three.fsm", "--lines"], "110\n101\n12\n");
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        output,
+        "accept R0\nreject R2\nreject R1 (no transition for '2')\n"
+    );
+}
+
+#[test]
+fn analyze() {
+    let (code, output, _) = fsm_cli(&["analyze", "turnstile.json"], "");
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        output,
+        "states: 3\nunreachable: none\ndead: Broken\nequivalent: none\nminimized: 3\n"
+    );
+
+    let (code, output, _) = fsm_cli(&["analyze", "parity.fsm"], "");
+    assert_eq!(code, Some(1));
+    assert!(output.contains("equivalent: {Even, Again}\nminimized: 2\n"));
+
+    let (code, output, _) = fsm_cli(&["analyze", "-"], "start A\naccept A\nA _ -> A\nB _ -> A\n");
+    assert_eq!(code, Some(1));
+    assert!(output.starts_with("states: 2\nunreachable: B\n"));
+}
+
+#[test]
+fn diff() {
+    assert_eq!(
+        fsm_cli(
+            &["diff", "parity.fsm", "-"],
+            include_str!("machines/parity.fsm")
+        ),
+        (Some(0), "equivalent\n".to_owned(), String::new())
+    );
+    assert_eq!(
+        fsm_cli(&["diff", "three.fsm", "parity.fsm"], "").1,
+        "differ: \"101\" is accepted by `parity.fsm` and rejected by `three.fsm`\n"
+    );
+}
+
+#[test]
+fn errors() {
+    let (code, _, error) = fsm_cli(&["render", "missing.fsm"], "");
+    assert_eq!(code, Some(2));
+    assert!(error.starts_with("fsm-cli: failed to read `missing.fsm`"));
+
+    let (code, _, error) = fsm_cli(&["run", "-"], "start A\nA 'a' -> B\n");
+    assert_eq!(code, Some(2));
+    assert!(error.starts_with("fsm-cli: invalid machine `-`"));
+
+    let (code, _, error) = fsm_cli(&["diff", "three.fsm", "turnstile.json"], "");
+    assert_eq!(code, Some(2));
+    assert!(error.contains("can't be compared"));
+
+    // Deeply nested JSON is rejected, rather than overflowing the stack
+    let (code, _, error) = fsm_cli(&["render", "-"], &"{\"a\":[".repeat(100_000));
+    assert_eq!(code, Some(2));
+    assert!(error.contains("nested too deeply"));
+
+    assert_eq!(fsm_cli(&["frobnicate"], "").0, Some(2));
+}
+
+#[test]
+fn repl() {
+    let (code, output, _) = fsm_cli(
+        &["repl", "three.fsm"],
+        "break R2\nrun 1011\nundo\nstep 2\nstate\nquit\nstate\n",
+    );
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        output,
+        "\
+breakpoint R2
+R0 --'1'--> R1
+R1 --'0'--> R2
+stopped at breakpoint R2, with 2 inputs left
+back in R1
+error: the state `R1` has no transition for '2'
+R1
+  '0' -> R2
+  '1' -> R0
+"
+    );
+
+    let (code, output, _) = fsm_cli(&["repl", "turnstile.json"], "run Coin Kick\ntrace");
+    assert_eq!(code, Some(0));
+    assert!(output.ends_with("| 1 | Unlocked | Kick  | Broken   |\n"));
+}
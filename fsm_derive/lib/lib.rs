@@ -1,5 +1,9 @@
 //! This crate defines derive macros for the [`fsm`](https://github.com/tomBoddaert/fsm) library.
 //! This should not be used directly, rather through the reexports in [`fsm`](https://github.com/tomBoddaert/fsm) with the `derive` feature enabled.
+//!
+//! The macros refer to the library as `fsm`. If it is renamed, or reexported from another crate,
+//! the path to it can be given with `#[fsm(crate = path)]`: on the item for the derives,
+//! on the states enum for `fsm!`, and with the other attributes for `ValidatedTransform!` and `Typestate!`.
 
 #![warn(
     clippy::all,
@@ -16,76 +20,786 @@
     clippy::undocumented_unsafe_blocks
 )]
 
+mod machine;
+mod transitions;
+mod typestate;
+mod validate;
+
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{Data, DataEnum, DeriveInput, Ident};
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, punctuated::Punctuated, Attribute, Data, DataEnum, DeriveInput, Error, Expr,
+    Fields, Generics, Ident, LitStr, Meta, Path, Token, Type, Variant,
+};
 
-#[proc_macro_derive(AcceptStates, attributes(accept))]
-/// Auto-derives the `fsm::HasAcceptState` trait on an enum, given at least one variant is marked `#[accept]`.
+#[proc_macro_derive(AcceptStates, attributes(accept, trap, fsm))]
+/// Auto-derives the `fsm::AcceptStates` trait on an enum, given at least one variant is marked `#[accept]`.
+/// Variants marked `#[trap]` are reported as trap states.
 ///
-/// # Panics
-/// This will panic on any of the following conditions:
-/// - This derive is run on anything but an enum
-/// - The accept attribute is not just the path
-/// - The accept attribute is used on a variant with fields
-/// - The accept attribute is used more than once for one variant
+/// Variants with fields are matched whatever their fields are, unless the attribute has a guard,
+/// `#[accept(if = "expr")]`, which can use the fields by reference: struct fields by name,
+/// and tuple fields as `_0`, `_1` and so on.
+///
+/// On a struct, the accept states are given by a function, `#[accept(with = path)]`,
+/// taking `&Self` and returning a `bool`, and the trap states similarly by `#[trap(with = path)]`.
+///
+/// # Errors
+/// This will fail to compile on any of the following conditions:
+/// - This derive is run on a union
+/// - The accept or trap attribute on a variant is not just the path, or of the form `#[accept(if = "expr")]`
+/// - The accept or trap attribute is used on an enum, rather than its variants
+/// - The accept attribute is missing from a struct, or the accept or trap attribute on a struct
+///   is not of the form `#[accept(with = path)]`
+/// - The accept or trap attribute is used more than once for one item
+/// - A variant is marked both accept and trap
+/// - The fsm attribute is not of the form `#[fsm(crate = path)]`
 pub fn accept_state_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
 
-    let name = input.ident;
+    proc_macro::TokenStream::from(
+        accept_states(&input).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
 
-    let Data::Enum(data) = input.data else {
-        panic!("#[derive(AcceptStates)] is only defined for enums!");
-    };
+fn accept_states(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+
+    match &input.data {
+        Data::Enum(data) => {
+            for attribute_name in ["accept", "trap"] {
+                if let Some(attribute) = find_attribute(&input.attrs, attribute_name, "item")? {
+                    return Err(Error::new_spanned(
+                        attribute,
+                        format!("#[{attribute_name}] should be used on the variants of an enum"),
+                    ));
+                }
+            }
+
+            check_accept_and_trap(data)?;
+            let accepted_variants = get_marked_variants(data, "accept")?;
+            let trap_variants = get_marked_variants(data, "trap")?;
+
+            Ok(produce_impl(
+                &krate,
+                name,
+                &input.generics,
+                &accepted_variants,
+                &trap_variants,
+            ))
+        }
+        Data::Struct(_) => produce_struct_impl(&krate, name, &input.generics, &input.attrs),
+        Data::Union(data) => Err(Error::new_spanned(
+            data.union_token,
+            "#[derive(AcceptStates)] is only defined for enums and structs",
+        )),
+    }
+}
+
+#[proc_macro_derive(ClassifyStates, attributes(class_type, accept, trap, fsm))]
+/// Auto-derives the `fsm::ClassifyStates` and `fsm::AcceptStates` traits on an enum.
+///
+/// The class type is given with `#[class_type(Type)]` on the enum, and accepted variants are marked
+/// with `#[accept(class)]`, where `class` is an expression of the class type.
+/// Variants marked `#[trap]` are reported as trap states.
+///
+/// As with `AcceptStates`, variants with fields are matched whatever their fields are, unless the attribute
+/// has a guard, `#[accept(class, if = "expr")]`, which can use the fields by reference.
+///
+/// # Errors
+/// This will fail to compile on any of the following conditions:
+/// - This derive is run on anything but an enum
+/// - The class type attribute is missing or does not contain a type
+/// - The accept attribute is not of the form `#[accept(class)]` or `#[accept(class, if = "expr")]`
+/// - The trap attribute is not just the path, or of the form `#[trap(if = "expr")]`
+/// - The class type, accept or trap attribute is used more than once for one item
+/// - A variant is marked both accept and trap
+/// - The fsm attribute is not of the form `#[fsm(crate = path)]`
+pub fn classify_states_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    proc_macro::TokenStream::from(
+        classify_states(&input).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
+
+fn classify_states(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+
+    let class_type = find_attribute(&input.attrs, "class_type", "item")?
+        .ok_or_else(|| {
+            Error::new_spanned(
+                name,
+                "#[derive(ClassifyStates)] requires a #[class_type(Type)] attribute",
+            )
+        })?
+        .parse_args::<Type>()?;
+
+    let data = enum_data(input, "ClassifyStates")?;
+
+    check_accept_and_trap(data)?;
+    let classified_variants = get_classified_variants(data)?;
+    let trap_variants = get_marked_variants(data, "trap")?;
+
+    let (accepted_variants, classes): (Vec<Marked>, Vec<Expr>) =
+        classified_variants.into_iter().unzip();
+
+    let accept_impl = produce_impl(
+        &krate,
+        name,
+        &input.generics,
+        &accepted_variants,
+        &trap_variants,
+    );
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::ClassifyStates for #name #type_generics #where_clause {
+            type Class = #class_type;
+
+            #[allow(unused_variables)]
+            fn class(&self) -> Option<Self::Class> {
+                match self {
+                    #(#accepted_variants => Some(#classes),)*
+                    _ => None,
+                }
+            }
+        }
+
+        #accept_impl
+    })
+}
+
+fn get_classified_variants(data: &DataEnum) -> syn::Result<Vec<(Marked, Expr)>> {
+    let mut classified_variants = Vec::new();
+
+    for variant in &data.variants {
+        let Some(attribute) = find_attribute(&variant.attrs, "accept", "variant")? else {
+            continue;
+        };
+
+        // Read `#[accept(class)]` or `#[accept(class, if = "expr")]`
+        let (class, guard) = attribute.parse_args_with(|input: syn::parse::ParseStream<'_>| {
+            let class = input.parse::<Expr>()?;
+            if input.is_empty() {
+                return Ok((class, None));
+            }
+
+            input.parse::<Token![,]>()?;
+            input.parse::<Token![if]>()?;
+            input.parse::<Token![=]>()?;
+            let guard = input.parse::<LitStr>()?.parse::<Expr>()?;
+            Ok((class, Some(guard)))
+        })?;
+
+        let marked = if guard.is_some() {
+            Marked {
+                pattern: binding_pattern(variant),
+                guard,
+            }
+        } else {
+            Marked::from(&variant.ident)
+        };
+
+        classified_variants.push((marked, class));
+    }
+
+    Ok(classified_variants)
+}
+
+#[proc_macro_derive(StateOutput, attributes(output_type, output, fsm))]
+/// Auto-derives the `fsm::StateOutput` trait on an enum.
+///
+/// The output type is given with `#[output_type(Type)]` on the enum, and every variant is marked
+/// with `#[output(value)]`, where `value` is an expression of the output type.
+/// Literal outputs can also be given as `#[output = literal]`.
+///
+/// # Errors
+/// This will fail to compile on any of the following conditions:
+/// - This derive is run on anything but an enum
+/// - The output type attribute is missing or does not contain a type
+/// - The output attribute does not contain an expression
+/// - The output attribute is missing from a variant, or used more than once for one variant
+/// - The fsm attribute is not of the form `#[fsm(crate = path)]`
+pub fn state_output_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    proc_macro::TokenStream::from(
+        state_output(&input).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
+
+fn state_output(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+
+    let output_type = find_attribute(&input.attrs, "output_type", "item")?
+        .ok_or_else(|| {
+            Error::new_spanned(
+                name,
+                "#[derive(StateOutput)] requires an #[output_type(Type)] attribute",
+            )
+        })?
+        .parse_args::<Type>()?;
+
+    let data = enum_data(input, "StateOutput")?;
+
+    let mut variants = Vec::new();
+    let mut outputs = Vec::new();
+
+    for variant in &data.variants {
+        let attribute = find_attribute(&variant.attrs, "output", "variant")?.ok_or_else(|| {
+            Error::new_spanned(
+                variant,
+                format!(
+                    "variant {} is missing an #[output(value)] attribute",
+                    variant.ident
+                ),
+            )
+        })?;
+
+        let output = match &attribute.meta {
+            Meta::NameValue(name_value) => name_value.value.clone(),
+            Meta::List(_) => attribute.parse_args::<Expr>()?,
+            Meta::Path(_) => {
+                return Err(Error::new_spanned(
+                    attribute,
+                    "#[output] should be of the form #[output(value)] or #[output = literal]",
+                ))
+            }
+        };
+
+        outputs.push(output);
+        variants.push(&variant.ident);
+    }
 
-    let accepted_variants = get_accepted_variants(data);
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
 
-    proc_macro::TokenStream::from(produce_impl(&name, &accepted_variants))
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::StateOutput for #name #type_generics #where_clause {
+            type Output = #output_type;
+
+            fn output(&self) -> Self::Output {
+                match self {
+                    #(Self::#variants { .. } => #outputs,)*
+                }
+            }
+        }
+    })
 }
 
-fn get_accepted_variants(data: DataEnum) -> Vec<Ident> {
-    let mut accepted_variants = Vec::new();
+#[proc_macro_derive(Finite, attributes(fsm))]
+/// Auto-derives the `fsm::Finite` trait on an enum, listing its variants in order.
+///
+/// # Errors
+/// This will fail to compile on any of the following conditions:
+/// - This derive is run on anything but an enum
+/// - Any variant has fields
+/// - The fsm attribute is not of the form `#[fsm(crate = path)]`
+pub fn finite_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    proc_macro::TokenStream::from(finite(&input).unwrap_or_else(syn::Error::into_compile_error))
+}
+
+fn finite(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
 
-    for variant in data.variants {
-        for attribute in variant.attrs {
-            let path = attribute.meta.path();
+    let data = enum_data(input, "Finite")?;
 
-            if !path.is_ident("accept") {
-                continue;
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+            if variant.fields.is_empty() {
+                Ok(variant.ident.clone())
+            } else {
+                Err(Error::new_spanned(
+                    &variant.fields,
+                    "#[derive(Finite)] can only be used on enums whose variants have no fields",
+                ))
             }
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(produce_finite_impl(
+        &krate,
+        name,
+        &input.generics,
+        &variants,
+    ))
+}
+
+#[proc_macro_derive(AtomicState, attributes(fsm))]
+/// Auto-derives the `fsm::AtomicState` trait on an enum with `#[repr(u8)]`, using its discriminants.
+///
+/// Types implementing `fsm::Finite` already implement `fsm::AtomicState`, so this is only needed for enums that don't.
+///
+/// # Errors
+/// This will fail to compile on any of the following conditions:
+/// - This derive is run on anything but an enum
+/// - The enum does not have `#[repr(u8)]`
+/// - Any variant has fields
+/// - The fsm attribute is not of the form `#[fsm(crate = path)]`
+pub fn atomic_state_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    proc_macro::TokenStream::from(
+        atomic_state(&input).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
+
+fn atomic_state(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input.attrs)?;
+    let name = &input.ident;
+
+    let data = enum_data(input, "AtomicState")?;
+
+    let mut is_u8 = false;
+    for attribute in &input.attrs {
+        if attribute.path().is_ident("repr") {
+            let representations =
+                attribute.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+            is_u8 |= representations
+                .iter()
+                .any(|representation| representation.path().is_ident("u8"));
+        }
+    }
+    if !is_u8 {
+        return Err(Error::new_spanned(
+            name,
+            "#[derive(AtomicState)] requires #[repr(u8)], or use #[derive(Finite)] instead",
+        ));
+    }
+
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+            if variant.fields.is_empty() {
+                Ok(&variant.ident)
+            } else {
+                Err(Error::new_spanned(
+                    &variant.fields,
+                    "#[derive(AtomicState)] can only be used on enums whose variants have no fields",
+                ))
+            }
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #krate::AtomicState for #name {
+            fn into_u8(self) -> u8 {
+                self as u8
+            }
+
+            fn from_u8(value: u8) -> Self {
+                match value {
+                    #(value if value == Self::#variants as u8 => Self::#variants,)*
+                    value => ::core::panic!(
+                        "{} is not the discriminant of a variant of {}",
+                        value,
+                        ::core::stringify!(#name)
+                    ),
+                }
+            }
+        }
+    })
+}
+
+#[proc_macro]
+/// Declares a Finite State Machine: its states, inputs, transform function and transitions.
+///
+/// # Syntax
+/// ```text
+/// fsm! {
+///     [attributes] [visibility] enum <states> { [#[start]] [#[accept]] <state>, ... }
+///     [attributes] [visibility] enum <inputs> { <input>, ... }
+///     [attributes] [visibility] fn <name>;
+///
+///     (<states | _>, <inputs | _>) => <new state>,
+/// }
+/// ```
+///
+/// Each arm gives the transitions for some states, separated by `|`, and some inputs, where `_`
+/// matches every state or input. Each transition is given by the most specific arm covering it,
+/// the one with the fewest `_`.
+///
+/// This generates both enums, deriving `Clone`, `Copy`, `Debug`, `PartialEq` and `Eq`,
+/// with `fsm::Finite` for both, and `fsm::AcceptStates` and `Default` for the states.
+/// The accept states are those marked `#[accept]`, the trap states are worked out from
+/// the transitions, and the default is the state marked `#[start]`.
+/// The transitions are stored in a `TRANSITIONS` constant on the states, used by the transform function.
+///
+/// It is a compile error for a transition to be missing, or given twice by equally specific arms,
+/// for an arm to be covered entirely by more specific arms, or for a state to be unreachable from the start state.
+pub fn fsm(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let machine = syn::parse_macro_input!(input as machine::Machine);
+
+    proc_macro::TokenStream::from(
+        machine::expand(machine).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
+
+#[proc_macro]
+#[allow(non_snake_case)]
+/// Defines a transform function like `fsm::DefineTransform!`, and checks it at compile time.
+///
+/// The states and inputs must implement `fsm::Finite` and `Copy`, and the guards must be
+/// usable in `const` functions. Every state and input is run through the arms, checking that:
+/// - every arm matches some state and input, rather than being shadowed by earlier arms
+/// - every state can be reached from a start state, given with `#[validate(start = <pattern>)]`,
+///   or without start states, by a transition from another state
+///
+/// Results that are not paths, or variables bound to the current state, are assumed to lead
+/// to any state. Bare names starting with a capital, such as variants brought in with `use`,
+/// are taken as variants rather than variables.
+///
+/// Failed checks are reported as deprecation warnings by default, or `#[validate(warn)]`,
+/// or as errors with `#[validate(deny)]`.
+///
+/// # Syntax
+/// ```text
+/// ValidatedTransform!([#[validate(<warn | deny>, start = <pattern>)]] [attributes] <name>, <states>, <input domain>,
+///     (<current>, <input>) => <new>,
+/// );
+/// ```
+pub fn ValidatedTransform(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let transform = syn::parse_macro_input!(input as validate::Transform);
+
+    proc_macro::TokenStream::from(validate::expand(&transform))
+}
+
+#[proc_macro]
+#[allow(non_snake_case)]
+/// Defines a transform function like `fsm::DefineTransform!`, and a type for each state,
+/// for a machine whose transitions are checked at compile time.
+///
+/// This generates a module with the same name as the function, containing a zero-sized type for
+/// each state, named after its variant, and a `Machine<S>` type for a machine in the state `S`.
+/// Each transition given by an arm gives `Machine<S>` a method that consumes it and returns the machine
+/// in the new state, named after the input in snake case, so `(Off, PowerOn) => On` gives
+/// `Machine<Off>::power_on`. There are no methods for other transitions, so applying them fails to compile.
+///
+/// Only the start state, given with `#[typestate(start = <variant>)]`, can be created directly,
+/// with `Machine::new`, so other states are only reached through their transitions.
+/// Each `Machine<S>` can be converted into an `fsm::FSM` with the transform function, for storage,
+/// and back with `TryFrom`, or from a runtime state with `Machine::from_state`,
+/// which fail if the runtime machine is in another state.
+///
+/// The arms given methods are those that only name variants, as paths or as bare names starting
+/// with a capital, and have no guard. Other arms, with wildcards, bindings or guards, are only used by
+/// the transform function, so they should come last, such as `(state, _) => state`.
+///
+/// # Syntax
+/// ```text
+/// Typestate!([#[typestate(start = <variant>)]] [attributes] [visibility] <name>, <states>, <input domain>,
+///     (<current>, <input>) => <new>,
+/// );
+/// ```
+///
+/// It is a compile error for an arm naming variants to come after an arm with wildcards, bindings or guards,
+/// for two arms to give the same transition, for the start state to have a transition named `new`,
+/// for a transition to be named `enter` or `from_state`, or for two inputs from a state to give the same method,
+/// such as `PowerOff` and `Power_Off`.
+pub fn Typestate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let typestate = syn::parse_macro_input!(input as typestate::Typestate);
+
+    proc_macro::TokenStream::from(
+        typestate::expand(&typestate).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
+
+#[proc_macro_derive(Transitions, attributes(input_type, on, default_transition))]
+/// Auto-derives a transform function on an enum of states, from attributes on its variants.
+///
+/// The input type is given with `#[input_type(Type)]` on the enum, and the transitions from each variant
+/// are given with `#[on(<inputs> => <state>, ...)]`, where `<inputs>` is a pattern, in which bare names
+/// are variants of the input type, and `<state>` is a variant name, or `Self` to stay in the same state.
+/// Inputs without a transition go to the state given by `#[default_transition(<state>)]`,
+/// or `#[default_transition = "<state>"]`, on the variant, or otherwise on the enum.
+///
+/// This generates an associated `transform` function, for `fsm::FSM::new`, and a `TRANSITIONS`
+/// constant, listing the state, input and next state of each transition given by `#[on]`.
+///
+/// # Errors
+/// This will fail to compile on any of the following conditions:
+/// - This derive is run on anything but an enum
+/// - The input type attribute is missing or does not contain a type
+/// - The on attribute does not contain a list of transitions
+/// - The on attributes of a variant give a transition for the same input name, path or literal more than once
+/// - The default transition attribute does not contain `Self` or a variant name, or is used more than once for one item
+/// - The on or default transition attribute is used on a variant with fields
+pub fn transitions_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    proc_macro::TokenStream::from(
+        transitions::derive(&input).unwrap_or_else(syn::Error::into_compile_error),
+    )
+}
+
+/// Get the data of an enum, or an error naming the derive if the input is a struct or union.
+fn enum_data<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<&'a DataEnum> {
+    let message = format!("#[derive({derive})] is only defined for enums");
+
+    match &input.data {
+        Data::Enum(data) => Ok(data),
+        Data::Struct(data) => Err(Error::new_spanned(data.struct_token, message)),
+        Data::Union(data) => Err(Error::new_spanned(data.union_token, message)),
+    }
+}
+
+/// Find the attribute with a name, checking that it is used at most once on the item.
+fn find_attribute<'a>(
+    attributes: &'a [Attribute],
+    attribute_name: &str,
+    item: &str,
+) -> syn::Result<Option<&'a Attribute>> {
+    let mut found = attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident(attribute_name));
+
+    let attribute = found.next();
+    if let Some(repeated) = found.next() {
+        return Err(Error::new_spanned(
+            repeated,
+            format!("#[{attribute_name}] can only be used once per {item}"),
+        ));
+    }
+
+    Ok(attribute)
+}
+
+/// Get the path to the `fsm` crate, given by `#[fsm(crate = path)]`, or `fsm` by default.
+fn crate_path(attributes: &[Attribute]) -> syn::Result<Path> {
+    find_attribute(attributes, "fsm", "item")?.map_or_else(
+        || Ok(parse_quote!(fsm)),
+        |attribute| get_argument(attribute, "crate"),
+    )
+}
+
+/// Take the `#[fsm(crate = path)]` attribute out of the attributes given to a function-like macro,
+/// returning the path to the `fsm` crate, or `fsm` by default.
+fn take_crate_path(attributes: &mut Vec<Attribute>) -> syn::Result<Path> {
+    let krate = crate_path(attributes)?;
+    attributes.retain(|attribute| !attribute.path().is_ident("fsm"));
+    Ok(krate)
+}
+
+/// A variant marked by an attribute, as a pattern matching it, and a guard on its fields.
+struct Marked {
+    pattern: TokenStream,
+    guard: Option<Expr>,
+}
+
+impl From<&Ident> for Marked {
+    fn from(variant: &Ident) -> Self {
+        Self {
+            pattern: quote! { Self::#variant { .. } },
+            guard: None,
+        }
+    }
+}
+
+impl quote::ToTokens for Marked {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let pattern = &self.pattern;
+        let guard = self.guard.as_ref().map(|guard| quote! { if #guard });
+        tokens.extend(quote! { #pattern #guard });
+    }
+}
 
-            assert!(
-                attribute.meta.require_path_only().is_ok(),
-                "#[accept] should only contain a path"
-            );
+/// Read the `name = value` argument of an attribute, such as `#[accept(if = "expr")]`.
+fn get_argument<T: syn::parse::Parse>(attribute: &Attribute, name: &str) -> syn::Result<T> {
+    let mut value = None;
 
-            assert!(
-                variant.fields.is_empty(),
-                "#[accept] can only be used on variants with no fields."
-            );
+    attribute.parse_nested_meta(|meta| {
+        if !meta.path.is_ident(name) {
+            return Err(meta.error(format!("expected `{name} = ...`")));
+        }
+        if value.is_some() {
+            return Err(meta.error(format!("`{name}` can only be given once")));
+        }
+        value = Some(meta.value()?.parse()?);
+        Ok(())
+    })?;
 
-            assert!(
-                accepted_variants.last() != Some(&variant.ident),
-                "#[accept] can only be used once per variant"
-            );
+    value.ok_or_else(|| Error::new_spanned(attribute, format!("expected `{name} = ...`")))
+}
 
-            accepted_variants.push(variant.ident.clone());
+/// Get a pattern matching a variant that binds its fields, for a guard to use:
+/// struct fields by name, and tuple fields as `_0`, `_1` and so on.
+fn binding_pattern(variant: &Variant) -> TokenStream {
+    let ident = &variant.ident;
+    match &variant.fields {
+        Fields::Named(fields) => {
+            let fields = fields.named.iter().map(|field| &field.ident);
+            quote! { Self::#ident { #(#fields),* } }
         }
+        Fields::Unnamed(fields) => {
+            let fields = (0..fields.unnamed.len()).map(|index| format_ident!("_{index}"));
+            quote! { Self::#ident(#(#fields),*) }
+        }
+        Fields::Unit => quote! { Self::#ident },
+    }
+}
+
+/// Check that no variant is marked both `#[accept]` and `#[trap]`,
+/// as a trap state is one that can't reach an accept state.
+fn check_accept_and_trap(data: &DataEnum) -> syn::Result<()> {
+    for variant in &data.variants {
+        if find_attribute(&variant.attrs, "accept", "variant")?.is_none() {
+            continue;
+        }
+        if let Some(trap) = find_attribute(&variant.attrs, "trap", "variant")? {
+            return Err(Error::new_spanned(
+                trap,
+                format!(
+                    "variant {} can't be marked both #[accept] and #[trap], as a trap state can't reach an accept state",
+                    variant.ident
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn get_marked_variants(data: &DataEnum, attribute_name: &str) -> syn::Result<Vec<Marked>> {
+    let mut marked_variants = Vec::new();
+
+    for variant in &data.variants {
+        let Some(attribute) = find_attribute(&variant.attrs, attribute_name, "variant")? else {
+            continue;
+        };
+
+        let ident = &variant.ident;
+        let marked = match &attribute.meta {
+            Meta::Path(_) => Marked::from(ident),
+            Meta::List(_) => {
+                let guard = get_argument::<LitStr>(attribute, "if")?.parse::<Expr>()?;
+
+                Marked {
+                    pattern: binding_pattern(variant),
+                    guard: Some(guard),
+                }
+            }
+            Meta::NameValue(_) => {
+                return Err(Error::new_spanned(
+                    attribute,
+                    format!("#[{attribute_name}] should be of the form #[{attribute_name}] or #[{attribute_name}(if = \"expr\")]"),
+                ))
+            }
+        };
+
+        marked_variants.push(marked);
     }
 
-    accepted_variants
+    Ok(marked_variants)
 }
 
-fn produce_impl(name: &Ident, accepted_variants: &[Ident]) -> TokenStream {
+fn produce_impl(
+    krate: &Path,
+    name: &Ident,
+    generics: &Generics,
+    accepted_variants: &[Marked],
+    trap_variants: &[Marked],
+) -> TokenStream {
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let is_trap = if trap_variants.is_empty() {
+        TokenStream::new()
+    } else {
+        quote! {
+            #[allow(unused_variables)]
+            fn is_trap(&self) -> bool {
+                match self {
+                    #(#trap_variants => true,)*
+                    _ => false,
+                }
+            }
+        }
+    };
+
     quote! {
         #[automatically_derived]
-        impl fsm::AcceptStates for #name {
+        impl #impl_generics #krate::AcceptStates for #name #type_generics #where_clause {
+            #[allow(unused_variables)]
             fn is_accepted(&self) -> bool {
                 match self {
-                    #(Self::#accepted_variants => true,)*
+                    #(#accepted_variants => true,)*
                     _ => false,
                 }
             }
+
+            #is_trap
+        }
+    }
+}
+
+fn produce_struct_impl(
+    krate: &Path,
+    name: &Ident,
+    generics: &Generics,
+    attributes: &[Attribute],
+) -> syn::Result<TokenStream> {
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+
+    let is_accepted = find_attribute(attributes, "accept", "struct")?
+        .ok_or_else(|| {
+            Error::new_spanned(
+                name,
+                "#[derive(AcceptStates)] on a struct requires an #[accept(with = path)] attribute",
+            )
+        })
+        .and_then(|attribute| get_argument::<Path>(attribute, "with"))?;
+    let is_trap = find_attribute(attributes, "trap", "struct")?
+        .map(|attribute| get_argument::<Path>(attribute, "with"))
+        .transpose()?
+        .map(|is_trap| {
+            quote! {
+                fn is_trap(&self) -> bool {
+                    #is_trap(self)
+                }
+            }
+        });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::AcceptStates for #name #type_generics #where_clause {
+            fn is_accepted(&self) -> bool {
+                #is_accepted(self)
+            }
+
+            #is_trap
+        }
+    })
+}
+
+fn produce_finite_impl(
+    krate: &Path,
+    name: &Ident,
+    generics: &Generics,
+    variants: &[Ident],
+) -> TokenStream {
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let indices = 0..variants.len();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::Finite for #name #type_generics #where_clause {
+            const VALUES: &'static [Self] = &[#(Self::#variants),*];
+
+            fn index(&self) -> usize {
+                match *self {
+                    #(Self::#variants => #indices,)*
+                }
+            }
         }
     }
 }
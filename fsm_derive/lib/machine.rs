@@ -0,0 +1,444 @@
+//! The `fsm!` macro, which declares the states, inputs and transitions of a machine together.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Data, DeriveInput, Error, Fields, Ident, Token, Visibility,
+};
+
+use crate::{produce_finite_impl, produce_impl, Marked};
+
+/// One side of a transition: some variants, or `_` for every variant.
+enum Pattern {
+    Any,
+    Variants(Vec<Ident>),
+}
+
+impl Parse for Pattern {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        if input.peek(Token![_]) {
+            input.parse::<Token![_]>()?;
+            return Ok(Self::Any);
+        }
+
+        let variants = Punctuated::<Ident, Token![|]>::parse_separated_nonempty(input)?;
+        Ok(Self::Variants(variants.into_iter().collect()))
+    }
+}
+
+/// A transition arm, `(<states>, <inputs>) => <target>`.
+struct Arm {
+    span: Span,
+    states: Pattern,
+    inputs: Pattern,
+    target: Ident,
+}
+
+impl Parse for Arm {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let content;
+        let parentheses = parenthesized!(content in input);
+        let states = content.parse()?;
+        content.parse::<Token![,]>()?;
+        let inputs = content.parse()?;
+        input.parse::<Token![=>]>()?;
+        let target = input.parse()?;
+
+        Ok(Self {
+            span: parentheses.span.join(),
+            states,
+            inputs,
+            target,
+        })
+    }
+}
+
+impl Arm {
+    /// The number of wildcards in the arm, where arms with fewer are more specific.
+    const fn wildcards(&self) -> usize {
+        matches!(self.states, Pattern::Any) as usize + matches!(self.inputs, Pattern::Any) as usize
+    }
+}
+
+/// The body of an `fsm!` macro.
+pub struct Machine {
+    states: DeriveInput,
+    inputs: DeriveInput,
+    function_attributes: Vec<Attribute>,
+    function_visibility: Visibility,
+    function: Ident,
+    arms: Vec<Arm>,
+}
+
+impl Parse for Machine {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let states = input.parse()?;
+        let inputs = input.parse()?;
+
+        let function_attributes = input.call(Attribute::parse_outer)?;
+        let function_visibility = input.parse()?;
+        input.parse::<Token![fn]>()?;
+        let function = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        let arms = Punctuated::<Arm, Token![,]>::parse_terminated(input)?;
+
+        Ok(Self {
+            states,
+            inputs,
+            function_attributes,
+            function_visibility,
+            function,
+            arms: arms.into_iter().collect(),
+        })
+    }
+}
+
+/// Combine a list of errors into one, if there are any.
+fn combine(errors: Vec<Error>) -> syn::Result<()> {
+    errors
+        .into_iter()
+        .reduce(|mut combined, error| {
+            combined.combine(error);
+            combined
+        })
+        .map_or(Ok(()), Err)
+}
+
+/// Get the variants of an enum, checking that they have no fields or discriminants.
+fn variants(item: &DeriveInput, kind: &str) -> syn::Result<Vec<Ident>> {
+    let Data::Enum(data) = &item.data else {
+        return Err(Error::new_spanned(
+            &item.ident,
+            format!("the {kind} should be an enum"),
+        ));
+    };
+    if !item.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &item.generics,
+            format!("the {kind} can't be generic"),
+        ));
+    }
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &item.ident,
+            format!("the {kind} need at least one variant"),
+        ));
+    }
+
+    data.variants
+        .iter()
+        .map(|variant| {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(Error::new_spanned(
+                    &variant.fields,
+                    format!("the {kind} can't have fields"),
+                ));
+            }
+            if let Some((_, discriminant)) = &variant.discriminant {
+                return Err(Error::new_spanned(
+                    discriminant,
+                    format!("the {kind} can't have discriminants"),
+                ));
+            }
+            Ok(variant.ident.clone())
+        })
+        .collect()
+}
+
+/// Remove the `#[start]` and `#[accept]` attributes from the states,
+/// returning the start state and the accept states.
+fn take_markers(states: &mut DeriveInput) -> syn::Result<(Ident, Vec<Ident>)> {
+    let Data::Enum(data) = &mut states.data else {
+        unreachable!("the states are checked to be an enum");
+    };
+
+    let mut start: Option<Ident> = None;
+    let mut accepting = Vec::new();
+    let mut errors = Vec::new();
+
+    for variant in &mut data.variants {
+        let mut marked = false;
+        for attribute in &variant.attrs {
+            let is_start = attribute.path().is_ident("start");
+            if !is_start && !attribute.path().is_ident("accept") {
+                continue;
+            }
+
+            if let Err(error) = attribute.meta.require_path_only() {
+                errors.push(error);
+            } else if !is_start && marked {
+                errors.push(Error::new_spanned(
+                    attribute,
+                    "#[accept] can only be used once per variant",
+                ));
+            } else if !is_start {
+                marked = true;
+                accepting.push(variant.ident.clone());
+            } else if let Some(first) = &start {
+                errors.push(Error::new_spanned(
+                    attribute,
+                    format!("`{first}` is already the start state"),
+                ));
+            } else {
+                start = Some(variant.ident.clone());
+            }
+        }
+
+        variant.attrs.retain(|attribute| {
+            !attribute.path().is_ident("start") && !attribute.path().is_ident("accept")
+        });
+    }
+
+    combine(errors)?;
+    let start = start
+        .ok_or_else(|| Error::new_spanned(&states.ident, "one state should be marked #[start]"))?;
+    Ok((start, accepting))
+}
+
+/// Resolve a pattern to the indices of the variants it matches.
+fn resolve(pattern: &Pattern, variants: &[Ident], kind: &str) -> syn::Result<Vec<usize>> {
+    match pattern {
+        Pattern::Any => Ok((0..variants.len()).collect()),
+        Pattern::Variants(names) => names
+            .iter()
+            .map(|name| {
+                variants
+                    .iter()
+                    .position(|variant| variant == name)
+                    .ok_or_else(|| {
+                        Error::new_spanned(name, format!("there is no {kind} named `{name}`"))
+                    })
+            })
+            .collect(),
+    }
+}
+
+/// Work out the target of each transition, by state then input.
+///
+/// Each transition is given by the most specific arm that covers it.
+fn transitions(arms: &[Arm], states: &[Ident], inputs: &[Ident]) -> syn::Result<Vec<usize>> {
+    // The most specific arm covering each transition, with its target
+    let mut chosen: Vec<Option<(usize, usize)>> = vec![None; states.len() * inputs.len()];
+    let mut errors = Vec::new();
+
+    // Visit the most specific arms first, so that they take precedence
+    let mut order = (0..arms.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&arm| arms[arm].wildcards());
+
+    let mut used = vec![false; arms.len()];
+    for index in order {
+        let arm = &arms[index];
+        let resolved = (
+            resolve(&arm.states, states, "state"),
+            resolve(&arm.inputs, inputs, "input"),
+            resolve(
+                &Pattern::Variants(vec![arm.target.clone()]),
+                states,
+                "state",
+            ),
+        );
+        let (arm_states, arm_inputs, target) = match resolved {
+            (Ok(arm_states), Ok(arm_inputs), Ok(target)) => (arm_states, arm_inputs, target[0]),
+            (states, inputs, target) => {
+                errors.extend(
+                    [states.err(), inputs.err(), target.err()]
+                        .into_iter()
+                        .flatten(),
+                );
+                used[index] = true;
+                continue;
+            }
+        };
+
+        let mut duplicate = None;
+        for &state in &arm_states {
+            for &input in &arm_inputs {
+                match chosen[state * inputs.len() + input] {
+                    // A more specific arm covers it
+                    Some((other, _)) if arms[other].wildcards() < arm.wildcards() => {}
+                    Some(_) => {
+                        duplicate.get_or_insert((state, input));
+                    }
+                    None => {
+                        chosen[state * inputs.len() + input] = Some((index, target));
+                        used[index] = true;
+                    }
+                }
+            }
+        }
+
+        if let Some((state, input)) = duplicate {
+            used[index] = true;
+            errors.push(Error::new(
+                arm.span,
+                format!(
+                    "the transition from `{}` for `{}` is already given by another arm",
+                    states[state], inputs[input]
+                ),
+            ));
+        }
+    }
+
+    for (arm, used) in arms.iter().zip(used) {
+        if !used {
+            errors.push(Error::new(
+                arm.span,
+                "this arm is never used, as more specific arms give all of its transitions",
+            ));
+        }
+    }
+
+    for (state, name) in states.iter().enumerate() {
+        let missing = (0..inputs.len())
+            .filter(|&input| chosen[state * inputs.len() + input].is_none())
+            .map(|input| format!("`{}`", inputs[input]))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            errors.push(Error::new_spanned(
+                name,
+                format!(
+                    "there is no transition from `{name}` for {}",
+                    missing.join(", ")
+                ),
+            ));
+        }
+    }
+
+    combine(errors)?;
+    Ok(chosen
+        .into_iter()
+        .map(|chosen| chosen.map_or(0, |(_, target)| target))
+        .collect())
+}
+
+/// Find the states that can reach one of the `targets`, following transitions forwards
+/// or, if `reverse` is set, backwards.
+fn closure(table: &[usize], inputs: usize, targets: &[usize], reverse: bool) -> Vec<bool> {
+    let states = table.len() / inputs;
+    let mut reached = vec![false; states];
+    for &target in targets {
+        reached[target] = true;
+    }
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for state in 0..states {
+            for &next in &table[state * inputs..(state + 1) * inputs] {
+                let (from, to) = if reverse {
+                    (next, state)
+                } else {
+                    (state, next)
+                };
+                if reached[from] && !reached[to] {
+                    reached[to] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+    reached
+}
+
+/// Expand an `fsm!` macro.
+pub fn expand(machine: Machine) -> syn::Result<TokenStream> {
+    let Machine {
+        mut states,
+        inputs,
+        function_attributes,
+        function_visibility,
+        function,
+        arms,
+    } = machine;
+
+    let krate = crate::take_crate_path(&mut states.attrs)?;
+    let state_names = variants(&states, "states")?;
+    let input_names = variants(&inputs, "inputs")?;
+    let (start, accepting) = take_markers(&mut states)?;
+    let table = transitions(&arms, &state_names, &input_names)?;
+
+    let index = |name: &Ident| {
+        state_names
+            .iter()
+            .position(|state| state == name)
+            .unwrap_or_default()
+    };
+    let reachable = closure(&table, input_names.len(), &[index(&start)], false);
+    combine(
+        state_names
+            .iter()
+            .zip(reachable)
+            .filter(|(_, reachable)| !reachable)
+            .map(|(name, _)| {
+                Error::new_spanned(
+                    name,
+                    format!("the state `{name}` can't be reached from the start state `{start}`"),
+                )
+            })
+            .collect(),
+    )?;
+
+    let live = closure(
+        &table,
+        input_names.len(),
+        &accepting.iter().map(index).collect::<Vec<_>>(),
+        true,
+    );
+    let traps = state_names
+        .iter()
+        .zip(live)
+        .filter(|(_, live)| !live)
+        .map(|(name, _)| Marked::from(name))
+        .collect::<Vec<_>>();
+
+    let states_name = &states.ident;
+    let inputs_name = &inputs.ident;
+    let visibility = &states.vis;
+    let state_count = state_names.len();
+    let input_count = input_names.len();
+    let rows = table.chunks(input_count).map(|row| {
+        let targets = row.iter().map(|&target| &state_names[target]);
+        quote! { [#(Self::#targets),*] }
+    });
+    let states_finite_impl =
+        produce_finite_impl(&krate, states_name, &states.generics, &state_names);
+    let inputs_finite_impl =
+        produce_finite_impl(&krate, inputs_name, &inputs.generics, &input_names);
+    let accepting = accepting.iter().map(Marked::from).collect::<Vec<_>>();
+    let accept_impl = produce_impl(&krate, states_name, &states.generics, &accepting, &traps);
+
+    Ok(quote! {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #states
+
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #inputs
+
+        impl #states_name {
+            /// The next state for each state and input, indexed by state then input.
+            #visibility const TRANSITIONS: [[Self; #input_count]; #state_count] = [#(#rows),*];
+        }
+
+        impl ::core::default::Default for #states_name {
+            fn default() -> Self {
+                Self::#start
+            }
+        }
+
+        #states_finite_impl
+
+        #inputs_finite_impl
+
+        #accept_impl
+
+        #(#function_attributes)*
+        #[must_use]
+        #function_visibility const fn #function(state: #states_name, input: #inputs_name) -> #states_name {
+            #states_name::TRANSITIONS[state as usize][input as usize]
+        }
+    })
+}
@@ -0,0 +1,198 @@
+//! The `Transitions` derive, which reads a transform function from attributes on the states.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, DeriveInput, Error, Expr, ExprLit, Ident, Lit, Meta, Pat, PatIdent, Token, Type,
+};
+
+use crate::{enum_data, find_attribute};
+
+/// A transition in an `#[on(...)]` attribute, `<inputs> => <state>`.
+struct On {
+    inputs: Pat,
+    target: Ident,
+}
+
+impl Parse for On {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let inputs = Pat::parse_multi_with_leading_vert(input)?;
+        input.parse::<Token![=>]>()?;
+        let target = input.call(Ident::parse_any)?;
+
+        Ok(Self { inputs, target })
+    }
+}
+
+/// Returns [`true`] if an identifier pattern is a bare name, rather than a binding.
+const fn is_name(binding: &PatIdent) -> bool {
+    binding.subpat.is_none() && binding.by_ref.is_none() && binding.mutability.is_none()
+}
+
+/// Qualify bare input names with the input type, so `Next | Prev` matches `Inputs::Next | Inputs::Prev`.
+fn qualify(pattern: &Pat, input_type: &Type) -> TokenStream {
+    match pattern {
+        Pat::Ident(binding) if is_name(binding) => {
+            let name = &binding.ident;
+            match input_type {
+                Type::Path(path) if path.qself.is_none() => quote! { #path::#name },
+                _ => quote! { <#input_type>::#name },
+            }
+        }
+        Pat::Or(or) => {
+            let cases = or.cases.iter().map(|case| qualify(case, input_type));
+            quote! { #(#cases)|* }
+        }
+        pattern => quote! { #pattern },
+    }
+}
+
+/// Get the inputs matched by a pattern, if it only matches names, paths and literals.
+fn values(pattern: &Pat, input_type: &Type) -> Option<Vec<TokenStream>> {
+    match pattern {
+        Pat::Ident(binding) if is_name(binding) => Some(vec![qualify(pattern, input_type)]),
+        Pat::Path(_) | Pat::Lit(_) => Some(vec![qualify(pattern, input_type)]),
+        Pat::Or(or) => or
+            .cases
+            .iter()
+            .map(|case| values(case, input_type))
+            .collect::<Option<Vec<_>>>()
+            .map(|values| values.into_iter().flatten().collect()),
+        _ => None,
+    }
+}
+
+/// Get the state a `Self` or variant name leads to, from `state`.
+fn target(target: &Ident) -> TokenStream {
+    if target == "Self" {
+        quote! { state }
+    } else {
+        quote! { Self::#target }
+    }
+}
+
+/// Read a `#[default_transition(<state>)]` or `#[default_transition = "<state>"]` attribute,
+/// if there is one.
+///
+/// Attribute values must be literals, so the state is given as a string in the second form.
+fn default_transition(attributes: &[Attribute], item: &str) -> syn::Result<Option<Ident>> {
+    find_attribute(attributes, "default_transition", item)?
+        .map(|attribute| match &attribute.meta {
+            Meta::NameValue(name_value) => {
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Str(state),
+                    ..
+                }) = &name_value.value
+                else {
+                    return Err(Error::new_spanned(
+                        &name_value.value,
+                        "expected `Self` or a variant name, as a string",
+                    ));
+                };
+                state.parse_with(Ident::parse_any)
+            }
+            _ => attribute.parse_args_with(Ident::parse_any),
+        })
+        .transpose()
+}
+
+/// Derive `Transitions`.
+pub fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let visibility = &input.vis;
+
+    let input_type = find_attribute(&input.attrs, "input_type", "item")?
+        .ok_or_else(|| {
+            Error::new_spanned(
+                name,
+                "#[derive(Transitions)] requires an #[input_type(Type)] attribute",
+            )
+        })?
+        .parse_args::<Type>()?;
+    let default = default_transition(&input.attrs, "item")?;
+
+    let data = enum_data(input, "Transitions")?;
+
+    let mut arms = Vec::new();
+    let mut listed = Vec::new();
+    for variant in &data.variants {
+        let from = &variant.ident;
+        let mut ons = Vec::new();
+        for attribute in &variant.attrs {
+            if attribute.path().is_ident("on") {
+                ons.extend(
+                    attribute.parse_args_with(Punctuated::<On, Token![,]>::parse_terminated)?,
+                );
+            }
+        }
+        let variant_default = default_transition(&variant.attrs, "variant")?;
+
+        if (!ons.is_empty() || variant_default.is_some()) && !variant.fields.is_empty() {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "#[on] and #[default_transition] can only be used on variants with no fields",
+            ));
+        }
+
+        // The inputs listed from this variant so far, to find transitions given twice
+        let mut seen = Vec::new();
+        for On { inputs, target } in ons {
+            let to = self::target(&target);
+            let pattern = qualify(&inputs, &input_type);
+            arms.push(quote! { (state @ Self::#from, #pattern) => #to });
+
+            let to = if target == "Self" {
+                quote! { Self::#from }
+            } else {
+                quote! { Self::#target }
+            };
+            for input in values(&inputs, &input_type).unwrap_or_default() {
+                let key = input.to_string();
+                if seen.contains(&key) {
+                    return Err(Error::new(
+                        inputs.span(),
+                        format!(
+                            "the transition from `{from}` for `{}` is already given",
+                            key.replace(' ', "")
+                        ),
+                    ));
+                }
+                seen.push(key);
+                listed.push(quote! { (Self::#from, #input, #to) });
+            }
+        }
+
+        if let Some(target) = variant_default {
+            let to = self::target(&target);
+            arms.push(quote! { (state @ Self::#from, _) => #to });
+        }
+    }
+
+    if let Some(target) = default {
+        let to = self::target(&target);
+        arms.push(quote! { (state, _) => #to });
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #name {
+            /// The transitions given by `#[on]` attributes, as the state, the input and the next state.
+            ///
+            /// Transitions for patterns other than names, paths and literals, and default transitions,
+            /// are not listed.
+            #visibility const TRANSITIONS: &'static [(Self, #input_type, Self)] = &[#(#listed),*];
+
+            #[allow(unused_variables, clippy::missing_const_for_fn)]
+            /// The transform function given by the `#[on]` and `#[default_transition]` attributes.
+            #visibility fn transform(state: Self, input: #input_type) -> Self {
+                match (state, input) {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,450 @@
+//! The `Typestate!` macro, a [`DefineTransform!`] that also gives a type for each state,
+//! so that invalid transitions fail to compile.
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Error, Expr, Ident, Pat, Path, Token, Visibility,
+};
+
+use crate::validate::Arm;
+
+/// The arguments to a `Typestate!` macro.
+pub struct Typestate {
+    attributes: Vec<Attribute>,
+    krate: Path,
+    start: Option<Variant>,
+    visibility: Visibility,
+    name: Ident,
+    states: Path,
+    domain: Path,
+    arms: Vec<Arm>,
+}
+
+impl Parse for Typestate {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut attributes = input.call(Attribute::parse_outer)?;
+        let krate = crate::take_crate_path(&mut attributes)?;
+
+        // Take the start state out of the `#[typestate(start = <variant>)]` attribute
+        let start = crate::find_attribute(&attributes, "typestate", "macro")?
+            .map(|attribute| {
+                let path = crate::get_argument::<Path>(attribute, "start")?;
+                let name = path
+                    .segments
+                    .last()
+                    .ok_or_else(|| Error::new_spanned(&path, "expected a variant"))?
+                    .ident
+                    .clone();
+                Ok::<_, Error>(Variant {
+                    name,
+                    path: path.to_token_stream(),
+                })
+            })
+            .transpose()?;
+        attributes.retain(|attribute| !attribute.path().is_ident("typestate"));
+
+        let visibility = input.parse()?;
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let states = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let domain = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let arms = Punctuated::<Arm, Token![,]>::parse_terminated(input)?;
+
+        Ok(Self {
+            attributes,
+            krate,
+            start,
+            visibility,
+            name,
+            states,
+            domain,
+            arms: arms.into_iter().collect(),
+        })
+    }
+}
+
+/// A variant named in an arm, as its name and the tokens it was written with.
+#[derive(Clone)]
+struct Variant {
+    name: Ident,
+    path: TokenStream,
+}
+
+/// Get the variant a pattern names, if it is a path, or a bare name starting with a capital.
+fn variant(pattern: &Pat) -> Option<Variant> {
+    match pattern {
+        Pat::Path(path) if path.qself.is_none() => Some(Variant {
+            name: path.path.segments.last()?.ident.clone(),
+            path: path.to_token_stream(),
+        }),
+        Pat::Ident(binding)
+            if binding.subpat.is_none()
+                && binding.by_ref.is_none()
+                && binding.mutability.is_none()
+                && binding.ident.to_string().starts_with(char::is_uppercase) =>
+        {
+            Some(Variant {
+                name: binding.ident.clone(),
+                path: binding.ident.to_token_stream(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Get the variants a pattern names, if it only names variants, separated by `|`.
+fn variants(pattern: &Pat) -> Option<Vec<Variant>> {
+    match pattern {
+        Pat::Or(or) => or.cases.iter().map(variant).collect(),
+        pattern => variant(pattern).map(|variant| vec![variant]),
+    }
+}
+
+/// Get the transitions an arm gives, as the current states, inputs and the new state,
+/// if it only names variants and has no guard.
+fn transitions(arm: &Arm) -> Option<(Vec<Variant>, Vec<Variant>, Variant)> {
+    let Pat::Tuple(tuple) = &arm.pattern else {
+        return None;
+    };
+    if arm.guard.is_some() || tuple.elems.len() != 2 {
+        return None;
+    }
+    let Expr::Path(result) = &arm.result else {
+        return None;
+    };
+    if result.qself.is_some() {
+        return None;
+    }
+
+    let target = Variant {
+        name: result.path.segments.last()?.ident.clone(),
+        path: result.to_token_stream(),
+    };
+    if !target.name.to_string().starts_with(char::is_uppercase) {
+        return None;
+    }
+
+    Some((
+        variants(&tuple.elems[0])?,
+        variants(&tuple.elems[1])?,
+        target,
+    ))
+}
+
+/// Convert a variant name to a method name, so `PowerOff` becomes `power_off`.
+fn method_name(input: &Ident) -> Ident {
+    let characters = input.to_string().chars().collect::<Vec<_>>();
+    let mut name = String::new();
+
+    for (index, &character) in characters.iter().enumerate() {
+        if character.is_uppercase() {
+            let previous = index.checked_sub(1).map(|index| characters[index]);
+            let next = characters.get(index + 1);
+            let starts_word = previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+            if starts_word {
+                name.push('_');
+            }
+            name.extend(character.to_lowercase());
+        } else {
+            name.push(character);
+        }
+    }
+
+    // Keywords, such as `move`, are used as raw identifiers
+    if syn::parse_str::<Ident>(&name).is_ok() {
+        Ident::new(&name, input.span())
+    } else {
+        Ident::new_raw(&name, input.span())
+    }
+}
+
+/// A state of the machine, with the transitions from it.
+struct State {
+    variant: Variant,
+    transitions: Vec<(Variant, Variant)>,
+}
+
+/// Get the states, starting with the start state, then in the order they are first named,
+/// with their transitions.
+fn states(arms: &[Arm], start: Option<&Variant>) -> syn::Result<Vec<State>> {
+    let mut states: Vec<State> = Vec::new();
+    let mut untyped: Option<&Arm> = None;
+
+    let index = |states: &mut Vec<State>, variant: &Variant| {
+        states
+            .iter()
+            .position(|state| state.variant.name == variant.name)
+            .unwrap_or_else(|| {
+                states.push(State {
+                    variant: variant.clone(),
+                    transitions: Vec::new(),
+                });
+                states.len() - 1
+            })
+    };
+
+    if let Some(start) = start {
+        index(&mut states, start);
+    }
+
+    for arm in arms {
+        let Some((from, inputs, target)) = transitions(arm) else {
+            untyped.get_or_insert(arm);
+            continue;
+        };
+
+        if let Some(untyped) = untyped {
+            let mut error = Error::new(
+                arm.pattern.span(),
+                "this arm can't be given a method, as it comes after an arm with wildcards, bindings or guards",
+            );
+            error.combine(Error::new(
+                untyped.pattern.span(),
+                "arms with wildcards, bindings or guards should come after the arms naming variants",
+            ));
+            return Err(error);
+        }
+
+        index(&mut states, &target);
+        for state in &from {
+            let state = index(&mut states, state);
+            for input in &inputs {
+                let transitions = &mut states[state].transitions;
+                if transitions
+                    .iter()
+                    .any(|(other, _)| other.name == input.name)
+                {
+                    return Err(Error::new(
+                        arm.pattern.span(),
+                        format!(
+                            "the transition from `{}` for `{}` is already given by an earlier arm",
+                            states[state].variant.name, input.name
+                        ),
+                    ));
+                }
+                transitions.push((input.clone(), target.clone()));
+            }
+        }
+    }
+
+    Ok(states)
+}
+
+/// Get the type for a state, with a method for each transition from it, to go in the generated module.
+fn state_type(state: &State) -> TokenStream {
+    let state_name = &state.variant.name;
+    let doc = format!("The `{state_name}` state, as a type.");
+    let methods = state.transitions.iter().map(|(input, target)| {
+        let method = method_name(&input.name);
+        let target_name = &target.name;
+        let doc = format!("Apply `{}`, moving to `{target_name}`.", input.name);
+        quote! {
+            #[must_use]
+            #[doc = #doc]
+            pub const fn #method(self) -> Machine<#target_name> {
+                Machine::enter()
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct #state_name;
+
+        impl Machine<#state_name> {
+            #(#methods)*
+        }
+    }
+}
+
+/// The methods every `Machine` has, which transitions can't be named after.
+const RESERVED: [&str; 2] = ["enter", "from_state"];
+
+/// Check that the method for each transition is a unique name in its state,
+/// which doesn't clash with the methods every `Machine` has, or the constructor of the start state.
+fn check_method_names(states: &[State], start: Option<&Variant>) -> syn::Result<()> {
+    for state in states {
+        let state_name = &state.variant.name;
+        let is_start = start.is_some_and(|start| start.name == *state_name);
+        let mut methods: Vec<(Ident, &Variant)> = Vec::new();
+
+        for (input, _) in &state.transitions {
+            let method = method_name(&input.name);
+            let unraw = method.unraw().to_string();
+
+            if RESERVED.contains(&unraw.as_str()) {
+                return Err(Error::new_spanned(
+                    &input.path,
+                    format!("the transition from `{state_name}` for `{}` can't be given a method named `{unraw}`, as every `Machine` has that method", input.name),
+                ));
+            }
+            if is_start && unraw == "new" {
+                return Err(Error::new_spanned(
+                    &input.path,
+                    format!("the start state `{state_name}` can't have a transition named `new`, as that is its constructor"),
+                ));
+            }
+            if let Some((_, other)) = methods.iter().find(|(other, _)| *other == method) {
+                return Err(Error::new_spanned(
+                    &input.path,
+                    format!(
+                        "the transitions from `{state_name}` for `{}` and `{}` would both be given the method `{unraw}`",
+                        other.name, input.name
+                    ),
+                ));
+            }
+            methods.push((method, input));
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the constructor for the start state, to go in the generated module.
+fn constructor(start: &Variant) -> TokenStream {
+    let start_name = &start.name;
+    let doc = format!("Create a machine in the start state, `{start_name}`.");
+    quote! {
+        impl Machine<#start_name> {
+            #[must_use]
+            #[doc = #doc]
+            pub const fn new() -> Self {
+                Self::enter()
+            }
+        }
+    }
+}
+
+/// Expand a `Typestate!` macro.
+pub fn expand(typestate: &Typestate) -> syn::Result<TokenStream> {
+    let Typestate {
+        attributes,
+        krate,
+        start,
+        visibility,
+        name,
+        states: states_type,
+        domain,
+        arms,
+    } = typestate;
+
+    let states = self::states(arms, start.as_ref())?;
+    check_method_names(&states, start.as_ref())?;
+
+    let patterns = arms.iter().map(|arm| &arm.pattern);
+    let guards = arms
+        .iter()
+        .map(|arm| arm.guard.as_ref().map(|guard| quote! { if #guard }));
+    let results = arms.iter().map(|arm| &arm.result);
+
+    let state_types = states.iter().map(state_type);
+
+    let constructor = start.as_ref().map(constructor);
+
+    let state_impls = states.iter().map(|state| {
+        let state_name = &state.variant.name;
+        let state_path = &state.variant.path;
+
+        quote! {
+            #[automatically_derived]
+            impl #name::Machine<#name::#state_name> {
+                /// The state of the machine, as a value of the runtime states.
+                pub const STATE: #states_type = #state_path;
+            }
+
+            #[automatically_derived]
+            impl #name::StateType<#states_type> for #name::#state_name {
+                fn is(state: &#states_type) -> bool {
+                    ::core::matches!(state, #state_path)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::core::convert::From<#name::Machine<#name::#state_name>> for #krate::FSM<#domain, #states_type> {
+                fn from(_: #name::Machine<#name::#state_name>) -> Self {
+                    Self::new(#state_path, #name)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::core::convert::TryFrom<#krate::FSM<#domain, #states_type>> for #name::Machine<#name::#state_name> {
+                type Error = #krate::FSM<#domain, #states_type>;
+
+                fn try_from(machine: #krate::FSM<#domain, #states_type>) -> ::core::result::Result<Self, Self::Error> {
+                    match Self::from_state(machine.state()) {
+                        ::core::option::Option::Some(typed) => ::core::result::Result::Ok(typed),
+                        ::core::option::Option::None => ::core::result::Result::Err(machine),
+                    }
+                }
+            }
+        }
+    });
+
+    let module_doc = format!(
+        "The states of [`{name}`](fn@{name}) as types, for a machine whose transitions are checked at compile time."
+    );
+
+    Ok(quote! {
+        #(#attributes)*
+        #[allow(clippy::missing_const_for_fn)]
+        #visibility fn #name(state: #states_type, input: #domain) -> #states_type {
+            match (state, input) {
+                #(#patterns #guards => #results),*
+            }
+        }
+
+        #[doc = #module_doc]
+        #visibility mod #name {
+            /// A machine in the state `S`, with a method for each transition from `S`.
+            ///
+            /// This can only be created in the start state, or from a runtime state,
+            /// so other states are only reached through their transitions.
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            pub struct Machine<S>(::core::marker::PhantomData<S>);
+
+            /// A state, as a type, standing for one of the runtime `States`.
+            pub trait StateType<States> {
+                /// Returns `true` if `state` is the state this type stands for.
+                fn is(state: &States) -> bool;
+            }
+
+            impl<S> Machine<S> {
+                /// Enter the state `S`, which is only done by transitions and checked conversions.
+                const fn enter() -> Self {
+                    Self(::core::marker::PhantomData)
+                }
+
+                #[must_use]
+                /// Create a machine in the state `S`, if it is the runtime `state`.
+                pub fn from_state<States>(state: &States) -> ::core::option::Option<Self>
+                where
+                    S: StateType<States>,
+                {
+                    if S::is(state) {
+                        ::core::option::Option::Some(Self::enter())
+                    } else {
+                        ::core::option::Option::None
+                    }
+                }
+            }
+
+            #constructor
+
+            #(#state_types)*
+        }
+
+        #(#state_impls)*
+    })
+}
@@ -0,0 +1,357 @@
+//! The `ValidatedTransform!` macro, a [`DefineTransform!`] that checks its arms at compile time.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Expr, Ident, Pat, Path, Token,
+};
+
+/// How failed checks are reported.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Warn,
+    Deny,
+}
+
+/// An arm of the transform function.
+pub struct Arm {
+    pub pattern: Pat,
+    pub guard: Option<Expr>,
+    pub result: Expr,
+}
+
+impl Parse for Arm {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let pattern = Pat::parse_multi_with_leading_vert(input)?;
+        let guard = if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        input.parse::<Token![=>]>()?;
+        let result = input.parse()?;
+
+        Ok(Self {
+            pattern,
+            guard,
+            result,
+        })
+    }
+}
+
+/// The arguments to a `ValidatedTransform!` macro.
+pub struct Transform {
+    attributes: Vec<Attribute>,
+    krate: Path,
+    level: Level,
+    start: Option<Pat>,
+    name: Ident,
+    states: Path,
+    domain: Path,
+    arms: Vec<Arm>,
+}
+
+impl Parse for Transform {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut attributes = input.call(Attribute::parse_outer)?;
+        let krate = crate::take_crate_path(&mut attributes)?;
+        let mut level = Level::Warn;
+        let mut start = None;
+
+        // Take the settings out of the `#[validate(...)]` attributes
+        let mut settings = Vec::new();
+        attributes.retain(|attribute| {
+            let is_setting = attribute.path().is_ident("validate");
+            if is_setting {
+                settings.push(attribute.clone());
+            }
+            !is_setting
+        });
+        for setting in settings {
+            setting.parse_nested_meta(|meta| {
+                if meta.path.is_ident("warn") {
+                    level = Level::Warn;
+                } else if meta.path.is_ident("deny") {
+                    level = Level::Deny;
+                } else if meta.path.is_ident("start") {
+                    start = Some(Pat::parse_multi_with_leading_vert(meta.value()?)?);
+                } else {
+                    return Err(meta.error("expected `warn`, `deny` or `start = <pattern>`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let states = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let domain = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let arms = Punctuated::<Arm, Token![,]>::parse_terminated(input)?;
+
+        Ok(Self {
+            attributes,
+            krate,
+            level,
+            start,
+            name,
+            states,
+            domain,
+            arms: arms.into_iter().collect(),
+        })
+    }
+}
+
+/// Returns [`true`] if a bare name is a variable, rather than a variant.
+///
+/// A bare name in a pattern may be a variant brought in with `use`, which can't be told apart
+/// from a binding without name resolution, so variants are assumed to start with a capital.
+fn is_variable(name: &Ident) -> bool {
+    !name.to_string().starts_with(char::is_uppercase)
+}
+
+/// Returns [`true`] if a pattern binds a variable named `name`.
+fn binds(pattern: &Pat, name: &Ident) -> bool {
+    match pattern {
+        Pat::Ident(binding) => {
+            (binding.ident == *name && is_variable(name))
+                || binding
+                    .subpat
+                    .as_ref()
+                    .is_some_and(|(_, pattern)| binds(pattern, name))
+        }
+        Pat::Or(or) => or.cases.iter().any(|pattern| binds(pattern, name)),
+        Pat::Paren(paren) => binds(&paren.pat, name),
+        Pat::Reference(reference) => binds(&reference.pat, name),
+        Pat::Slice(slice) => slice.elems.iter().any(|pattern| binds(pattern, name)),
+        Pat::Struct(structure) => structure.fields.iter().any(|field| binds(&field.pat, name)),
+        Pat::Tuple(tuple) => tuple.elems.iter().any(|pattern| binds(pattern, name)),
+        Pat::TupleStruct(tuple) => tuple.elems.iter().any(|pattern| binds(pattern, name)),
+        Pat::Type(typed) => binds(&typed.pat, name),
+        _ => false,
+    }
+}
+
+/// Get an expression for whether an arm, taken from the state at index `from`,
+/// can lead to the different state at index `to`.
+fn leads_to(arm: &Arm) -> TokenStream {
+    let Expr::Path(result) = &arm.result else {
+        // The result can't be worked out without running it, so assume it can lead anywhere
+        return quote! { from != to };
+    };
+
+    if let Some(name) = result.path.get_ident().filter(|name| is_variable(name)) {
+        // Staying in the current state doesn't lead anywhere else
+        if let Pat::Tuple(tuple) = &arm.pattern {
+            if tuple.elems.first().is_some_and(|first| binds(first, name)) {
+                return quote! { false };
+            }
+        }
+        if binds(&arm.pattern, name) {
+            return quote! { from != to };
+        }
+    }
+
+    let path = &result.path;
+    quote! { from != to && matches!(STATES[to], #path) }
+}
+
+/// Get the checks for the arms and states, which use the functions generated by [`expand`].
+fn checks(level: Level, arms: &[Arm], states: &Path) -> TokenStream {
+    // Report each arm that never matches at the arm, and unreachable states at the states
+    let arm_checks = arms.iter().enumerate().map(|(index, arm)| {
+        let span = arm.pattern.span();
+        match level {
+            Level::Warn => {
+                quote_spanned! {span=> const _: () = Check::<{ matches_any(#index) }>::arm(); }
+            }
+            Level::Deny => quote_spanned! {span=>
+                const _: () = assert!(
+                    matches_any(#index),
+                    "this arm never matches, as earlier arms match every state and input it does"
+                );
+            },
+        }
+    });
+    let span = states.span();
+    let states_check = match level {
+        Level::Warn => {
+            quote_spanned! {span=> const _: () = Check::<{ all_reachable() }>::states(); }
+        }
+        Level::Deny => quote_spanned! {span=>
+            const _: () = assert!(
+                all_reachable(),
+                "a state can't be reached from a `start` state, or from another state if none are given"
+            );
+        },
+    };
+
+    let warnings = (level == Level::Warn).then(|| {
+        quote! {
+            /// Warnings for failed checks, as deprecations.
+            struct Check<const PASSED: bool>;
+
+            impl Check<true> {
+                const fn arm() {}
+                const fn states() {}
+            }
+
+            impl Check<false> {
+                #[deprecated(note = "this arm never matches, as earlier arms match every state and input it does")]
+                const fn arm() {}
+                #[deprecated(note = "a state can't be reached from a `start` state, or from another state if none are given")]
+                const fn states() {}
+            }
+        }
+    });
+
+    quote! {
+        #warnings
+
+        #(#arm_checks)*
+        #states_check
+    }
+}
+
+/// Get the `all_reachable` function, which uses the functions generated by [`expand`].
+fn all_reachable(start: Option<&Pat>) -> TokenStream {
+    let has_start = start.is_some();
+    let start = start.map_or_else(
+        || quote! { false },
+        |start| quote! { matches!(STATES[to], #start) },
+    );
+
+    quote! {
+        /// Returns `true` if every state can be reached from a start state,
+        /// or without start states, if every state can be reached from another state.
+        const fn all_reachable() -> bool {
+            // The states reached so far, starting from the start states
+            let mut reached = [false; STATES.len()];
+            let mut to = 0;
+            while to < STATES.len() {
+                reached[to] = #start;
+                to += 1;
+            }
+            let has_start = #has_start;
+
+            let mut changed = true;
+            while changed {
+                changed = false;
+
+                let mut from = 0;
+                while from < STATES.len() {
+                    if reached[from] || !has_start {
+                        let mut input = 0;
+                        while input < INPUTS.len() {
+                            let arm = matching_arm(STATES[from], INPUTS[input]);
+                            let mut to = 0;
+                            while to < STATES.len() {
+                                if !reached[to] && leads_to(arm, from, to) {
+                                    reached[to] = true;
+                                    changed = true;
+                                }
+                                to += 1;
+                            }
+                            input += 1;
+                        }
+                    }
+                    from += 1;
+                }
+            }
+
+            let mut state = 0;
+            while state < STATES.len() {
+                if !reached[state] {
+                    return false;
+                }
+                state += 1;
+            }
+            true
+        }
+    }
+}
+
+/// Expand a `ValidatedTransform!` macro.
+pub fn expand(transform: &Transform) -> TokenStream {
+    let Transform {
+        attributes,
+        krate,
+        level,
+        start,
+        name,
+        states,
+        domain,
+        arms,
+    } = transform;
+
+    let patterns = arms.iter().map(|arm| &arm.pattern).collect::<Vec<_>>();
+    let guards = arms
+        .iter()
+        .map(|arm| arm.guard.as_ref().map(|guard| quote! { if #guard }))
+        .collect::<Vec<_>>();
+    let results = arms.iter().map(|arm| &arm.result);
+    let indices = 0..arms.len();
+    let leads_to = arms.iter().map(leads_to);
+    let arm_indices = 0..arms.len();
+    let all_reachable = all_reachable(start.as_ref());
+
+    let checks = checks(*level, arms, states);
+
+    quote! {
+        #(#attributes)*
+        #[allow(clippy::missing_const_for_fn)]
+        fn #name(state: #states, input: #domain) -> #states {
+            match (state, input) {
+                #(#patterns #guards => #results),*
+            }
+        }
+
+        const _: () = {
+            const STATES: &[#states] = <#states as #krate::Finite>::VALUES;
+            const INPUTS: &[#domain] = <#domain as #krate::Finite>::VALUES;
+
+            /// Get the index of the arm that matches a state and input.
+            #[allow(unused_variables, unreachable_patterns)]
+            const fn matching_arm(state: #states, input: #domain) -> usize {
+                match (state, input) {
+                    #(#patterns #guards => #indices,)*
+                }
+            }
+
+            /// Returns `true` if the arm at index `arm` matches any state and input.
+            const fn matches_any(arm: usize) -> bool {
+                let mut state = 0;
+                while state < STATES.len() {
+                    let mut input = 0;
+                    while input < INPUTS.len() {
+                        if matching_arm(STATES[state], INPUTS[input]) == arm {
+                            return true;
+                        }
+                        input += 1;
+                    }
+                    state += 1;
+                }
+                false
+            }
+
+            /// Returns `true` if the arm at index `arm`, taken from the state at index `from`,
+            /// can lead to the state at index `to`.
+            #[allow(unused_variables, clippy::match_like_matches_macro)]
+            const fn leads_to(arm: usize, from: usize, to: usize) -> bool {
+                match arm {
+                    #(#arm_indices => #leads_to,)*
+                    _ => false,
+                }
+            }
+
+            #all_reachable
+
+            #checks
+        };
+    }
+}